@@ -270,4 +270,172 @@ pub enum StaderLiquidStakingError {
 
     #[msg("Capacity of the list must be not less than it's current size")]
     ShrinkingListWithDeletingContents, // 6086 0x17c6
+
+    #[msg("Signer is not a pending authority")]
+    InvalidPendingAuthority, // 6087 0x17c7
+
+    #[msg("No pending authority change")]
+    NoPendingAuthorityChange, // 6088 0x17c8
+
+    #[msg("Config delay is too high")]
+    ConfigDelayIsTooHigh, // 6089 0x17c9
+
+    #[msg("Pending config is not due yet")]
+    PendingConfigNotDue, // 6090 0x17ca
+
+    #[msg("No pending config")]
+    NoPendingConfig, // 6091 0x17cb
+
+    #[msg("Invalid fee authority")]
+    InvalidFeeAuthority, // 6092 0x17cc
+
+    #[msg("Invalid limits authority")]
+    InvalidLimitsAuthority, // 6093 0x17cd
+
+    #[msg("Invalid metadata authority")]
+    InvalidMetadataAuthority, // 6094 0x17ce
+
+    #[msg("Invalid LP config authority")]
+    InvalidLpConfigAuthority, // 6095 0x17cf
+
+    #[msg("Invalid pause groups mask")]
+    InvalidPauseGroups, // 6096 0x17d0
+
+    #[msg("Circuit breaker is not tripped")]
+    CircuitBreakerNotTripped, // 6097 0x17d1
+
+    #[msg("Groups are held by the circuit breaker")]
+    CircuitBreakerIsTripped, // 6098 0x17d2
+
+    #[msg("Unknown state account layout")]
+    UnknownStateLayout, // 6099 0x17d3
+
+    #[msg("State account is already migrated")]
+    StateAlreadyMigrated, // 6100 0x17d4
+
+    #[msg("Ticket wait epochs is too high")]
+    TicketWaitEpochsIsTooHigh, // 6101 0x17d5
+
+    #[msg("Ticket extra wait seconds is too high")]
+    TicketExtraWaitSecondsIsTooHigh, // 6102 0x17d6
+
+    #[msg("Protocol is winding down")]
+    ProtocolIsWindingDown, // 6103 0x17d7
+
+    #[msg("Protocol is not winding down")]
+    ProtocolIsNotWindingDown, // 6104 0x17d8
+
+    #[msg("Stakes are not withdrawn yet")]
+    StakesAreNotWithdrawn, // 6105 0x17d9
+
+    #[msg("Invalid reserve surplus policy")]
+    InvalidReserveSurplusPolicy, // 6106 0x17da
+
+    #[msg("Validator score updates are empty")]
+    EmptyValidatorScoreUpdates, // 6107 0x17db
+
+    #[msg("Invalid vote account")]
+    InvalidVoteAccount, // 6108 0x17dc
+
+    #[msg("Invalid validator scoring config")]
+    InvalidValidatorScoringConfig, // 6109 0x17dd
+
+    #[msg("On-chain validator scoring is disabled")]
+    ValidatorScoringDisabled, // 6110 0x17de
+
+    #[msg("Max validator stake share is too low")]
+    MaxValidatorStakeShareIsTooLow, // 6111 0x17df
+
+    #[msg("Validator stake share exceeded")]
+    ValidatorStakeShareExceeded, // 6112 0x17e0
+
+    #[msg("Invalid max validator commission")]
+    InvalidMaxValidatorCommission, // 6113 0x17e1
+
+    #[msg("Validator commission is too high")]
+    ValidatorCommissionIsTooHigh, // 6114 0x17e2
+
+    #[msg("Validator commission is not too high")]
+    ValidatorCommissionIsNotTooHigh, // 6115 0x17e3
+
+    #[msg("Validator is blocklisted")]
+    ValidatorIsBlocklisted, // 6116 0x17e4
+
+    #[msg("Wrong directed stake account")]
+    WrongDirectedStakeAccount, // 6117 0x17e5
+
+    #[msg("Directed stake requires validator list and directed stake accounts")]
+    MissingDirectedStakeAccounts, // 6118 0x17e6
+
+    #[msg("Applicant is neither the vote account withdrawer nor its node identity")]
+    InvalidValidatorApplicant, // 6119 0x17e7
+
+    #[msg("Validator list records already have metadata")]
+    ValidatorListAlreadyMigrated, // 6120 0x17e8
+
+    #[msg("Invalid delinquent unstake epochs")]
+    InvalidDelinquentUnstakeEpochs, // 6121 0x17e9
+
+    #[msg("Validator is not delinquent")]
+    ValidatorIsNotDelinquent, // 6122 0x17ea
+
+    #[msg("List item is already removed")]
+    ListItemIsRemoved, // 6123 0x17eb
+
+    #[msg("Lists can be compacted only while cranks and stake moves are paused")]
+    ListCompactionNotSafe, // 6124 0x17ec
+
+    #[msg("Stake reserve candidates must be unique with their accounts in remaining accounts")]
+    InvalidStakeReserveCandidates, // 6125 0x17ed
+
+    #[msg("Update batch needs one stake account in remaining accounts per entry")]
+    InvalidUpdateBatch, // 6126 0x17ee
+
+    #[msg("Stale price grace seconds is too high")]
+    StalePriceGraceSecondsIsTooHigh, // 6127 0x17ef
+
+    #[msg("Stale price fee is too high")]
+    StalePriceFeeIsTooHigh, // 6128 0x17f0
+
+    #[msg("staderSOL price is stale until the stake accounts of the epoch are updated")]
+    PriceIsStale, // 6129 0x17f1
+
+    #[msg("Crank reward is too high")]
+    CrankRewardIsTooHigh, // 6130 0x17f2
+
+    #[msg("Pending config was queued by another authority")]
+    PendingConfigQueuedByOtherAuthority, // 6131 0x17f3
+
+    #[msg("Signer is not the admin, fee or limits authority")]
+    InvalidConfigAuthority, // 6132 0x17f4
+
+    #[msg("Ticket wait epochs is too low")]
+    TicketWaitEpochsIsTooLow, // 6133 0x17f5
+
+    #[msg("Config history account is required")]
+    MissingConfigHistory, // 6134 0x17f6
+
+    #[msg("Validator score was set by the manager, see release_validator_score")]
+    ValidatorScoreIsManual, // 6135 0x17f7
+
+    #[msg("Unknown blocklist reason")]
+    InvalidBlocklistReason, // 6136 0x17f8
+
+    #[msg("Can not direct stake to a validator with score 0")]
+    DirectedValidatorHasZeroScore, // 6137 0x17f9
+
+    #[msg("Directed stake would exceed max_directed_stake_share of the active balance")]
+    DirectedStakeCapExceeded, // 6138 0x17fa
+
+    #[msg("Validator is already in the list")]
+    ValidatorAlreadyAdded, // 6139 0x17fb
+
+    #[msg("Directed stake was fully unwound, its shares must be released first")]
+    DirectedStakeIsUnwound, // 6140 0x17fc
+
+    #[msg("Proposed authority can not be the default key, see cancel_authority_change")]
+    InvalidProposedAuthority, // 6141 0x17fd
+
+    #[msg("Pending config account is required to queue timelocked params, see init_pending_config")]
+    MissingPendingConfig, // 6142 0x17fe
 }
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::instructions::InitializeData;
+use crate::instructions::{ConfigLpParams, ConfigStaderParams, InitializeData};
 
 use super::{
     BoolValueChange, FeeCentsValueChange, FeeValueChange, PubkeyValueChange, U64ValueChange,
@@ -9,13 +9,38 @@ use super::{
 #[event]
 pub struct ChangeAuthorityEvent {
     pub state: Pubkey,
-    pub admin_change: Option<PubkeyValueChange>,
-    pub validator_manager_change: Option<PubkeyValueChange>,
     pub operational_sol_account_change: Option<PubkeyValueChange>,
     pub treasury_stader_sol_account_change: Option<PubkeyValueChange>,
+    pub fee_authority_change: Option<PubkeyValueChange>,
+    pub limits_authority_change: Option<PubkeyValueChange>,
+    pub metadata_authority_change: Option<PubkeyValueChange>,
+    pub lp_config_authority_change: Option<PubkeyValueChange>,
+}
+
+#[event]
+pub struct ChangeAuthorityProposedEvent {
+    pub state: Pubkey,
+    pub admin_change: Option<PubkeyValueChange>,
+    pub validator_manager_change: Option<PubkeyValueChange>,
     pub pause_authority_change: Option<PubkeyValueChange>,
 }
 
+#[event]
+pub struct ChangeAuthorityAcceptedEvent {
+    pub state: Pubkey,
+    pub admin_change: Option<PubkeyValueChange>,
+    pub validator_manager_change: Option<PubkeyValueChange>,
+    pub pause_authority_change: Option<PubkeyValueChange>,
+}
+
+#[event]
+pub struct ChangeAuthorityCancelledEvent {
+    pub state: Pubkey,
+    pub cancelled_admin: Option<Pubkey>,
+    pub cancelled_validator_manager: Option<Pubkey>,
+    pub cancelled_pause_authority: Option<Pubkey>,
+}
+
 #[event]
 pub struct ConfigLpEvent {
     pub state: Pubkey,
@@ -39,6 +64,36 @@ pub struct ConfigStaderLiquidStakingEvent {
     pub delayed_unstake_fee_change: Option<FeeCentsValueChange>,
     pub withdraw_stake_account_fee_change: Option<FeeCentsValueChange>,
     pub max_stake_moved_per_epoch_change: Option<FeeValueChange>,
+    pub config_delay_epochs_change: Option<U64ValueChange>,
+    pub ticket_wait_epochs_change: Option<U64ValueChange>,
+    pub ticket_extra_wait_seconds_change: Option<U64ValueChange>,
+    pub reserve_surplus_policy_change: Option<U64ValueChange>,
+    pub max_validator_stake_share_change: Option<FeeValueChange>,
+    pub max_validator_commission_change: Option<U64ValueChange>,
+    pub delinquent_unstake_epochs_change: Option<U64ValueChange>,
+    pub stale_price_grace_seconds_change: Option<U64ValueChange>,
+    pub stale_price_fee_change: Option<FeeCentsValueChange>,
+    pub crank_reward_lamports_change: Option<U64ValueChange>,
+    pub max_crank_rewards_per_epoch_change: Option<U64ValueChange>,
+    pub rebalance_tolerance_change: Option<FeeValueChange>,
+    pub slashing_threshold_change: Option<FeeValueChange>,
+    pub max_directed_stake_share_change: Option<FeeValueChange>,
+}
+
+/// params waiting for the timelock, including the ones queued before
+#[event]
+pub struct QueueConfigEvent {
+    pub state: Pubkey,
+    pub stader_params: ConfigStaderParams,
+    pub lp_params: ConfigLpParams,
+    pub earliest_execution_epoch: u64,
+}
+
+#[event]
+pub struct CancelPendingConfigEvent {
+    pub state: Pubkey,
+    pub stader_params: ConfigStaderParams,
+    pub lp_params: ConfigLpParams,
 }
 
 // TODO: ConfigValidatorSystemEvent?
@@ -59,11 +114,41 @@ pub struct InitializeEvent {
 #[event]
 pub struct EmergencyPauseEvent {
     pub state: Pubkey,
+    pub paused_groups: u8, // groups paused by this instruction
+    pub old_paused_groups: u8,
+    pub new_paused_groups: u8,
 }
 
 #[event]
 pub struct ResumeEvent {
     pub state: Pubkey,
+    pub resumed_groups: u8, // groups resumed by this instruction
+    pub old_paused_groups: u8,
+    pub new_paused_groups: u8,
+}
+
+#[event]
+pub struct StartWindDownEvent {
+    pub state: Pubkey,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct ResetCircuitBreakerEvent {
+    pub state: Pubkey,
+    pub reason: u8,
+    pub tripped_epoch: u64,
+    pub resumed_groups: u8,
+    pub new_paused_groups: u8,
+}
+
+#[event]
+pub struct MigrateStateEvent {
+    pub state: Pubkey,
+    pub old_version: u8,
+    pub new_version: u8,
+    pub old_len: u32,
+    pub new_len: u32,
 }
 
 #[event]
@@ -79,3 +164,14 @@ pub struct ReallocStakeListEvent {
     pub count: u32,
     pub new_capacity: u32,
 }
+
+#[event]
+pub struct MigrateValidatorListEvent {
+    pub state: Pubkey,
+    pub count: u32,
+    pub old_record_size: u32,
+    pub new_record_size: u32, // old_record_size until the account reaches the full size
+    pub new_capacity: u32,
+    pub old_len: u32,
+    pub new_len: u32,
+}
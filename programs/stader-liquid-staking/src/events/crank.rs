@@ -10,6 +10,37 @@ pub struct SplitStakeAccountInfo {
     pub index: u32,
 }
 
+#[event]
+pub struct CircuitBreakerTrippedEvent {
+    pub state: Pubkey,
+    pub reason: u8, // CircuitBreaker::REASON_*
+    pub epoch: u64,
+    pub expected: u64,
+    pub observed: u64,
+    pub tripped_groups: u8,
+    pub paused_groups: u8,
+}
+
+#[event]
+pub struct ReserveSurplusDonationEvent {
+    pub state: Pubkey,
+    pub epoch: u64,
+    pub amount: u64,
+    pub stader_sol_price_change: U64ValueChange,
+    // staderSOLprice used
+    pub total_virtual_staked_lamports: u64,
+    pub stader_sol_supply: u64,
+}
+
+#[event]
+pub struct ReserveSurplusSweptEvent {
+    pub state: Pubkey,
+    pub epoch: u64,
+    pub amount: u64,
+    pub operational_sol_account: Pubkey,
+    pub operational_sol_balance: u64,
+}
+
 #[event]
 pub struct DeactivateStakeEvent {
     pub state: Pubkey,
@@ -71,6 +102,28 @@ pub struct RedelegateEvent {
     pub redelegate_stake_account: Pubkey,
 }
 
+#[event]
+pub struct RebalanceEvent {
+    pub state: Pubkey,
+    pub epoch: u64,
+    pub stake_index: u32,
+    pub stake_account: Pubkey,
+    pub source_validator_index: u32,
+    pub source_validator_vote: Pubkey,
+    pub source_validator_stake_target: u64,
+    // lamports over the stake target, before and after the move
+    pub source_validator_gap: U64ValueChange,
+    pub dest_validator_index: u32,
+    pub dest_validator_vote: Pubkey,
+    pub dest_validator_stake_target: u64,
+    // lamports under the stake target, before and after the move
+    pub dest_validator_gap: U64ValueChange,
+    pub amount: u64,
+    pub split_stake_account: Option<SplitStakeAccountInfo>, // None if the whole stake is moved
+    pub redelegate_stake_index: u32,
+    pub redelegate_stake_account: Pubkey,
+}
+
 #[event]
 pub struct StakeReserveEvent {
     pub state: Pubkey,
@@ -125,3 +178,70 @@ pub struct UpdateDeactivatedEvent {
     pub total_virtual_staked_lamports: u64,
     pub stader_sol_supply: u64,
 }
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateActiveStakeDetails {
+    pub stake_index: u32,
+    pub stake_account: Pubkey,
+    pub validator_index: u32,
+    pub validator_vote: Pubkey,
+    pub delegation_change: U64ValueChange,
+    pub extra_lamports: u64,
+    pub validator_active_balance: u64,
+}
+
+#[event]
+pub struct UpdateActiveBatchEvent {
+    pub state: Pubkey,
+    pub epoch: u64,
+    pub stakes: Vec<UpdateActiveStakeDetails>,
+    /// delegation growth and extra lamports of all the stake accounts
+    pub total_rewards: u64,
+    pub stader_sol_fees: Option<u64>,
+    pub total_active_balance: u64,
+    pub stader_sol_price_change: U64ValueChange,
+    pub reward_fee_used: Fee,
+    // staderSOLprice used
+    pub total_virtual_staked_lamports: u64,
+    pub stader_sol_supply: u64,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateDeactivatedStakeDetails {
+    pub stake_index: u32,
+    pub stake_account: Pubkey,
+    pub balance_without_rent_exempt: u64,
+    pub last_update_delegated_lamports: u64,
+}
+
+#[event]
+pub struct UpdateDeactivatedBatchEvent {
+    pub state: Pubkey,
+    pub epoch: u64,
+    pub stakes: Vec<UpdateDeactivatedStakeDetails>,
+    pub total_rewards: u64,
+    pub stader_sol_fees: Option<u64>,
+    pub stader_sol_price_change: U64ValueChange,
+    pub reward_fee_used: Fee,
+    pub operational_sol_balance: u64,
+    // staderSOLprice used
+    pub total_virtual_staked_lamports: u64,
+    pub stader_sol_supply: u64,
+}
+
+#[event]
+pub struct CrankRewardEvent {
+    pub state: Pubkey,
+    pub epoch: u64,
+    pub receiver: Pubkey,
+    pub amount: u64,
+    pub crank_rewards_paid_in_epoch: u64,
+}
+
+/// All the stake records were updated in the epoch, see State::is_epoch_fully_updated
+#[event]
+pub struct EpochFullyUpdatedEvent {
+    pub state: Pubkey,
+    pub epoch: u64,
+    pub updated_stake_count: u32,
+}
@@ -17,6 +17,9 @@ pub struct ClaimEvent {
 pub struct OrderUnstakeEvent {
     pub state: Pubkey,
     pub ticket_epoch: u64,
+    // the ticket can be claimed from ticket_due_epoch start + ticket_extra_wait_seconds
+    pub ticket_due_epoch: u64,
+    pub ticket_extra_wait_seconds: u64,
     pub ticket: Pubkey,
     pub beneficiary: Pubkey,
     pub circulating_ticket_balance: u64,
@@ -28,4 +31,6 @@ pub struct OrderUnstakeEvent {
     // staderSOLprice used
     pub total_virtual_staked_lamports: u64,
     pub stader_sol_supply: u64,
+    // taken while the price is stale, see State::stale_price_fee
+    pub stale_price_fee: u64,
 }
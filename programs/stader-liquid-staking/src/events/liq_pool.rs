@@ -26,7 +26,7 @@ pub struct LiquidUnstakeEvent {
     pub treasury_stader_sol_balance: Option<u64>,
     pub user_stader_sol_balance: u64,
     pub user_sol_balance: u64,
-    pub stader_sol_amount: u64,
+    pub stader_sol_amount: u64, // swapped, stale_price_fee not included
     pub stader_sol_fee: u64,
     pub treasury_stader_sol_cut: u64,
     pub sol_amount: u64,
@@ -35,6 +35,8 @@ pub struct LiquidUnstakeEvent {
     pub lp_max_fee: Fee,
     pub lp_min_fee: Fee,
     pub treasury_cut: Fee,
+    // staderSOL burned while the price is stale, see State::stale_price_fee
+    pub stale_price_fee: u64,
 }
 
 #[event]
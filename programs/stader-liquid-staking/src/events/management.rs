@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 
 use super::U32ValueChange;
+use crate::state::validator_system::ValidatorScoringConfig;
 
 #[event]
 pub struct AddValidatorEvent {
@@ -28,3 +29,128 @@ pub struct SetValidatorScoreEvent {
     pub index: u32,
     pub score_change: U32ValueChange,
 }
+
+#[event]
+pub struct ReleaseValidatorScoreEvent {
+    pub state: Pubkey,
+    pub validator: Pubkey,
+    pub index: u32,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct ValidatorScoreChange {
+    pub validator: Pubkey,
+    pub index: u32,
+    pub score_change: U32ValueChange,
+}
+
+#[event]
+pub struct SetValidatorScoresEvent {
+    pub state: Pubkey,
+    pub changes: Vec<ValidatorScoreChange>,
+    pub total_validator_score_change: U32ValueChange,
+}
+
+#[event]
+pub struct ConfigValidatorScoringEvent {
+    pub state: Pubkey,
+    pub old: ValidatorScoringConfig,
+    pub new: ValidatorScoringConfig,
+}
+
+#[event]
+pub struct RescoreValidatorEvent {
+    pub state: Pubkey,
+    pub epoch: u64,
+    pub validator: Pubkey,
+    pub index: u32,
+    pub score_change: U32ValueChange,
+    pub total_validator_score: u32,
+    // vote account data used
+    pub commission: u8,
+    pub last_vote_slot: Option<u64>,
+    pub last_epoch_credits: u64,
+}
+
+#[event]
+pub struct ZeroScoreHighCommissionEvent {
+    pub state: Pubkey,
+    pub epoch: u64,
+    pub validator: Pubkey,
+    pub index: u32,
+    pub score_change: U32ValueChange,
+    pub commission: u8,
+    pub max_validator_commission: u8,
+}
+
+#[event]
+pub struct BlockValidatorEvent {
+    pub state: Pubkey,
+    pub validator: Pubkey,
+    pub reason: u8,
+    pub blocked_epoch: u64,
+}
+
+#[event]
+pub struct UnblockValidatorEvent {
+    pub state: Pubkey,
+    pub validator: Pubkey,
+    pub reason: u8,
+    pub blocked_epoch: u64,
+    pub unblocked_epoch: u64,
+}
+
+#[event]
+pub struct ApplyValidatorEvent {
+    pub state: Pubkey,
+    pub validator: Pubkey,
+    pub applicant: Pubkey,
+    pub node_pubkey: Pubkey,
+    pub commission: u8,
+    pub last_epoch_credits: u64,
+    pub applied_epoch: u64,
+}
+
+#[event]
+pub struct ApproveValidatorApplicationEvent {
+    pub state: Pubkey,
+    pub validator: Pubkey,
+    pub index: u32,
+    pub score: u32,
+    pub applied_epoch: u64,
+}
+
+#[event]
+pub struct RejectValidatorApplicationEvent {
+    pub state: Pubkey,
+    pub validator: Pubkey,
+    pub applied_epoch: u64,
+}
+
+#[event]
+pub struct CancelValidatorApplicationEvent {
+    pub state: Pubkey,
+    pub validator: Pubkey,
+    pub applicant: Pubkey,
+    pub applied_epoch: u64,
+}
+
+#[event]
+pub struct UnstakeDelinquentValidatorEvent {
+    pub state: Pubkey,
+    pub epoch: u64,
+    pub validator: Pubkey,
+    pub validator_index: u32,
+    pub score_change: U32ValueChange,
+    pub stake_account: Pubkey,
+    pub stake_index: u32,
+    pub unstake_amount: u64,
+    pub delinquent_unstake_epochs: u8,
+}
+
+#[event]
+pub struct CompactListsEvent {
+    pub state: Pubkey,
+    pub validator_count_change: U32ValueChange,
+    pub stake_count_change: U32ValueChange,
+}
@@ -16,6 +16,8 @@ pub struct DepositStakeAccountEvent {
     // staderSOLprice used
     pub total_virtual_staked_lamports: u64,
     pub stader_sol_supply: u64,
+    // taken while the price is stale, see State::stale_price_fee
+    pub stale_price_fee: u64,
 }
 
 #[event]
@@ -34,6 +36,12 @@ pub struct DepositEvent {
     // staderSOLprice used
     pub total_virtual_staked_lamports: u64,
     pub stader_sol_supply: u64,
+    // the deposit value is directed to this validator when set
+    pub directed_validator: Option<Pubkey>,
+    pub preferred_validator_index: Option<u32>,
+    pub total_directed_lamports: u64,
+    // taken while the price is stale, see State::stale_price_fee
+    pub stale_price_fee: u64,
 }
 
 #[event]
@@ -56,4 +64,21 @@ pub struct WithdrawStakeAccountEvent {
     // staderSOLprice used
     pub total_virtual_staked_lamports: u64,
     pub stader_sol_supply: u64,
+    // taken while the price is stale, see State::stale_price_fee
+    pub stale_price_fee: u64,
+}
+
+#[event]
+pub struct WindDownRedeemEvent {
+    pub state: Pubkey,
+    pub epoch: u64,
+    pub user_stader_sol_balance: u64,
+    pub user_stader_sol_auth: Pubkey,
+    pub stader_sol_burned: u64,
+    pub sol_redeemed: u64,
+    pub transfer_sol_to: Pubkey,
+    pub reserve_balance: u64,
+    // staderSOLprice used
+    pub total_virtual_staked_lamports: u64,
+    pub stader_sol_supply: u64,
 }
@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::StaderLiquidStakingError,
+    events::{admin::ChangeAuthorityAcceptedEvent, PubkeyValueChange},
+    state::config_history::{ConfigHistory, ConfigParam, ConfigValueChange},
+    State,
+};
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    pub new_authority: Signer<'info>,
+    #[account(mut, has_one = state)]
+    pub config_history: Option<Box<Account<'info, ConfigHistory>>>,
+}
+
+impl<'info> AcceptAuthority<'info> {
+    /// finalizes every pending authority change proposed for the signer key
+    pub fn process(&mut self) -> Result<()> {
+        self.state
+            .check_config_history(self.config_history.as_ref().map(|h| h.key()))?;
+        let new_authority = self.new_authority.key();
+        require_keys_neq!(
+            new_authority,
+            Pubkey::default(),
+            StaderLiquidStakingError::InvalidPendingAuthority
+        );
+
+        let admin_change = if self.state.pending_admin_authority == new_authority {
+            let old = self.state.admin_authority;
+            self.state.admin_authority = new_authority;
+            self.state.pending_admin_authority = Pubkey::default();
+            Some(PubkeyValueChange {
+                old,
+                new: new_authority,
+            })
+        } else {
+            None
+        };
+
+        let validator_manager_change =
+            if self.state.pending_validator_manager_authority == new_authority {
+                let old = self.state.validator_system.manager_authority;
+                self.state.validator_system.manager_authority = new_authority;
+                self.state.pending_validator_manager_authority = Pubkey::default();
+                Some(PubkeyValueChange {
+                    old,
+                    new: new_authority,
+                })
+            } else {
+                None
+            };
+
+        let pause_authority_change = if self.state.pending_pause_authority == new_authority {
+            let old = self.state.pause_authority;
+            self.state.pause_authority = new_authority;
+            self.state.pending_pause_authority = Pubkey::default();
+            Some(PubkeyValueChange {
+                old,
+                new: new_authority,
+            })
+        } else {
+            None
+        };
+
+        require!(
+            admin_change.is_some()
+                || validator_manager_change.is_some()
+                || pause_authority_change.is_some(),
+            StaderLiquidStakingError::InvalidPendingAuthority
+        );
+
+        if let Some(config_history) = &mut self.config_history {
+            let pubkey_change =
+                |change: &Option<PubkeyValueChange>| change.clone().map(ConfigValueChange::Pubkey);
+            config_history.record(
+                &Clock::get()?,
+                new_authority,
+                [
+                    (ConfigParam::AdminAuthority, pubkey_change(&admin_change)),
+                    (
+                        ConfigParam::ValidatorManagerAuthority,
+                        pubkey_change(&validator_manager_change),
+                    ),
+                    (
+                        ConfigParam::PauseAuthority,
+                        pubkey_change(&pause_authority_change),
+                    ),
+                ],
+            );
+        }
+
+        emit!(ChangeAuthorityAcceptedEvent {
+            state: self.state.key(),
+            admin_change,
+            validator_manager_change,
+            pause_authority_change,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::StaderLiquidStakingError,
+    events::{admin::CancelPendingConfigEvent, U64ValueChange},
+    state::{
+        config_history::{ConfigHistory, ConfigParam, ConfigValueChange},
+        pending_config::PendingConfig,
+    },
+    State,
+};
+
+#[derive(Accounts)]
+pub struct CancelPendingConfig<'info> {
+    #[account(
+        has_one = admin_authority @ StaderLiquidStakingError::InvalidAdminAuthority
+    )]
+    pub state: Account<'info, State>,
+    pub admin_authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = state,
+        seeds = [
+            &state.key().to_bytes(),
+            PendingConfig::SEED
+        ],
+        bump
+    )]
+    pub pending_config: Account<'info, PendingConfig>,
+    #[account(mut, has_one = state)]
+    pub config_history: Option<Box<Account<'info, ConfigHistory>>>,
+}
+
+impl<'info> CancelPendingConfig<'info> {
+    pub fn process(&mut self) -> Result<()> {
+        self.state
+            .check_config_history(self.config_history.as_ref().map(|h| h.key()))?;
+        require!(
+            !self.pending_config.is_empty(),
+            StaderLiquidStakingError::NoPendingConfig
+        );
+
+        if let Some(config_history) = &mut self.config_history {
+            config_history.record(
+                &Clock::get()?,
+                self.admin_authority.key(),
+                [(
+                    ConfigParam::CanceledPendingConfig,
+                    Some(ConfigValueChange::U64(U64ValueChange {
+                        old: self.pending_config.earliest_execution_epoch,
+                        new: u64::MAX,
+                    })),
+                )],
+            );
+        }
+
+        emit!(CancelPendingConfigEvent {
+            state: self.state.key(),
+            stader_params: self.pending_config.stader_params,
+            lp_params: self.pending_config.lp_params,
+        });
+
+        self.pending_config.clear();
+        Ok(())
+    }
+}
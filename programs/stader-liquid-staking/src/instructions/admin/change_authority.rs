@@ -2,7 +2,13 @@ use anchor_lang::prelude::*;
 
 use crate::{
     error::StaderLiquidStakingError,
-    events::{admin::ChangeAuthorityEvent, PubkeyValueChange},
+    events::{
+        admin::{
+            ChangeAuthorityCancelledEvent, ChangeAuthorityEvent, ChangeAuthorityProposedEvent,
+        },
+        PubkeyValueChange,
+    },
+    state::config_history::{ConfigHistory, ConfigParam, ConfigValueChange},
     State,
 };
 
@@ -14,6 +20,8 @@ pub struct ChangeAuthority<'info> {
     )]
     pub state: Account<'info, State>,
     pub admin_authority: Signer<'info>,
+    #[account(mut, has_one = state)]
+    pub config_history: Option<Box<Account<'info, ConfigHistory>>>,
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
@@ -23,29 +31,57 @@ pub struct ChangeAuthorityData {
     pub operational_sol_account: Option<Pubkey>,
     pub treasury_stader_sol_account: Option<Pubkey>,
     pub pause_authority: Option<Pubkey>,
+    // narrower roles are assigned immediately, Some(Pubkey::default()) unsets the role
+    pub fee_authority: Option<Pubkey>,
+    pub limits_authority: Option<Pubkey>,
+    pub metadata_authority: Option<Pubkey>,
+    pub lp_config_authority: Option<Pubkey>,
 }
 
 impl<'info> ChangeAuthority<'info> {
     pub fn process(&mut self, data: ChangeAuthorityData) -> Result<()> {
+        self.state
+            .check_config_history(self.config_history.as_ref().map(|h| h.key()))?;
+        // signing authorities are only proposed here,
+        // the new key must sign accept_authority to finalize the change.
+        // The default key means no pending change, cancel_authority_change clears it
+        require!(
+            [data.admin, data.validator_manager, data.pause_authority]
+                .iter()
+                .all(|proposed| *proposed != Some(Pubkey::default())),
+            StaderLiquidStakingError::InvalidProposedAuthority
+        );
         let admin_change = if let Some(admin) = data.admin {
-            let old = self.state.admin_authority;
-            self.state.admin_authority = admin;
-            Some(PubkeyValueChange { old, new: admin })
+            self.state.pending_admin_authority = admin;
+            Some(PubkeyValueChange {
+                old: self.state.admin_authority,
+                new: admin,
+            })
         } else {
             None
         };
 
         let validator_manager_change = if let Some(validator_manager) = data.validator_manager {
-            let old = self.state.validator_system.manager_authority;
-            self.state.validator_system.manager_authority = validator_manager;
+            self.state.pending_validator_manager_authority = validator_manager;
             Some(PubkeyValueChange {
-                old,
+                old: self.state.validator_system.manager_authority,
                 new: validator_manager,
             })
         } else {
             None
         };
 
+        let pause_authority_change = if let Some(pause_authority) = data.pause_authority {
+            self.state.pending_pause_authority = pause_authority;
+            Some(PubkeyValueChange {
+                old: self.state.pause_authority,
+                new: pause_authority,
+            })
+        } else {
+            None
+        };
+
+        // plain accounts (not signers) are changed immediately
         let operational_sol_account_change =
             if let Some(operational_sol_account) = data.operational_sol_account {
                 let old = self.state.operational_sol_account;
@@ -70,24 +106,149 @@ impl<'info> ChangeAuthority<'info> {
                 None
             };
 
-        let pause_authority_change = if let Some(pause_authority) = data.pause_authority {
-            let old = self.state.pause_authority;
-            self.state.pause_authority = pause_authority;
-            Some(PubkeyValueChange {
-                old,
-                new: pause_authority,
+        let assign_role = |role: &mut Pubkey, new: Option<Pubkey>| {
+            new.map(|new| PubkeyValueChange {
+                old: std::mem::replace(role, new),
+                new,
             })
-        } else {
-            None
         };
+        let fee_authority_change = assign_role(&mut self.state.fee_authority, data.fee_authority);
+        let limits_authority_change =
+            assign_role(&mut self.state.limits_authority, data.limits_authority);
+        let metadata_authority_change =
+            assign_role(&mut self.state.metadata_authority, data.metadata_authority);
+        let lp_config_authority_change = assign_role(
+            &mut self.state.lp_config_authority,
+            data.lp_config_authority,
+        );
+
+        if let Some(config_history) = &mut self.config_history {
+            let pubkey_change =
+                |change: &Option<PubkeyValueChange>| change.clone().map(ConfigValueChange::Pubkey);
+            config_history.record(
+                &Clock::get()?,
+                self.admin_authority.key(),
+                [
+                    (
+                        ConfigParam::ProposedAdminAuthority,
+                        pubkey_change(&admin_change),
+                    ),
+                    (
+                        ConfigParam::ProposedValidatorManagerAuthority,
+                        pubkey_change(&validator_manager_change),
+                    ),
+                    (
+                        ConfigParam::ProposedPauseAuthority,
+                        pubkey_change(&pause_authority_change),
+                    ),
+                    (
+                        ConfigParam::OperationalSolAccount,
+                        pubkey_change(&operational_sol_account_change),
+                    ),
+                    (
+                        ConfigParam::TreasuryStaderSolAccount,
+                        pubkey_change(&treasury_stader_sol_account_change),
+                    ),
+                    (
+                        ConfigParam::FeeAuthority,
+                        pubkey_change(&fee_authority_change),
+                    ),
+                    (
+                        ConfigParam::LimitsAuthority,
+                        pubkey_change(&limits_authority_change),
+                    ),
+                    (
+                        ConfigParam::MetadataAuthority,
+                        pubkey_change(&metadata_authority_change),
+                    ),
+                    (
+                        ConfigParam::LpConfigAuthority,
+                        pubkey_change(&lp_config_authority_change),
+                    ),
+                ],
+            );
+        }
+
+        if admin_change.is_some()
+            || validator_manager_change.is_some()
+            || pause_authority_change.is_some()
+        {
+            emit!(ChangeAuthorityProposedEvent {
+                state: self.state.key(),
+                admin_change,
+                validator_manager_change,
+                pause_authority_change,
+            });
+        }
 
         emit!(ChangeAuthorityEvent {
             state: self.state.key(),
-            admin_change,
-            validator_manager_change,
             operational_sol_account_change,
             treasury_stader_sol_account_change,
-            pause_authority_change
+            fee_authority_change,
+            limits_authority_change,
+            metadata_authority_change,
+            lp_config_authority_change,
+        });
+
+        Ok(())
+    }
+
+    pub fn cancel(&mut self) -> Result<()> {
+        self.state
+            .check_config_history(self.config_history.as_ref().map(|h| h.key()))?;
+        require!(
+            self.state.has_pending_authority_change(),
+            StaderLiquidStakingError::NoPendingAuthorityChange
+        );
+
+        let take_pending = |pending: &mut Pubkey| {
+            let cancelled = std::mem::take(pending);
+            if cancelled != Pubkey::default() {
+                Some(cancelled)
+            } else {
+                None
+            }
+        };
+        let cancelled_admin = take_pending(&mut self.state.pending_admin_authority);
+        let cancelled_validator_manager =
+            take_pending(&mut self.state.pending_validator_manager_authority);
+        let cancelled_pause_authority = take_pending(&mut self.state.pending_pause_authority);
+
+        if let Some(config_history) = &mut self.config_history {
+            let cancelled_change = |cancelled: Option<Pubkey>| {
+                cancelled.map(|old| {
+                    ConfigValueChange::Pubkey(PubkeyValueChange {
+                        old,
+                        new: Pubkey::default(),
+                    })
+                })
+            };
+            config_history.record(
+                &Clock::get()?,
+                self.admin_authority.key(),
+                [
+                    (
+                        ConfigParam::ProposedAdminAuthority,
+                        cancelled_change(cancelled_admin),
+                    ),
+                    (
+                        ConfigParam::ProposedValidatorManagerAuthority,
+                        cancelled_change(cancelled_validator_manager),
+                    ),
+                    (
+                        ConfigParam::ProposedPauseAuthority,
+                        cancelled_change(cancelled_pause_authority),
+                    ),
+                ],
+            );
+        }
+
+        emit!(ChangeAuthorityCancelledEvent {
+            state: self.state.key(),
+            cancelled_admin,
+            cancelled_validator_manager,
+            cancelled_pause_authority,
         });
 
         Ok(())
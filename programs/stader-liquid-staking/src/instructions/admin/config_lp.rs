@@ -1,8 +1,11 @@
 use anchor_lang::prelude::*;
 
 use crate::{
-    events::{admin::ConfigLpEvent, FeeValueChange, U64ValueChange},
-    state::Fee,
+    events::{
+        admin::{ConfigLpEvent, QueueConfigEvent},
+        FeeValueChange, U64ValueChange,
+    },
+    state::{config_history::ConfigHistory, pending_config::PendingConfig, Fee},
     StaderLiquidStakingError, State,
 };
 
@@ -14,73 +17,178 @@ pub struct ConfigLpParams {
     pub treasury_cut: Option<Fee>,
 }
 
+impl ConfigLpParams {
+    /// Splits the params into the ones that can be applied right away and the ones
+    /// that must wait for the config timelock.
+    /// Lowering any point of the fee curve only makes liquid unstake cheaper, so it is immediate
+    pub fn split_timelocked(self, state: &State) -> (Self, Self) {
+        fn split<T: PartialOrd>(value: Option<T>, current: T) -> (Option<T>, Option<T>) {
+            match value {
+                Some(v) if v > current => (None, Some(v)),
+                v => (v, None),
+            }
+        }
+        let (min_fee, queued_min_fee) = split(self.min_fee, state.liq_pool.lp_min_fee);
+        let (max_fee, queued_max_fee) = split(self.max_fee, state.liq_pool.lp_max_fee);
+        let (liquidity_target, queued_liquidity_target) =
+            split(self.liquidity_target, state.liq_pool.lp_liquidity_target);
+        let (treasury_cut, queued_treasury_cut) =
+            split(self.treasury_cut, state.liq_pool.treasury_cut);
+
+        (
+            Self {
+                min_fee,
+                max_fee,
+                liquidity_target,
+                treasury_cut,
+            },
+            Self {
+                min_fee: queued_min_fee,
+                max_fee: queued_max_fee,
+                liquidity_target: queued_liquidity_target,
+                treasury_cut: queued_treasury_cut,
+            },
+        )
+    }
+
+    /// Overwrites the queued values with the newly queued ones
+    pub fn merge(&mut self, other: Self) {
+        self.min_fee = other.min_fee.or(self.min_fee);
+        self.max_fee = other.max_fee.or(self.max_fee);
+        self.liquidity_target = other.liquidity_target.or(self.liquidity_target);
+        self.treasury_cut = other.treasury_cut.or(self.treasury_cut);
+    }
+}
+
 #[derive(Accounts)]
 pub struct ConfigLp<'info> {
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    #[account(
+        address = state.effective_lp_config_authority()
+            @ StaderLiquidStakingError::InvalidLpConfigAuthority
+    )]
+    pub lp_config_authority: Signer<'info>,
     #[account(
         mut,
-        has_one = admin_authority @ StaderLiquidStakingError::InvalidAdminAuthority
+        has_one = state,
+        seeds = [
+            &state.key().to_bytes(),
+            PendingConfig::SEED
+        ],
+        bump
     )]
-    pub state: Account<'info, State>,
-    pub admin_authority: Signer<'info>,
+    /// required only when some params are timelocked
+    pub pending_config: Option<Account<'info, PendingConfig>>,
+    pub clock: Sysvar<'info, Clock>,
+    #[account(mut, has_one = state)]
+    pub config_history: Option<Box<Account<'info, ConfigHistory>>>,
 }
 
 impl<'info> ConfigLp<'info> {
-    pub fn process(
-        &mut self,
-        ConfigLpParams {
-            min_fee,
-            max_fee,
-            liquidity_target,
-            treasury_cut,
-        }: ConfigLpParams,
-    ) -> Result<()> {
-        let min_fee_change = if let Some(min_fee) = min_fee {
-            let old = self.state.liq_pool.lp_min_fee;
-            self.state.liq_pool.lp_min_fee = min_fee;
-            Some(FeeValueChange { old, new: min_fee })
-        } else {
-            None
-        };
-
-        let max_fee_change = if let Some(max_fee) = max_fee {
-            let old = self.state.liq_pool.lp_max_fee;
-            self.state.liq_pool.lp_max_fee = max_fee;
-            Some(FeeValueChange { old, new: max_fee })
-        } else {
-            None
-        };
-
-        let liquidity_target_change = if let Some(liquidity_target) = liquidity_target {
-            let old = self.state.liq_pool.lp_liquidity_target;
-            self.state.liq_pool.lp_liquidity_target = liquidity_target;
-            Some(U64ValueChange {
-                old,
-                new: liquidity_target,
-            })
-        } else {
-            None
-        };
-
-        let treasury_cut_change = if let Some(treasury_cut) = treasury_cut {
-            let old = self.state.liq_pool.treasury_cut;
-            self.state.liq_pool.treasury_cut = treasury_cut;
-            Some(FeeValueChange {
-                old,
-                new: treasury_cut,
-            })
-        } else {
-            None
-        };
-
-        self.state.liq_pool.validate()?;
-
-        emit!(ConfigLpEvent {
-            state: self.state.key(),
-            min_fee_change,
-            max_fee_change,
-            liquidity_target_change,
-            treasury_cut_change
-        });
+    pub fn process(&mut self, params: ConfigLpParams) -> Result<()> {
+        self.state
+            .check_config_history(self.config_history.as_ref().map(|h| h.key()))?;
+        let (immediate, queued) = params.split_timelocked(&self.state);
+
+        if queued != ConfigLpParams::default() {
+            let pending_config = self
+                .pending_config
+                .as_mut()
+                .ok_or_else(|| error!(StaderLiquidStakingError::MissingPendingConfig))?;
+            // validate the resulting fee curve now, so the admin does not wait for the timelock to find out it is wrong
+            let mut resulting_params = pending_config.lp_params;
+            resulting_params.merge(queued);
+            let mut simulated_state = (*self.state).clone();
+            apply_config_lp(&mut simulated_state, self.state.key(), immediate)?;
+            apply_config_lp(&mut simulated_state, self.state.key(), resulting_params)?;
+
+            pending_config.restart_timelock(
+                self.lp_config_authority.key(),
+                self.clock.epoch,
+                self.state.config_delay_epochs,
+            )?;
+            pending_config.lp_params = resulting_params;
+            emit!(QueueConfigEvent {
+                state: self.state.key(),
+                stader_params: pending_config.stader_params,
+                lp_params: pending_config.lp_params,
+                earliest_execution_epoch: pending_config.earliest_execution_epoch,
+            });
+        }
+
+        let state_address = self.state.key();
+        let event = apply_config_lp(&mut self.state, state_address, immediate)?;
+        if let Some(config_history) = &mut self.config_history {
+            config_history.record(
+                &self.clock,
+                self.lp_config_authority.key(),
+                ConfigHistory::config_lp_changes(&event),
+            );
+        }
+        emit!(event);
+
         Ok(())
     }
 }
+
+/// Applies the params to the state and returns the event describing the changes.
+/// Used by config_lp for immediate changes and by execute_pending_config for timelocked ones
+pub fn apply_config_lp(
+    state: &mut State,
+    state_address: Pubkey,
+    ConfigLpParams {
+        min_fee,
+        max_fee,
+        liquidity_target,
+        treasury_cut,
+    }: ConfigLpParams,
+) -> Result<ConfigLpEvent> {
+    let min_fee_change = if let Some(min_fee) = min_fee {
+        let old = state.liq_pool.lp_min_fee;
+        state.liq_pool.lp_min_fee = min_fee;
+        Some(FeeValueChange { old, new: min_fee })
+    } else {
+        None
+    };
+
+    let max_fee_change = if let Some(max_fee) = max_fee {
+        let old = state.liq_pool.lp_max_fee;
+        state.liq_pool.lp_max_fee = max_fee;
+        Some(FeeValueChange { old, new: max_fee })
+    } else {
+        None
+    };
+
+    let liquidity_target_change = if let Some(liquidity_target) = liquidity_target {
+        let old = state.liq_pool.lp_liquidity_target;
+        state.liq_pool.lp_liquidity_target = liquidity_target;
+        Some(U64ValueChange {
+            old,
+            new: liquidity_target,
+        })
+    } else {
+        None
+    };
+
+    let treasury_cut_change = if let Some(treasury_cut) = treasury_cut {
+        let old = state.liq_pool.treasury_cut;
+        state.liq_pool.treasury_cut = treasury_cut;
+        Some(FeeValueChange {
+            old,
+            new: treasury_cut,
+        })
+    } else {
+        None
+    };
+
+    state.liq_pool.validate()?;
+
+    Ok(ConfigLpEvent {
+        state: state_address,
+        min_fee_change,
+        max_fee_change,
+        liquidity_target_change,
+        treasury_cut_change,
+    })
+}
@@ -3,8 +3,12 @@ use crate::events::{
     U64ValueChange,
 };
 use crate::{
+    events::admin::QueueConfigEvent,
     require_lte,
-    state::{stake_system::StakeSystem, Fee, FeeCents},
+    state::{
+        config_history::ConfigHistory, pending_config::PendingConfig, stake_system::StakeSystem,
+        Fee, FeeCents,
+    },
     StaderLiquidStakingError, State,
 };
 use anchor_lang::prelude::*;
@@ -22,212 +26,969 @@ pub struct ConfigStaderParams {
     pub delayed_unstake_fee: Option<FeeCents>,
     pub withdraw_stake_account_fee: Option<FeeCents>,
     pub max_stake_moved_per_epoch: Option<Fee>,
+    pub config_delay_epochs: Option<u64>,
+    pub ticket_wait_epochs: Option<u64>,
+    pub ticket_extra_wait_seconds: Option<u64>,
+    pub reserve_surplus_policy: Option<u8>,
+    pub max_validator_stake_share: Option<Fee>,
+    pub max_validator_commission: Option<u8>,
+    pub delinquent_unstake_epochs: Option<u8>,
+    pub stale_price_grace_seconds: Option<u64>,
+    pub stale_price_fee: Option<FeeCents>,
+    pub crank_reward_lamports: Option<u64>,
+    pub max_crank_rewards_per_epoch: Option<u64>,
+    pub rebalance_tolerance: Option<Fee>,
+    pub slashing_threshold: Option<Fee>,
+    pub max_directed_stake_share: Option<Fee>,
+}
+
+impl ConfigStaderParams {
+    /// Splits the params into the ones that can be applied right away and the ones
+    /// that must wait for the config timelock.
+    /// Fee, cap and timelock changes are timelocked unless they only tighten the current value
+    pub fn split_timelocked(self, state: &State) -> (Self, Self) {
+        fn split<T>(
+            value: Option<T>,
+            is_tightening: impl FnOnce(&T) -> bool,
+        ) -> (Option<T>, Option<T>) {
+            match value {
+                Some(v) if !is_tightening(&v) => (None, Some(v)),
+                v => (v, None),
+            }
+        }
+        let (rewards_fee, queued_rewards_fee) = split(self.rewards_fee, |v| *v <= state.reward_fee);
+        let (staking_sol_cap, queued_staking_sol_cap) =
+            split(self.staking_sol_cap, |v| *v <= state.staking_sol_cap);
+        let (liquidity_sol_cap, queued_liquidity_sol_cap) = split(self.liquidity_sol_cap, |v| {
+            *v <= state.liq_pool.liquidity_sol_cap
+        });
+        let (delayed_unstake_fee, queued_delayed_unstake_fee) =
+            split(self.delayed_unstake_fee, |v| {
+                *v <= state.delayed_unstake_fee
+            });
+        let (withdraw_stake_account_fee, queued_withdraw_stake_account_fee) =
+            split(self.withdraw_stake_account_fee, |v| {
+                *v <= state.withdraw_stake_account_fee
+            });
+        let (max_stake_moved_per_epoch, queued_max_stake_moved_per_epoch) =
+            split(self.max_stake_moved_per_epoch, |v| {
+                *v <= state.max_stake_moved_per_epoch
+            });
+        let (max_validator_stake_share, queued_max_validator_stake_share) =
+            split(self.max_validator_stake_share, |v| {
+                *v <= state.max_validator_stake_share
+            });
+        let (max_validator_commission, queued_max_validator_commission) =
+            split(self.max_validator_commission, |v| {
+                *v <= state.max_validator_commission
+            });
+        // disabling or requiring more epochs without credits makes the permissionless unstake harder
+        let (delinquent_unstake_epochs, queued_delinquent_unstake_epochs) =
+            split(self.delinquent_unstake_epochs, |v| {
+                *v == 0
+                    || (state.delinquent_unstake_epochs != 0
+                        && *v >= state.delinquent_unstake_epochs)
+            });
+        // a shorter stale price window or a lower fee only favors users
+        let (stale_price_grace_seconds, queued_stale_price_grace_seconds) =
+            split(self.stale_price_grace_seconds, |v| {
+                *v <= state.stale_price_grace_seconds
+            });
+        let (stale_price_fee, queued_stale_price_fee) =
+            split(self.stale_price_fee, |v| *v <= state.stale_price_fee);
+        // higher crank rewards drain the CrankRewardVault faster
+        let (crank_reward_lamports, queued_crank_reward_lamports) =
+            split(self.crank_reward_lamports, |v| {
+                *v <= state.crank_reward_lamports
+            });
+        let (max_crank_rewards_per_epoch, queued_max_crank_rewards_per_epoch) =
+            split(self.max_crank_rewards_per_epoch, |v| {
+                *v <= state.max_crank_rewards_per_epoch
+            });
+        // a lower tolerance lets anyone move more stake with rebalance
+        let (rebalance_tolerance, queued_rebalance_tolerance) =
+            split(self.rebalance_tolerance, |v| *v >= state.rebalance_tolerance);
+        // a lower threshold trips the circuit breaker sooner
+        let (slashing_threshold, queued_slashing_threshold) =
+            split(self.slashing_threshold, |v| *v <= state.slashing_threshold);
+        let (max_directed_stake_share, queued_max_directed_stake_share) =
+            split(self.max_directed_stake_share, |v| {
+                *v <= state.max_directed_stake_share
+            });
+        // a shorter ticket wait lets users claim before the unstaked stake is withdrawn
+        let (ticket_wait_epochs, queued_ticket_wait_epochs) =
+            split(self.ticket_wait_epochs, |v| *v >= state.ticket_wait_epochs);
+        let (ticket_extra_wait_seconds, queued_ticket_extra_wait_seconds) =
+            split(self.ticket_extra_wait_seconds, |v| {
+                *v <= state.ticket_extra_wait_seconds
+            });
+        // sending the surplus to operations instead of holders must wait
+        let (reserve_surplus_policy, queued_reserve_surplus_policy) =
+            split(self.reserve_surplus_policy, |v| {
+                *v == State::RESERVE_SURPLUS_DONATE
+            });
+        // a longer timelock only tightens, a shorter one must wait for the current timelock
+        let (config_delay_epochs, queued_config_delay_epochs) =
+            split(self.config_delay_epochs, |v| {
+                *v >= state.config_delay_epochs
+            });
+
+        (
+            Self {
+                rewards_fee,
+                staking_sol_cap,
+                liquidity_sol_cap,
+                delayed_unstake_fee,
+                withdraw_stake_account_fee,
+                max_stake_moved_per_epoch,
+                config_delay_epochs,
+                ticket_wait_epochs,
+                ticket_extra_wait_seconds,
+                reserve_surplus_policy,
+                max_validator_stake_share,
+                max_validator_commission,
+                delinquent_unstake_epochs,
+                stale_price_grace_seconds,
+                stale_price_fee,
+                crank_reward_lamports,
+                max_crank_rewards_per_epoch,
+                rebalance_tolerance,
+                slashing_threshold,
+                max_directed_stake_share,
+                ..self
+            },
+            Self {
+                rewards_fee: queued_rewards_fee,
+                staking_sol_cap: queued_staking_sol_cap,
+                liquidity_sol_cap: queued_liquidity_sol_cap,
+                delayed_unstake_fee: queued_delayed_unstake_fee,
+                withdraw_stake_account_fee: queued_withdraw_stake_account_fee,
+                max_stake_moved_per_epoch: queued_max_stake_moved_per_epoch,
+                config_delay_epochs: queued_config_delay_epochs,
+                ticket_wait_epochs: queued_ticket_wait_epochs,
+                ticket_extra_wait_seconds: queued_ticket_extra_wait_seconds,
+                reserve_surplus_policy: queued_reserve_surplus_policy,
+                max_validator_stake_share: queued_max_validator_stake_share,
+                max_validator_commission: queued_max_validator_commission,
+                delinquent_unstake_epochs: queued_delinquent_unstake_epochs,
+                stale_price_grace_seconds: queued_stale_price_grace_seconds,
+                stale_price_fee: queued_stale_price_fee,
+                crank_reward_lamports: queued_crank_reward_lamports,
+                max_crank_rewards_per_epoch: queued_max_crank_rewards_per_epoch,
+                rebalance_tolerance: queued_rebalance_tolerance,
+                slashing_threshold: queued_slashing_threshold,
+                max_directed_stake_share: queued_max_directed_stake_share,
+                ..Self::default()
+            },
+        )
+    }
+
+    pub const FEE_ROLE: u8 = 1 << 0;
+    pub const LIMITS_ROLE: u8 = 1 << 1;
+    pub const ADMIN_ROLE: u8 = 1 << 2;
+
+    /// Bitmask of the *_ROLE authorities needed for the params set.
+    /// Every field is destructured and must be used, so a new param does not compile without a role
+    #[deny(unused_variables)]
+    pub fn changed_roles(&self) -> u8 {
+        fn role<T>(value: &Option<T>, role: u8) -> u8 {
+            if value.is_some() {
+                role
+            } else {
+                0
+            }
+        }
+        let Self {
+            rewards_fee,
+            slots_for_stake_delta,
+            min_stake,
+            min_deposit,
+            min_withdraw,
+            staking_sol_cap,
+            liquidity_sol_cap,
+            withdraw_stake_account_enabled,
+            delayed_unstake_fee,
+            withdraw_stake_account_fee,
+            max_stake_moved_per_epoch,
+            config_delay_epochs,
+            ticket_wait_epochs,
+            ticket_extra_wait_seconds,
+            reserve_surplus_policy,
+            max_validator_stake_share,
+            max_validator_commission,
+            delinquent_unstake_epochs,
+            stale_price_grace_seconds,
+            stale_price_fee,
+            crank_reward_lamports,
+            max_crank_rewards_per_epoch,
+            rebalance_tolerance,
+            slashing_threshold,
+            max_directed_stake_share,
+        } = self;
+        role(rewards_fee, Self::FEE_ROLE)
+            | role(slots_for_stake_delta, Self::LIMITS_ROLE)
+            | role(min_stake, Self::LIMITS_ROLE)
+            | role(min_deposit, Self::LIMITS_ROLE)
+            | role(min_withdraw, Self::LIMITS_ROLE)
+            | role(staking_sol_cap, Self::LIMITS_ROLE)
+            | role(liquidity_sol_cap, Self::LIMITS_ROLE)
+            | role(withdraw_stake_account_enabled, Self::LIMITS_ROLE)
+            | role(delayed_unstake_fee, Self::FEE_ROLE)
+            | role(withdraw_stake_account_fee, Self::FEE_ROLE)
+            | role(max_stake_moved_per_epoch, Self::LIMITS_ROLE)
+            // the timelock itself protects against the other roles
+            | role(config_delay_epochs, Self::ADMIN_ROLE)
+            | role(ticket_wait_epochs, Self::LIMITS_ROLE)
+            | role(ticket_extra_wait_seconds, Self::LIMITS_ROLE)
+            // where the reserve surplus goes
+            | role(reserve_surplus_policy, Self::ADMIN_ROLE)
+            | role(max_validator_stake_share, Self::LIMITS_ROLE)
+            | role(max_validator_commission, Self::LIMITS_ROLE)
+            | role(delinquent_unstake_epochs, Self::LIMITS_ROLE)
+            | role(stale_price_grace_seconds, Self::LIMITS_ROLE)
+            | role(stale_price_fee, Self::FEE_ROLE)
+            | role(crank_reward_lamports, Self::LIMITS_ROLE)
+            | role(max_crank_rewards_per_epoch, Self::LIMITS_ROLE)
+            | role(rebalance_tolerance, Self::LIMITS_ROLE)
+            | role(slashing_threshold, Self::LIMITS_ROLE)
+            | role(max_directed_stake_share, Self::LIMITS_ROLE)
+    }
+
+    /// Overwrites the queued values with the newly queued ones
+    pub fn merge(&mut self, other: Self) {
+        self.rewards_fee = other.rewards_fee.or(self.rewards_fee);
+        self.slots_for_stake_delta = other.slots_for_stake_delta.or(self.slots_for_stake_delta);
+        self.min_stake = other.min_stake.or(self.min_stake);
+        self.min_deposit = other.min_deposit.or(self.min_deposit);
+        self.min_withdraw = other.min_withdraw.or(self.min_withdraw);
+        self.staking_sol_cap = other.staking_sol_cap.or(self.staking_sol_cap);
+        self.liquidity_sol_cap = other.liquidity_sol_cap.or(self.liquidity_sol_cap);
+        self.withdraw_stake_account_enabled = other
+            .withdraw_stake_account_enabled
+            .or(self.withdraw_stake_account_enabled);
+        self.delayed_unstake_fee = other.delayed_unstake_fee.or(self.delayed_unstake_fee);
+        self.withdraw_stake_account_fee = other
+            .withdraw_stake_account_fee
+            .or(self.withdraw_stake_account_fee);
+        self.max_stake_moved_per_epoch = other
+            .max_stake_moved_per_epoch
+            .or(self.max_stake_moved_per_epoch);
+        self.config_delay_epochs = other.config_delay_epochs.or(self.config_delay_epochs);
+        self.ticket_wait_epochs = other.ticket_wait_epochs.or(self.ticket_wait_epochs);
+        self.ticket_extra_wait_seconds = other
+            .ticket_extra_wait_seconds
+            .or(self.ticket_extra_wait_seconds);
+        self.reserve_surplus_policy = other.reserve_surplus_policy.or(self.reserve_surplus_policy);
+        self.max_validator_stake_share = other
+            .max_validator_stake_share
+            .or(self.max_validator_stake_share);
+        self.max_validator_commission = other
+            .max_validator_commission
+            .or(self.max_validator_commission);
+        self.delinquent_unstake_epochs = other
+            .delinquent_unstake_epochs
+            .or(self.delinquent_unstake_epochs);
+        self.stale_price_grace_seconds = other
+            .stale_price_grace_seconds
+            .or(self.stale_price_grace_seconds);
+        self.stale_price_fee = other.stale_price_fee.or(self.stale_price_fee);
+        self.crank_reward_lamports = other.crank_reward_lamports.or(self.crank_reward_lamports);
+        self.max_crank_rewards_per_epoch = other
+            .max_crank_rewards_per_epoch
+            .or(self.max_crank_rewards_per_epoch);
+        self.rebalance_tolerance = other.rebalance_tolerance.or(self.rebalance_tolerance);
+        self.slashing_threshold = other.slashing_threshold.or(self.slashing_threshold);
+        self.max_directed_stake_share = other.max_directed_stake_share.or(self.max_directed_stake_share);
+    }
 }
 
 #[derive(Accounts)]
 pub struct ConfigStader<'info> {
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    /// fee, limits or admin authority, depending on the params being changed
+    pub authority: Signer<'info>,
     #[account(
         mut,
-        has_one = admin_authority @ StaderLiquidStakingError::InvalidAdminAuthority
+        has_one = state,
+        seeds = [
+            &state.key().to_bytes(),
+            PendingConfig::SEED
+        ],
+        bump
     )]
-    pub state: Account<'info, State>,
-    pub admin_authority: Signer<'info>,
+    /// required only when some params are timelocked
+    pub pending_config: Option<Account<'info, PendingConfig>>,
+    pub clock: Sysvar<'info, Clock>,
+    #[account(mut, has_one = state)]
+    pub config_history: Option<Box<Account<'info, ConfigHistory>>>,
 }
 
 impl<'info> ConfigStader<'info> {
-    pub fn process(
-        &mut self,
-        ConfigStaderParams {
+    pub fn process(&mut self, params: ConfigStaderParams) -> Result<()> {
+        self.state
+            .check_config_history(self.config_history.as_ref().map(|h| h.key()))?;
+        let authority = self.authority.key();
+        require!(
+            authority == self.state.admin_authority
+                || authority == self.state.effective_fee_authority()
+                || authority == self.state.effective_limits_authority(),
+            StaderLiquidStakingError::InvalidConfigAuthority
+        );
+        let roles = params.changed_roles();
+        if roles & ConfigStaderParams::FEE_ROLE != 0 {
+            require_keys_eq!(
+                authority,
+                self.state.effective_fee_authority(),
+                StaderLiquidStakingError::InvalidFeeAuthority
+            );
+        }
+        if roles & ConfigStaderParams::LIMITS_ROLE != 0 {
+            require_keys_eq!(
+                authority,
+                self.state.effective_limits_authority(),
+                StaderLiquidStakingError::InvalidLimitsAuthority
+            );
+        }
+        if roles & ConfigStaderParams::ADMIN_ROLE != 0 {
+            require_keys_eq!(
+                authority,
+                self.state.admin_authority,
+                StaderLiquidStakingError::InvalidAdminAuthority
+            );
+        }
+
+        let (immediate, queued) = params.split_timelocked(&self.state);
+
+        if queued != ConfigStaderParams::default() {
+            let pending_config = self
+                .pending_config
+                .as_mut()
+                .ok_or_else(|| error!(StaderLiquidStakingError::MissingPendingConfig))?;
+            // restart the timelock before the params merge, so the pending state is checked as is
+            pending_config.restart_timelock(
+                self.authority.key(),
+                self.clock.epoch,
+                self.state.config_delay_epochs,
+            )?;
+            // validate the resulting params now, so the admin does not wait for the timelock to find out they are wrong
+            let mut resulting_params = pending_config.stader_params;
+            resulting_params.merge(queued);
+            let mut simulated_state = (*self.state).clone();
+            apply_config_stader(&mut simulated_state, self.state.key(), immediate)?;
+            apply_config_stader(&mut simulated_state, self.state.key(), resulting_params)?;
+
+            pending_config.stader_params = resulting_params;
+            emit!(QueueConfigEvent {
+                state: self.state.key(),
+                stader_params: pending_config.stader_params,
+                lp_params: pending_config.lp_params,
+                earliest_execution_epoch: pending_config.earliest_execution_epoch,
+            });
+        }
+
+        let state_address = self.state.key();
+        let event = apply_config_stader(&mut self.state, state_address, immediate)?;
+        if let Some(config_history) = &mut self.config_history {
+            config_history.record(
+                &self.clock,
+                self.authority.key(),
+                ConfigHistory::config_stader_changes(&event),
+            );
+        }
+        emit!(event);
+
+        Ok(())
+    }
+}
+
+/// Applies the params to the state and returns the event describing the changes.
+/// Used by config_stader for immediate changes and by execute_pending_config for timelocked ones
+pub fn apply_config_stader(
+    state: &mut State,
+    state_address: Pubkey,
+    ConfigStaderParams {
+        rewards_fee,
+        slots_for_stake_delta,
+        min_stake,
+        min_deposit,
+        min_withdraw,
+        staking_sol_cap,
+        liquidity_sol_cap,
+        withdraw_stake_account_enabled,
+        delayed_unstake_fee,
+        withdraw_stake_account_fee,
+        max_stake_moved_per_epoch,
+        config_delay_epochs,
+        ticket_wait_epochs,
+        ticket_extra_wait_seconds,
+        reserve_surplus_policy,
+        max_validator_stake_share,
+        max_validator_commission,
+        delinquent_unstake_epochs,
+        stale_price_grace_seconds,
+        stale_price_fee,
+        crank_reward_lamports,
+        max_crank_rewards_per_epoch,
+        rebalance_tolerance,
+        slashing_threshold,
+        max_directed_stake_share,
+    }: ConfigStaderParams,
+) -> Result<ConfigStaderLiquidStakingEvent> {
+    let rewards_fee_change = if let Some(rewards_fee) = rewards_fee {
+        require_lte!(
             rewards_fee,
+            State::MAX_REWARD_FEE,
+            StaderLiquidStakingError::RewardsFeeIsTooHigh
+        );
+        let old = state.reward_fee;
+        state.reward_fee = rewards_fee;
+        Some(FeeValueChange {
+            old,
+            new: rewards_fee,
+        })
+    } else {
+        None
+    };
+
+    let slots_for_stake_delta_change = if let Some(slots_for_stake_delta) = slots_for_stake_delta {
+        require_gte!(
             slots_for_stake_delta,
+            StakeSystem::MIN_UPDATE_WINDOW,
+            StaderLiquidStakingError::UpdateWindowIsTooLow
+        );
+        let old = state.stake_system.slots_for_stake_delta;
+        state.stake_system.slots_for_stake_delta = slots_for_stake_delta;
+        Some(U64ValueChange {
+            old,
+            new: slots_for_stake_delta,
+        })
+    } else {
+        None
+    };
+
+    let min_stake_change = if let Some(min_stake) = min_stake {
+        require_gte!(
             min_stake,
-            min_deposit,
+            State::MIN_STAKE_LOWER_LIMIT,
+            StaderLiquidStakingError::MinStakeIsTooLow
+        );
+        let old = state.stake_system.min_stake;
+        state.stake_system.min_stake = min_stake;
+        Some(U64ValueChange {
+            old,
+            new: min_stake,
+        })
+    } else {
+        None
+    };
+
+    let min_deposit_change = if let Some(min_deposit) = min_deposit {
+        // It is not dangerous to skip value checks because it is deposit only action
+        // We can use u64::MAX to stop accepting deposits
+        // or 0 to accept 1 lamport
+        let old = state.min_deposit;
+        state.min_deposit = min_deposit;
+        Some(U64ValueChange {
+            old,
+            new: min_deposit,
+        })
+    } else {
+        None
+    };
+
+    let min_withdraw_change = if let Some(min_withdraw) = min_withdraw {
+        require_lte!(
             min_withdraw,
-            staking_sol_cap,
-            liquidity_sol_cap,
-            withdraw_stake_account_enabled,
+            State::MAX_WITHDRAW_ATOM,
+            StaderLiquidStakingError::MinWithdrawIsTooHigh
+        );
+        let old = state.min_withdraw;
+        state.min_withdraw = min_withdraw;
+        Some(U64ValueChange {
+            old,
+            new: min_withdraw,
+        })
+    } else {
+        None
+    };
+
+    let staking_sol_cap_change = if let Some(staking_sol_cap) = staking_sol_cap {
+        let old = state.staking_sol_cap;
+        state.staking_sol_cap = staking_sol_cap;
+        Some(U64ValueChange {
+            old,
+            new: staking_sol_cap,
+        })
+    } else {
+        None
+    };
+
+    let liquidity_sol_cap_change = if let Some(liquidity_sol_cap) = liquidity_sol_cap {
+        let old = state.liq_pool.liquidity_sol_cap;
+        state.liq_pool.liquidity_sol_cap = liquidity_sol_cap;
+        Some(U64ValueChange {
+            old,
+            new: liquidity_sol_cap,
+        })
+    } else {
+        None
+    };
+
+    let withdraw_stake_account_enabled_change =
+        if let Some(withdraw_stake_account_enabled) = withdraw_stake_account_enabled {
+            let old = state.withdraw_stake_account_enabled;
+            state.withdraw_stake_account_enabled = withdraw_stake_account_enabled;
+            Some(BoolValueChange {
+                old,
+                new: withdraw_stake_account_enabled,
+            })
+        } else {
+            None
+        };
+
+    let delayed_unstake_fee_change = if let Some(delayed_unstake_fee) = delayed_unstake_fee {
+        require_lte!(
             delayed_unstake_fee,
-            withdraw_stake_account_fee,
-            max_stake_moved_per_epoch,
-        }: ConfigStaderParams,
-    ) -> Result<()> {
-        let rewards_fee_change = if let Some(rewards_fee) = rewards_fee {
+            State::MAX_DELAYED_UNSTAKE_FEE,
+            StaderLiquidStakingError::DelayedUnstakeFeeIsTooHigh
+        );
+        let old = state.delayed_unstake_fee;
+        state.delayed_unstake_fee = delayed_unstake_fee;
+        Some(FeeCentsValueChange {
+            old,
+            new: delayed_unstake_fee,
+        })
+    } else {
+        None
+    };
+
+    let withdraw_stake_account_fee_change =
+        if let Some(withdraw_stake_account_fee) = withdraw_stake_account_fee {
             require_lte!(
-                rewards_fee,
-                State::MAX_REWARD_FEE,
-                StaderLiquidStakingError::RewardsFeeIsTooHigh
+                withdraw_stake_account_fee,
+                State::MAX_WITHDRAW_STAKE_ACCOUNT_FEE,
+                StaderLiquidStakingError::WithdrawStakeAccountFeeIsTooHigh
             );
-            let old = self.state.reward_fee;
-            self.state.reward_fee = rewards_fee;
+            let old = state.withdraw_stake_account_fee;
+            state.withdraw_stake_account_fee = withdraw_stake_account_fee;
+            Some(FeeCentsValueChange {
+                old,
+                new: withdraw_stake_account_fee,
+            })
+        } else {
+            None
+        };
+
+    let max_stake_moved_per_epoch_change =
+        if let Some(max_stake_moved_per_epoch) = max_stake_moved_per_epoch {
+            // Not checking for 100% because probably for some emergency case
+            // we need to move the same stake multiple times,
+            // for example to fix some incident
+            // max_stake_moved_per_epoch.check()?;
+            let old = state.max_stake_moved_per_epoch;
+            state.max_stake_moved_per_epoch = max_stake_moved_per_epoch;
             Some(FeeValueChange {
                 old,
-                new: rewards_fee,
+                new: max_stake_moved_per_epoch,
             })
         } else {
             None
         };
 
-        let slots_for_stake_delta_change =
-            if let Some(slots_for_stake_delta) = slots_for_stake_delta {
-                require_gte!(
-                    slots_for_stake_delta,
-                    StakeSystem::MIN_UPDATE_WINDOW,
-                    StaderLiquidStakingError::UpdateWindowIsTooLow
-                );
-                let old = self.state.stake_system.slots_for_stake_delta;
-                self.state.stake_system.slots_for_stake_delta = slots_for_stake_delta;
-                Some(U64ValueChange {
-                    old,
-                    new: slots_for_stake_delta,
-                })
-            } else {
-                None
-            };
-
-        let min_stake_change = if let Some(min_stake) = min_stake {
-            require_gte!(
-                min_stake,
-                State::MIN_STAKE_LOWER_LIMIT,
-                StaderLiquidStakingError::MinStakeIsTooLow
+    let config_delay_epochs_change = if let Some(config_delay_epochs) = config_delay_epochs {
+        require_lte!(
+            config_delay_epochs,
+            State::MAX_CONFIG_DELAY_EPOCHS,
+            StaderLiquidStakingError::ConfigDelayIsTooHigh
+        );
+        let old = state.config_delay_epochs;
+        state.config_delay_epochs = config_delay_epochs;
+        Some(U64ValueChange {
+            old,
+            new: config_delay_epochs,
+        })
+    } else {
+        None
+    };
+
+    let ticket_wait_epochs_change = if let Some(ticket_wait_epochs) = ticket_wait_epochs {
+        require_lte!(
+            ticket_wait_epochs,
+            State::MAX_TICKET_WAIT_EPOCHS,
+            StaderLiquidStakingError::TicketWaitEpochsIsTooHigh
+        );
+        require_gte!(
+            ticket_wait_epochs,
+            State::MIN_TICKET_WAIT_EPOCHS,
+            StaderLiquidStakingError::TicketWaitEpochsIsTooLow
+        );
+        let old = state.ticket_wait_epochs;
+        state.set_ticket_wait_epochs(ticket_wait_epochs, Clock::get()?.epoch);
+        Some(U64ValueChange {
+            old,
+            new: ticket_wait_epochs,
+        })
+    } else {
+        None
+    };
+
+    let ticket_extra_wait_seconds_change =
+        if let Some(ticket_extra_wait_seconds) = ticket_extra_wait_seconds {
+            require_lte!(
+                ticket_extra_wait_seconds,
+                State::MAX_TICKET_EXTRA_WAIT_SECONDS,
+                StaderLiquidStakingError::TicketExtraWaitSecondsIsTooHigh
             );
-            let old = self.state.stake_system.min_stake;
-            self.state.stake_system.min_stake = min_stake;
+            let old = state.ticket_extra_wait_seconds;
+            state.ticket_extra_wait_seconds = ticket_extra_wait_seconds;
             Some(U64ValueChange {
                 old,
-                new: min_stake,
+                new: ticket_extra_wait_seconds,
             })
         } else {
             None
         };
 
-        let min_deposit_change = if let Some(min_deposit) = min_deposit {
-            // It is not dangerous to skip value checks because it is deposit only action
-            // We can use u64::MAX to stop accepting deposits
-            // or 0 to accept 1 lamport
-            let old = self.state.min_deposit;
-            self.state.min_deposit = min_deposit;
-            Some(U64ValueChange {
+    let reserve_surplus_policy_change = if let Some(reserve_surplus_policy) = reserve_surplus_policy
+    {
+        require!(
+            reserve_surplus_policy == State::RESERVE_SURPLUS_DONATE
+                || reserve_surplus_policy == State::RESERVE_SURPLUS_TO_OPERATIONAL,
+            StaderLiquidStakingError::InvalidReserveSurplusPolicy
+        );
+        let old = state.reserve_surplus_policy;
+        state.reserve_surplus_policy = reserve_surplus_policy;
+        Some(U64ValueChange {
+            old: old.into(),
+            new: reserve_surplus_policy.into(),
+        })
+    } else {
+        None
+    };
+
+    let max_validator_stake_share_change =
+        if let Some(max_validator_stake_share) = max_validator_stake_share {
+            max_validator_stake_share.check()?;
+            require_gt!(
+                max_validator_stake_share.basis_points,
+                0,
+                StaderLiquidStakingError::MaxValidatorStakeShareIsTooLow
+            );
+            let old = state.max_validator_stake_share;
+            state.max_validator_stake_share = max_validator_stake_share;
+            Some(FeeValueChange {
                 old,
-                new: min_deposit,
+                new: max_validator_stake_share,
             })
         } else {
             None
         };
 
-        let min_withdraw_change = if let Some(min_withdraw) = min_withdraw {
+    let max_validator_commission_change =
+        if let Some(max_validator_commission) = max_validator_commission {
             require_lte!(
-                min_withdraw,
-                State::MAX_WITHDRAW_ATOM,
-                StaderLiquidStakingError::MinWithdrawIsTooHigh
+                max_validator_commission,
+                100,
+                StaderLiquidStakingError::InvalidMaxValidatorCommission
             );
-            let old = self.state.min_withdraw;
-            self.state.min_withdraw = min_withdraw;
+            let old = state.max_validator_commission;
+            state.max_validator_commission = max_validator_commission;
             Some(U64ValueChange {
-                old,
-                new: min_withdraw,
+                old: old.into(),
+                new: max_validator_commission.into(),
+            })
+        } else {
+            None
+        };
+
+    let delinquent_unstake_epochs_change =
+        if let Some(delinquent_unstake_epochs) = delinquent_unstake_epochs {
+            require_lte!(
+                delinquent_unstake_epochs,
+                State::MAX_DELINQUENT_UNSTAKE_EPOCHS,
+                StaderLiquidStakingError::InvalidDelinquentUnstakeEpochs
+            );
+            let old = state.delinquent_unstake_epochs;
+            state.delinquent_unstake_epochs = delinquent_unstake_epochs;
+            Some(U64ValueChange {
+                old: old.into(),
+                new: delinquent_unstake_epochs.into(),
             })
         } else {
             None
         };
 
-        let staking_sol_cap_change = if let Some(staking_sol_cap) = staking_sol_cap {
-            let old = self.state.staking_sol_cap;
-            self.state.staking_sol_cap = staking_sol_cap;
+    let stale_price_grace_seconds_change =
+        if let Some(stale_price_grace_seconds) = stale_price_grace_seconds {
+            require_lte!(
+                stale_price_grace_seconds,
+                State::MAX_STALE_PRICE_GRACE_SECONDS,
+                StaderLiquidStakingError::StalePriceGraceSecondsIsTooHigh
+            );
+            let old = state.stale_price_grace_seconds;
+            state.stale_price_grace_seconds = stale_price_grace_seconds;
             Some(U64ValueChange {
                 old,
-                new: staking_sol_cap,
+                new: stale_price_grace_seconds,
             })
         } else {
             None
         };
 
-        let liquidity_sol_cap_change = if let Some(liquidity_sol_cap) = liquidity_sol_cap {
-            let old = self.state.liq_pool.liquidity_sol_cap;
-            self.state.liq_pool.liquidity_sol_cap = liquidity_sol_cap;
+    let stale_price_fee_change = if let Some(stale_price_fee) = stale_price_fee {
+        require_lte!(
+            stale_price_fee,
+            State::MAX_STALE_PRICE_FEE,
+            StaderLiquidStakingError::StalePriceFeeIsTooHigh
+        );
+        let old = state.stale_price_fee;
+        state.stale_price_fee = stale_price_fee;
+        Some(FeeCentsValueChange {
+            old,
+            new: stale_price_fee,
+        })
+    } else {
+        None
+    };
+
+    let crank_reward_lamports_change = if let Some(crank_reward_lamports) = crank_reward_lamports
+    {
+        require_lte!(
+            crank_reward_lamports,
+            State::MAX_CRANK_REWARD_LAMPORTS,
+            StaderLiquidStakingError::CrankRewardIsTooHigh
+        );
+        let old = state.crank_reward_lamports;
+        state.crank_reward_lamports = crank_reward_lamports;
+        Some(U64ValueChange {
+            old,
+            new: crank_reward_lamports,
+        })
+    } else {
+        None
+    };
+
+    let max_crank_rewards_per_epoch_change =
+        if let Some(max_crank_rewards_per_epoch) = max_crank_rewards_per_epoch {
+            let old = state.max_crank_rewards_per_epoch;
+            state.max_crank_rewards_per_epoch = max_crank_rewards_per_epoch;
             Some(U64ValueChange {
                 old,
-                new: liquidity_sol_cap,
+                new: max_crank_rewards_per_epoch,
             })
         } else {
             None
         };
 
-        let withdraw_stake_account_enabled_change =
-            if let Some(withdraw_stake_account_enabled) = withdraw_stake_account_enabled {
-                let old = self.state.withdraw_stake_account_enabled;
-                self.state.withdraw_stake_account_enabled = withdraw_stake_account_enabled;
-                Some(BoolValueChange {
-                    old,
-                    new: withdraw_stake_account_enabled,
-                })
-            } else {
-                None
-            };
+    let rebalance_tolerance_change = if let Some(rebalance_tolerance) = rebalance_tolerance {
+        rebalance_tolerance.check()?;
+        let old = state.rebalance_tolerance;
+        state.rebalance_tolerance = rebalance_tolerance;
+        Some(FeeValueChange {
+            old,
+            new: rebalance_tolerance,
+        })
+    } else {
+        None
+    };
 
-        let delayed_unstake_fee_change = if let Some(delayed_unstake_fee) = delayed_unstake_fee {
-            require_lte!(
-                delayed_unstake_fee,
-                State::MAX_DELAYED_UNSTAKE_FEE,
-                StaderLiquidStakingError::DelayedUnstakeFeeIsTooHigh
-            );
-            let old = self.state.delayed_unstake_fee;
-            self.state.delayed_unstake_fee = delayed_unstake_fee;
-            Some(FeeCentsValueChange {
+    let slashing_threshold_change = if let Some(slashing_threshold) = slashing_threshold {
+        slashing_threshold.check()?;
+        let old = state.slashing_threshold;
+        state.slashing_threshold = slashing_threshold;
+        Some(FeeValueChange {
+            old,
+            new: slashing_threshold,
+        })
+    } else {
+        None
+    };
+
+    let max_directed_stake_share_change =
+        if let Some(max_directed_stake_share) = max_directed_stake_share {
+            max_directed_stake_share.check()?;
+            let old = state.max_directed_stake_share;
+            state.max_directed_stake_share = max_directed_stake_share;
+            Some(FeeValueChange {
                 old,
-                new: delayed_unstake_fee,
+                new: max_directed_stake_share,
             })
         } else {
             None
         };
 
-        let withdraw_stake_account_fee_change =
-            if let Some(withdraw_stake_account_fee) = withdraw_stake_account_fee {
-                require_lte!(
-                    withdraw_stake_account_fee,
-                    State::MAX_WITHDRAW_STAKE_ACCOUNT_FEE,
-                    StaderLiquidStakingError::WithdrawStakeAccountFeeIsTooHigh
-                );
-                let old = self.state.withdraw_stake_account_fee;
-                self.state.withdraw_stake_account_fee = withdraw_stake_account_fee;
-                Some(FeeCentsValueChange {
-                    old,
-                    new: withdraw_stake_account_fee,
-                })
-            } else {
-                None
-            };
-
-        let max_stake_moved_per_epoch_change =
-            if let Some(max_stake_moved_per_epoch) = max_stake_moved_per_epoch {
-                // Not checking for 100% because probably for some emergency case
-                // we need to move the same stake multiple times,
-                // for example to fix some incident
-                // max_stake_moved_per_epoch.check()?;
-                let old = self.state.max_stake_moved_per_epoch;
-                self.state.max_stake_moved_per_epoch = max_stake_moved_per_epoch;
-                Some(FeeValueChange {
-                    old,
-                    new: max_stake_moved_per_epoch,
-                })
-            } else {
-                None
-            };
-
-        emit!(ConfigStaderLiquidStakingEvent {
-            state: self.state.key(),
-            rewards_fee_change,
-            slots_for_stake_delta_change,
-            min_stake_change,
-            min_deposit_change,
-            min_withdraw_change,
-            staking_sol_cap_change,
-            liquidity_sol_cap_change,
-            withdraw_stake_account_enabled_change,
-            delayed_unstake_fee_change,
-            withdraw_stake_account_fee_change,
-            max_stake_moved_per_epoch_change,
+    Ok(ConfigStaderLiquidStakingEvent {
+        state: state_address,
+        rewards_fee_change,
+        slots_for_stake_delta_change,
+        min_stake_change,
+        min_deposit_change,
+        min_withdraw_change,
+        staking_sol_cap_change,
+        liquidity_sol_cap_change,
+        withdraw_stake_account_enabled_change,
+        delayed_unstake_fee_change,
+        withdraw_stake_account_fee_change,
+        max_stake_moved_per_epoch_change,
+        config_delay_epochs_change,
+        ticket_wait_epochs_change,
+        ticket_extra_wait_seconds_change,
+        reserve_surplus_policy_change,
+        max_validator_stake_share_change,
+        max_validator_commission_change,
+        delinquent_unstake_epochs_change,
+        stale_price_grace_seconds_change,
+        stale_price_fee_change,
+        crank_reward_lamports_change,
+        max_crank_rewards_per_epoch_change,
+        rebalance_tolerance_change,
+        slashing_threshold_change,
+        max_directed_stake_share_change,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConfigStaderParams;
+    use crate::{
+        state::{Fee, FeeCents},
+        State,
+    };
+
+    #[test]
+    fn test_split_timelocked() {
+        let mut state = State::for_tests();
+        state.reward_fee = Fee::from_basis_points(500);
+        state.staking_sol_cap = 1_000;
+        state.delayed_unstake_fee = FeeCents::from_bp_cents(100);
+        state.config_delay_epochs = 2;
+
+        let (immediate, queued) = ConfigStaderParams {
+            rewards_fee: Some(Fee::from_basis_points(400)),
+            staking_sol_cap: Some(2_000),
+            delayed_unstake_fee: Some(FeeCents::from_bp_cents(200)),
+            config_delay_epochs: Some(3),
+            min_deposit: Some(10),
+            ..Default::default()
+        }
+        .split_timelocked(&state);
+        // lowering a fee, a longer timelock and not timelocked params apply right away
+        assert_eq!(
+            immediate,
+            ConfigStaderParams {
+                rewards_fee: Some(Fee::from_basis_points(400)),
+                config_delay_epochs: Some(3),
+                min_deposit: Some(10),
+                ..Default::default()
+            }
+        );
+        // raising a fee or a cap waits
+        assert_eq!(
+            queued,
+            ConfigStaderParams {
+                staking_sol_cap: Some(2_000),
+                delayed_unstake_fee: Some(FeeCents::from_bp_cents(200)),
+                ..Default::default()
+            }
+        );
+
+        // unchanged values are not timelocked, a shorter timelock or ticket wait is
+        state.ticket_wait_epochs = 2;
+        let (immediate, queued) = ConfigStaderParams {
+            rewards_fee: Some(Fee::from_basis_points(500)),
+            config_delay_epochs: Some(1),
+            ticket_wait_epochs: Some(1),
+            ..Default::default()
+        }
+        .split_timelocked(&state);
+        assert_eq!(
+            immediate,
+            ConfigStaderParams {
+                rewards_fee: Some(Fee::from_basis_points(500)),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            queued,
+            ConfigStaderParams {
+                config_delay_epochs: Some(1),
+                ticket_wait_epochs: Some(1),
+                ..Default::default()
+            }
+        );
+
+        // raising the crank rewards waits, lowering them does not
+        state.crank_reward_lamports = 100;
+        state.max_crank_rewards_per_epoch = 1_000;
+        let (immediate, queued) = ConfigStaderParams {
+            crank_reward_lamports: Some(200),
+            max_crank_rewards_per_epoch: Some(500),
+            ..Default::default()
+        }
+        .split_timelocked(&state);
+        assert_eq!(
+            immediate,
+            ConfigStaderParams {
+                max_crank_rewards_per_epoch: Some(500),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            queued,
+            ConfigStaderParams {
+                crank_reward_lamports: Some(200),
+                ..Default::default()
+            }
+        );
+
+        // a higher rebalance tolerance applies right away, a lower one waits
+        state.rebalance_tolerance = Fee::from_basis_points(500);
+        for (tolerance, is_immediate) in [(600, true), (400, false)] {
+            let (immediate, queued) = ConfigStaderParams {
+                rebalance_tolerance: Some(Fee::from_basis_points(tolerance)),
+                ..Default::default()
+            }
+            .split_timelocked(&state);
+            assert_eq!(immediate.rebalance_tolerance.is_some(), is_immediate);
+            assert_eq!(queued.rebalance_tolerance.is_some(), !is_immediate);
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut pending = ConfigStaderParams {
+            rewards_fee: Some(Fee::from_basis_points(600)),
+            staking_sol_cap: Some(2_000),
+            ..Default::default()
+        };
+        pending.merge(ConfigStaderParams {
+            staking_sol_cap: Some(3_000),
+            ..Default::default()
         });
+        assert_eq!(pending.rewards_fee, Some(Fee::from_basis_points(600)));
+        assert_eq!(pending.staking_sol_cap, Some(3_000));
+    }
 
-        Ok(())
+    #[test]
+    fn test_changed_roles() {
+        assert_eq!(ConfigStaderParams::default().changed_roles(), 0);
+        assert_eq!(
+            ConfigStaderParams {
+                stale_price_fee: Some(FeeCents::from_bp_cents(1)),
+                rebalance_tolerance: Some(Fee::from_basis_points(1)),
+                ..Default::default()
+            }
+            .changed_roles(),
+            ConfigStaderParams::FEE_ROLE | ConfigStaderParams::LIMITS_ROLE
+        );
+        assert_eq!(
+            ConfigStaderParams {
+                config_delay_epochs: Some(1),
+                ..Default::default()
+            }
+            .changed_roles(),
+            ConfigStaderParams::ADMIN_ROLE
+        );
     }
 }
@@ -2,7 +2,8 @@ use anchor_lang::prelude::*;
 
 use crate::{
     error::StaderLiquidStakingError,
-    events::admin::{EmergencyPauseEvent, ResumeEvent},
+    events::admin::{EmergencyPauseEvent, ResetCircuitBreakerEvent, ResumeEvent},
+    state::config_history::ConfigHistory,
     State,
 };
 
@@ -15,25 +16,95 @@ pub struct EmergencyPause<'info> {
     )]
     pub state: Account<'info, State>,
     pub pause_authority: Signer<'info>,
+    #[account(mut, has_one = state)]
+    pub config_history: Option<Box<Account<'info, ConfigHistory>>>,
 }
 
 impl<'info> EmergencyPause<'info> {
-    pub fn pause(&mut self) -> Result<()> {
-        require!(!self.state.paused, StaderLiquidStakingError::AlreadyPaused);
-        self.state.paused = true;
+    /// pauses every instruction group in the mask, see State::PAUSE_*
+    pub fn pause(&mut self, groups: u8) -> Result<()> {
+        Self::check_groups(groups)?;
+        let old_paused_groups = self.state.paused_groups;
+        // at least one of the groups must not be paused yet
+        require!(
+            groups & !old_paused_groups != 0,
+            StaderLiquidStakingError::AlreadyPaused
+        );
+        self.state.paused_groups |= groups;
+        self.record_paused_groups_change(old_paused_groups)?;
         emit!(EmergencyPauseEvent {
             state: self.state.key(),
+            paused_groups: groups & !old_paused_groups,
+            old_paused_groups,
+            new_paused_groups: self.state.paused_groups,
         });
 
         Ok(())
     }
 
-    pub fn resume(&mut self) -> Result<()> {
-        require!(self.state.paused, StaderLiquidStakingError::NotPaused);
-        self.state.paused = false;
+    pub fn resume(&mut self, groups: u8) -> Result<()> {
+        Self::check_groups(groups)?;
+        let old_paused_groups = self.state.paused_groups;
+        require!(
+            groups & old_paused_groups != 0,
+            StaderLiquidStakingError::NotPaused
+        );
+        // groups paused by the circuit breaker are resumed by reset_circuit_breaker
+        require!(
+            groups & self.state.circuit_breaker.tripped_groups == 0,
+            StaderLiquidStakingError::CircuitBreakerIsTripped
+        );
+        self.state.paused_groups &= !groups;
+        self.record_paused_groups_change(old_paused_groups)?;
         emit!(ResumeEvent {
             state: self.state.key(),
+            resumed_groups: groups & old_paused_groups,
+            old_paused_groups,
+            new_paused_groups: self.state.paused_groups,
         });
         Ok(())
     }
+
+    /// clears the circuit breaker record and resumes the groups it paused
+    pub fn reset_circuit_breaker(&mut self) -> Result<()> {
+        require!(
+            self.state.circuit_breaker.is_tripped(),
+            StaderLiquidStakingError::CircuitBreakerNotTripped
+        );
+        let old_paused_groups = self.state.paused_groups;
+        let circuit_breaker = self.state.reset_circuit_breaker();
+        self.record_paused_groups_change(old_paused_groups)?;
+        emit!(ResetCircuitBreakerEvent {
+            state: self.state.key(),
+            reason: circuit_breaker.reason,
+            tripped_epoch: circuit_breaker.tripped_epoch,
+            resumed_groups: circuit_breaker.tripped_groups,
+            new_paused_groups: self.state.paused_groups,
+        });
+        Ok(())
+    }
+
+    fn record_paused_groups_change(&mut self, old_paused_groups: u8) -> Result<()> {
+        self.state
+            .check_config_history(self.config_history.as_ref().map(|h| h.key()))?;
+        if let Some(config_history) = &mut self.config_history {
+            config_history.record(
+                &Clock::get()?,
+                self.pause_authority.key(),
+                [ConfigHistory::paused_groups_change(
+                    old_paused_groups,
+                    self.state.paused_groups,
+                )],
+            );
+        }
+        Ok(())
+    }
+
+    fn check_groups(groups: u8) -> Result<()> {
+        require!(
+            groups != 0 && groups & !State::PAUSE_ALL == 0,
+            StaderLiquidStakingError::InvalidPauseGroups
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::StaderLiquidStakingError,
+    instructions::{apply_config_lp, apply_config_stader},
+    state::{config_history::ConfigHistory, pending_config::PendingConfig},
+    State,
+};
+
+/// permissionless, anybody can apply the queued config once the timelock has passed
+#[derive(Accounts)]
+pub struct ExecutePendingConfig<'info> {
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    #[account(
+        mut,
+        has_one = state,
+        seeds = [
+            &state.key().to_bytes(),
+            PendingConfig::SEED
+        ],
+        bump
+    )]
+    pub pending_config: Account<'info, PendingConfig>,
+    pub clock: Sysvar<'info, Clock>,
+    #[account(mut, has_one = state)]
+    pub config_history: Option<Box<Account<'info, ConfigHistory>>>,
+}
+
+impl<'info> ExecutePendingConfig<'info> {
+    pub fn process(&mut self) -> Result<()> {
+        self.state
+            .check_config_history(self.config_history.as_ref().map(|h| h.key()))?;
+        require!(
+            !self.pending_config.is_empty(),
+            StaderLiquidStakingError::NoPendingConfig
+        );
+        require_gte!(
+            self.clock.epoch,
+            self.pending_config.earliest_execution_epoch,
+            StaderLiquidStakingError::PendingConfigNotDue
+        );
+
+        let state_address = self.state.key();
+        let stader_event = apply_config_stader(
+            &mut self.state,
+            state_address,
+            self.pending_config.stader_params,
+        )?;
+        let lp_event = apply_config_lp(
+            &mut self.state,
+            state_address,
+            self.pending_config.lp_params,
+        )?;
+        if let Some(config_history) = &mut self.config_history {
+            // no signer, the changes were authorized when queued
+            let mut changes = ConfigHistory::config_stader_changes(&stader_event);
+            changes.extend(ConfigHistory::config_lp_changes(&lp_event));
+            config_history.record(&self.clock, Pubkey::default(), changes);
+        }
+        emit!(stader_event);
+        emit!(lp_event);
+
+        self.pending_config.clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_program;
+
+use crate::{error::StaderLiquidStakingError, state::config_history::ConfigHistory, State};
+
+#[derive(Accounts)]
+pub struct InitConfigHistory<'info> {
+    #[account(
+        mut,
+        has_one = admin_authority @ StaderLiquidStakingError::InvalidAdminAuthority
+    )]
+    pub state: Account<'info, State>,
+    pub admin_authority: Signer<'info>,
+    #[account(
+        init,
+        payer = rent_payer,
+        space = ConfigHistory::serialized_len(),
+        seeds = [
+            &state.key().to_bytes(),
+            ConfigHistory::SEED
+        ],
+        bump,
+    )]
+    pub config_history: Box<Account<'info, ConfigHistory>>,
+    #[account(
+        mut,
+        owner = system_program::ID
+    )]
+    pub rent_payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitConfigHistory<'info> {
+    pub fn process(&mut self) -> Result<()> {
+        self.config_history.state = self.state.key();
+        self.config_history.total_entries = 0;
+        self.config_history.entries = vec![];
+        self.state.config_history = self.config_history.key();
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_program;
+
+use crate::{error::StaderLiquidStakingError, state::crank_reward_vault::CrankRewardVault, State};
+
+#[derive(Accounts)]
+pub struct InitCrankRewardVault<'info> {
+    #[account(
+        has_one = admin_authority @ StaderLiquidStakingError::InvalidAdminAuthority
+    )]
+    pub state: Account<'info, State>,
+    pub admin_authority: Signer<'info>,
+    #[account(
+        init,
+        payer = rent_payer,
+        space = CrankRewardVault::LEN,
+        seeds = [
+            &state.key().to_bytes(),
+            CrankRewardVault::SEED
+        ],
+        bump,
+    )]
+    pub crank_reward_vault: Account<'info, CrankRewardVault>,
+    #[account(
+        mut,
+        owner = system_program::ID
+    )]
+    pub rent_payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitCrankRewardVault<'info> {
+    pub fn process(&mut self) -> Result<()> {
+        self.crank_reward_vault.state = self.state.key();
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_program;
+
+use crate::{error::StaderLiquidStakingError, state::pending_config::PendingConfig, State};
+
+#[derive(Accounts)]
+pub struct InitPendingConfig<'info> {
+    #[account(
+        has_one = admin_authority @ StaderLiquidStakingError::InvalidAdminAuthority
+    )]
+    pub state: Account<'info, State>,
+    pub admin_authority: Signer<'info>,
+    #[account(
+        init,
+        payer = rent_payer,
+        space = PendingConfig::serialized_len(),
+        seeds = [
+            &state.key().to_bytes(),
+            PendingConfig::SEED
+        ],
+        bump,
+    )]
+    pub pending_config: Account<'info, PendingConfig>,
+    #[account(
+        mut,
+        owner = system_program::ID
+    )]
+    pub rent_payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitPendingConfig<'info> {
+    pub fn process(&mut self) -> Result<()> {
+        self.pending_config.state = self.state.key();
+        self.pending_config.clear();
+        Ok(())
+    }
+}
@@ -7,8 +7,9 @@ use crate::{
     events::admin::InitializeEvent,
     require_lte,
     state::{
-        fee::FeeCents, liq_pool::LiqPool, stake_system::StakeSystem,
-        validator_system::ValidatorSystem, Fee,
+        circuit_breaker::CircuitBreaker, fee::FeeCents, liq_pool::LiqPool, stake_system::StakeSystem,
+        validator_system::{ValidatorScoringConfig, ValidatorSystem},
+        Fee,
     },
     State, ID,
 };
@@ -180,13 +181,52 @@ impl<'info> Initialize<'info> {
             staking_sol_cap: std::u64::MAX, // Unlimited
             emergency_cooling_down: 0,
             pause_authority,
-            paused: false,
+            paused_groups: 0,
             delayed_unstake_fee: FeeCents::from_bp_cents(0),
             withdraw_stake_account_fee: FeeCents::from_bp_cents(0),
             withdraw_stake_account_enabled: false,
             last_stake_move_epoch: 0,
             stake_moved: 0,
             max_stake_moved_per_epoch: Fee::from_basis_points(10000), // 100% of total_lamports_under_control
+            pending_admin_authority: Pubkey::default(),
+            pending_validator_manager_authority: Pubkey::default(),
+            pending_pause_authority: Pubkey::default(),
+            config_delay_epochs: State::DEFAULT_CONFIG_DELAY_EPOCHS,
+            fee_authority: Pubkey::default(),
+            limits_authority: Pubkey::default(),
+            metadata_authority: Pubkey::default(),
+            lp_config_authority: Pubkey::default(),
+            circuit_breaker: CircuitBreaker::default(),
+            version: State::CURRENT_VERSION,
+            ticket_wait_epochs: State::DEFAULT_TICKET_WAIT_EPOCHS,
+            ticket_extra_wait_seconds: State::DEFAULT_TICKET_EXTRA_WAIT_SECONDS,
+            wind_down: false,
+            wind_down_epoch: 0,
+            reserve_surplus_policy: State::RESERVE_SURPLUS_DONATE,
+            validator_scoring: ValidatorScoringConfig::default(),
+            max_validator_stake_share: Fee::from_basis_points(Fee::MAX_BASIS_POINTS), // no cap
+            max_validator_commission: 100, // no guard
+            total_directed_shares: 0,
+            total_directed_lamports: 0,
+            delinquent_unstake_epochs: 0, // disabled
+            stake_update_epoch: 0,
+            updated_stake_count: 0,
+            stale_price_grace_seconds: 0, // no stale price protection
+            stale_price_fee: FeeCents::from_bp_cents(0),
+            crank_reward_lamports: 0, // disabled
+            max_crank_rewards_per_epoch: 0,
+            crank_reward_epoch: 0,
+            crank_rewards_paid_in_epoch: 0,
+            total_crank_rewards_paid: 0,
+            rebalance_tolerance: Fee::from_basis_points(0), // any gap over min_stake
+            slashing_threshold: State::DEFAULT_SLASHING_THRESHOLD,
+            legacy_ticket_wait_epochs: 0,
+            legacy_ticket_wait_until_epoch: 0,
+            config_history: Pubkey::default(),
+            total_unhonored_directed_shares: 0,
+            max_directed_stake_share: State::DEFAULT_MAX_DIRECTED_STAKE_SHARE,
+            deactivating_stake_count: 0,
+            reserved: [0; 317],
         });
 
         emit!(InitializeEvent {
@@ -0,0 +1,71 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{self, transfer, Transfer},
+};
+
+use crate::{
+    error::StaderLiquidStakingError, events::admin::MigrateStateEvent,
+    state::migration::decode_any_version, State,
+};
+
+#[derive(Accounts)]
+pub struct MigrateState<'info> {
+    /// CHECK: decoded in code, the layout may be older than the current State
+    #[account(mut, owner = crate::ID)]
+    pub state: UncheckedAccount<'info>,
+    pub admin_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        owner = system_program::ID,
+    )]
+    pub rent_funds: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateState<'info> {
+    /// reallocs the state account to at least the current size and converts the old layout
+    pub fn process(&mut self) -> Result<()> {
+        let old_len = self.state.data_len();
+        let (old_version, state) = decode_any_version(&self.state.try_borrow_data()?)?;
+        require_keys_eq!(
+            self.admin_authority.key(),
+            state.admin_authority,
+            StaderLiquidStakingError::InvalidAdminAuthority
+        );
+        require_gt!(
+            State::CURRENT_VERSION,
+            old_version,
+            StaderLiquidStakingError::StateAlreadyMigrated
+        );
+
+        let new_len = old_len.max(State::serialized_len());
+        let rent_needed = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(self.state.lamports());
+        if rent_needed > 0 {
+            transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    Transfer {
+                        from: self.rent_funds.to_account_info(),
+                        to: self.state.to_account_info(),
+                    },
+                ),
+                rent_needed,
+            )?;
+        }
+        self.state.realloc(new_len, true)?;
+        state.try_serialize(&mut &mut self.state.try_borrow_mut_data()?[..])?;
+
+        emit!(MigrateStateEvent {
+            state: self.state.key(),
+            old_version,
+            new_version: State::CURRENT_VERSION,
+            old_len: old_len as u32,
+            new_len: new_len as u32,
+        });
+        Ok(())
+    }
+}
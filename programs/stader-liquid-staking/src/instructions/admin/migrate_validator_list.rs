@@ -0,0 +1,101 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE,
+    system_program::{self, transfer, Transfer},
+    Discriminator,
+};
+
+use crate::{
+    error::StaderLiquidStakingError,
+    events::admin::MigrateValidatorListEvent,
+    state::validator_system::{ValidatorList, ValidatorSystem},
+    State,
+};
+
+#[derive(Accounts)]
+pub struct MigrateValidatorList<'info> {
+    #[account(
+        mut,
+        has_one = admin_authority @ StaderLiquidStakingError::InvalidAdminAuthority,
+    )]
+    pub state: Account<'info, State>,
+    pub admin_authority: Signer<'info>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+
+    #[account(
+        mut,
+        owner = system_program::ID,
+    )]
+    pub rent_funds: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateValidatorList<'info> {
+    /// Grows the records of a list created without room for ValidatorMetadata.
+    /// An account can only grow MAX_PERMITTED_DATA_INCREASE per instruction, so large lists
+    /// need several calls with the same capacity: the first ones only grow the account
+    /// (the records keep the old size), the one reaching the full size converts them in place.
+    /// Existing records get ValidatorMetadata::unknown() until observed again
+    pub fn process(&mut self, capacity: u32) -> Result<()> {
+        let count = self.state.validator_system.validator_count();
+        require_gte!(
+            capacity,
+            count,
+            StaderLiquidStakingError::ShrinkingListWithDeletingContents
+        );
+        let old_record_size = self.state.validator_system.validator_record_size();
+        require_gt!(
+            ValidatorSystem::record_size_with_metadata(),
+            old_record_size,
+            StaderLiquidStakingError::ValidatorListAlreadyMigrated
+        );
+
+        let target_len = ValidatorList::DISCRIMINATOR.len()
+            + (ValidatorSystem::record_size_with_metadata() * capacity) as usize;
+        let old_len = self.validator_list.to_account_info().data_len();
+        let new_len = target_len.min(old_len + MAX_PERMITTED_DATA_INCREASE).max(old_len);
+        if new_len > old_len {
+            let rent_needed = Rent::get()?
+                .minimum_balance(new_len)
+                .saturating_sub(self.validator_list.to_account_info().lamports());
+            if rent_needed > 0 {
+                transfer(
+                    CpiContext::new(
+                        self.system_program.to_account_info(),
+                        Transfer {
+                            from: self.rent_funds.to_account_info(),
+                            to: self.validator_list.to_account_info(),
+                        },
+                    ),
+                    rent_needed,
+                )?;
+            }
+            self.validator_list
+                .to_account_info()
+                .realloc(new_len, false)?;
+        }
+
+        // the records are converted once the account is large enough
+        if new_len >= target_len {
+            self.state.validator_system.migrate_to_metadata(
+                &mut self.validator_list.to_account_info().data.borrow_mut(),
+            )?;
+        }
+
+        emit!(MigrateValidatorListEvent {
+            state: self.state.key(),
+            count,
+            old_record_size,
+            new_record_size: self.state.validator_system.validator_record_size(),
+            new_capacity: capacity,
+            old_len: old_len as u32,
+            new_len: new_len as u32,
+        });
+        Ok(())
+    }
+}
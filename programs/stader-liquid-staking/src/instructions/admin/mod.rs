@@ -1,22 +1,40 @@
+pub mod accept_authority;
+pub mod cancel_pending_config;
 pub mod change_authority;
 pub mod config_lp;
 pub mod config_stader;
 pub mod config_validator_system;
 pub mod emergency_pause;
+pub mod execute_pending_config;
+pub mod init_config_history;
+pub mod init_crank_reward_vault;
+pub mod init_pending_config;
 pub mod initialize;
+pub mod migrate_state;
+pub mod migrate_validator_list;
 pub mod realloc_stake_list;
 pub mod realloc_validator_list;
+pub mod start_wind_down;
 pub mod update_lp_token_metadata;
 pub mod update_stader_sol_token_metadata;
 
+pub use accept_authority::*;
+pub use cancel_pending_config::*;
 pub use change_authority::*;
 pub use config_lp::*;
 pub use config_stader::*;
 pub use config_validator_system::*;
 pub use emergency_pause::*;
+pub use execute_pending_config::*;
+pub use init_config_history::*;
+pub use init_crank_reward_vault::*;
+pub use init_pending_config::*;
 pub use initialize::*;
+pub use migrate_state::*;
+pub use migrate_validator_list::*;
 pub use realloc_stake_list::*;
 pub use realloc_validator_list::*;
+pub use start_wind_down::*;
 pub use update_lp_token_metadata::*;
 pub use update_stader_sol_token_metadata::*;
 
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::StaderLiquidStakingError, events::admin::StartWindDownEvent, State};
+
+#[derive(Accounts)]
+pub struct StartWindDown<'info> {
+    #[account(
+        mut,
+        has_one = admin_authority @ StaderLiquidStakingError::InvalidAdminAuthority
+    )]
+    pub state: Account<'info, State>,
+    pub admin_authority: Signer<'info>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+impl<'info> StartWindDown<'info> {
+    /// Retires the pool instance, there is no way back.
+    /// Deposits and new liquidity are refused, every stake can be deactivated permissionlessly
+    /// and once the stakes are withdrawn staderSOL is redeemed pro-rata from the reserve
+    pub fn process(&mut self) -> Result<()> {
+        self.state.check_not_winding_down()?;
+        self.state.wind_down = true;
+        self.state.wind_down_epoch = self.clock.epoch;
+        emit!(StartWindDownEvent {
+            state: self.state.key(),
+            epoch: self.clock.epoch,
+        });
+        Ok(())
+    }
+}
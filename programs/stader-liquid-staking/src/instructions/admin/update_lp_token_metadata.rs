@@ -2,7 +2,7 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::Mint;
 use anchor_spl::metadata::Metadata;
 use crate::state::liq_pool::LiqPool;
-use crate::State;
+use crate::{error::StaderLiquidStakingError, State};
 
 use super::initialize_metadata_account;
 
@@ -11,6 +11,11 @@ pub struct UpdateLpMintTokenMetadata<'info> {
     #[account(mut)]    
     pub payer: Signer<'info>,
     pub state: Box<Account<'info, State>>,
+    #[account(
+        address = state.effective_metadata_authority()
+            @ StaderLiquidStakingError::InvalidMetadataAuthority
+    )]
+    pub metadata_authority: Signer<'info>,
     #[account( address = state.liq_pool.lp_mint )]
     pub lp_mint: Account<'info, Mint>,
     /// CHECK: PDA
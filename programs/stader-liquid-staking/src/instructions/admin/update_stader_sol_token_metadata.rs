@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::Mint;
 use anchor_spl::metadata::Metadata;
-use crate::State;
+use crate::{error::StaderLiquidStakingError, State};
 
 use super::initialize_metadata_account;
 
@@ -11,6 +11,11 @@ pub struct UpdateStaderSolTokenMetadata<'info> {
     pub payer: Signer<'info>,
     #[account(has_one = stader_sol_mint)]
     pub state: Box<Account<'info, State>>,
+    #[account(
+        address = state.effective_metadata_authority()
+            @ StaderLiquidStakingError::InvalidMetadataAuthority
+    )]
+    pub metadata_authority: Signer<'info>,
     pub stader_sol_mint: Account<'info, Mint>,
     /// CHECK: PDA
     #[account(
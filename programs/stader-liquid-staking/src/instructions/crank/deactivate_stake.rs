@@ -1,8 +1,10 @@
 use crate::{
     error::StaderLiquidStakingError,
     events::crank::{DeactivateStakeEvent, SplitStakeAccountInfo},
-    require_lt,
+    pay_crank_reward, require_lt,
     state::{
+        crank_reward_vault::CrankRewardVault,
+        directed_stake::DirectedStake,
         stake_system::{StakeList, StakeSystem},
         validator_system::ValidatorList,
     },
@@ -77,6 +79,15 @@ pub struct DeactivateStake<'info> {
 
     pub system_program: Program<'info, System>,
     pub stake_program: Program<'info, Stake>,
+
+    /// CHECK: may not exist, see DirectedStake::load_shares
+    pub directed_stake: UncheckedAccount<'info>,
+
+    #[account(mut, has_one = state)]
+    pub crank_reward_vault: Option<Account<'info, CrankRewardVault>>,
+    /// CHECK: any writable account of the caller, receives the crank reward
+    #[account(mut)]
+    pub crank_reward_receiver: Option<UncheckedAccount<'info>>,
 }
 
 impl<'info> DeactivateStake<'info> {
@@ -84,8 +95,13 @@ impl<'info> DeactivateStake<'info> {
     // fn deactivate_stake()
     //
     pub fn process(&mut self, stake_index: u32, validator_index: u32) -> Result<()> {
-        require!(!self.state.paused, StaderLiquidStakingError::ProgramIsPaused);
+        self.state.check_not_paused(State::PAUSE_CRANKS)?;
 
+        let stake_index = self.state.stake_system.resolve_index(
+            &self.stake_list.to_account_info().data.as_ref().borrow(),
+            stake_index,
+            self.stake_account.to_account_info().key,
+        )?;
         let mut stake = self.state.stake_system.get_checked(
             &self.stake_list.to_account_info().data.as_ref().borrow(),
             stake_index,
@@ -100,6 +116,15 @@ impl<'info> DeactivateStake<'info> {
             StaderLiquidStakingError::StakeAccountIsEmergencyUnstaking
         );
 
+        let validator_index = self.state.validator_system.resolve_index(
+            &self.validator_list.to_account_info().data.as_ref().borrow(),
+            validator_index,
+            &self
+                .stake_account
+                .delegation()
+                .map(|delegation| delegation.voter_pubkey)
+                .unwrap_or_default(),
+        )?;
         let mut validator = self.state.validator_system.get(
             &self.validator_list.to_account_info().data.as_ref().borrow(),
             validator_index,
@@ -115,19 +140,23 @@ impl<'info> DeactivateStake<'info> {
         //     StaderLiquidStakingError::TooEarlyForStakeDelta
         // );
 
-        // compute total required stake delta (i128, must be negative)
-        let total_stake_delta_i128 = self.state.stake_delta(self.reserve_pda.lamports());
-        msg!("total_stake_delta_i128 {}", total_stake_delta_i128);
-        require_lt!(
-            total_stake_delta_i128,
-            0,
-            StaderLiquidStakingError::UnstakingOnPositiveDelta
-        );
-        // convert to u64
-        let total_unstake_delta =
-            u64::try_from(-total_stake_delta_i128).expect("Unstake delta overflow");
-        // compute total target stake (current total active stake minus delta)
         let total_active_balance = self.state.validator_system.total_active_balance; // record for event
+        let total_unstake_delta = if self.state.wind_down {
+            // everything must be unstaked, not limited by the stake delta
+            total_active_balance
+        } else {
+            // compute total required stake delta (i128, must be negative)
+            let total_stake_delta_i128 = self.state.stake_delta(self.reserve_pda.lamports());
+            msg!("total_stake_delta_i128 {}", total_stake_delta_i128);
+            require_lt!(
+                total_stake_delta_i128,
+                0,
+                StaderLiquidStakingError::UnstakingOnPositiveDelta
+            );
+            // convert to u64
+            u64::try_from(-total_stake_delta_i128).expect("Unstake delta overflow")
+        };
+        // compute total target stake (current total active stake minus delta)
         let total_stake_target = total_active_balance.saturating_sub(total_unstake_delta);
 
         // check currently_staked in this account & validator vote-key
@@ -138,10 +167,25 @@ impl<'info> DeactivateStake<'info> {
         )?;
 
         // compute target for this particular validator (total_stake_target * score/total_score)
+        let validator_directed_lamports = self.state.validator_directed_lamports(
+            &validator,
+            DirectedStake::load_shares(
+                &self.directed_stake,
+                &self.state.key(),
+                &validator.validator_account,
+            )
+            .map_err(|e| e.with_account_name("directed_stake"))?,
+        )?;
         let validator_stake_target = self
             .state
             .validator_system
-            .validator_stake_target(&validator, total_stake_target)?;
+            .validator_stake_target(
+                &validator,
+                total_stake_target,
+                self.state.max_validator_stake_share,
+                validator_directed_lamports,
+                self.state.honored_directed_lamports()?,
+            )?;
 
         // compute how much we should unstake from this validator
         let validator_active_balance = validator.active_balance; // record for event
@@ -171,7 +215,7 @@ impl<'info> DeactivateStake<'info> {
             },
         );
 
-        let (unstaked_amount, deactivate_whole_stake) =
+        let (unstaked_amount, split_stake_index) =
             if stake_account_target < 2 * self.state.stake_system.min_stake {
                 // unstake all if what will remain in the account is < twice min_stake
                 msg!("Deactivate whole stake {}", stake.stake_account);
@@ -191,11 +235,13 @@ impl<'info> DeactivateStake<'info> {
                         &[self.state.stake_system.stake_deposit_bump_seed],
                     ]],
                 ))?;
+                self.state
+                    .on_stake_record_deactivating(&stake, self.clock.epoch);
 
                 // Return back the rent reserve of unused split stake account
                 self.return_unused_split_stake_account_rent()?;
 
-                (stake.last_update_delegated_lamports, true)
+                (stake.last_update_delegated_lamports, None)
             } else {
                 // we must perform partial unstake
                 // Update validator.last_stake_delta_epoch for split-stakes only because probably we need to unstake multiple whole stakes for the same validator
@@ -230,13 +276,15 @@ impl<'info> DeactivateStake<'info> {
                     stake.stake_account
                 );
 
-                self.state.stake_system.add(
+                let split_stake_index = self.state.stake_system.add(
                     &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
                     &self.split_stake_account.key(),
                     split_amount,
                     &self.clock,
                     0, // is_emergency_unstaking? no
                 )?;
+                self.state
+                    .on_deactivating_stake_record_added(self.clock.epoch);
 
                 let split_instruction = stake::instruction::split(
                     self.stake_account.to_account_info().key,
@@ -277,7 +325,7 @@ impl<'info> DeactivateStake<'info> {
                 ))?;
 
                 stake.last_update_delegated_lamports -= split_amount;
-                (split_amount, false)
+                (split_amount, Some(split_stake_index))
             };
         // we now consider amount no longer "active" for this specific validator
         validator.active_balance -= unstaked_amount;
@@ -308,20 +356,22 @@ impl<'info> DeactivateStake<'info> {
             validator,
         )?;
 
+        pay_crank_reward(
+            &mut self.state,
+            &self.crank_reward_vault,
+            &self.crank_reward_receiver,
+            self.clock.epoch,
+        )?;
         emit!(DeactivateStakeEvent {
             state: self.state.key(),
             epoch: self.clock.epoch,
             stake_index,
             stake_account: self.stake_account.key(),
             last_update_stake_delegation,
-            split_stake_account: if deactivate_whole_stake {
-                None
-            } else {
-                Some(SplitStakeAccountInfo {
-                    account: self.split_stake_account.key(),
-                    index: self.state.stake_system.stake_count() - 1,
-                })
-            },
+            split_stake_account: split_stake_index.map(|index| SplitStakeAccountInfo {
+                account: self.split_stake_account.key(),
+                index,
+            }),
             validator_index,
             validator_vote: validator.validator_account,
             total_stake_target,
@@ -4,6 +4,8 @@ use anchor_lang::solana_program::{program::invoke_signed, stake};
 use anchor_spl::stake::{withdraw, Stake, StakeAccount, Withdraw};
 
 use crate::events::crank::MergeStakesEvent;
+use crate::pay_crank_reward;
+use crate::state::crank_reward_vault::CrankRewardVault;
 use crate::state::stake_system::StakeList;
 use crate::state::validator_system::ValidatorList;
 use crate::{error::StaderLiquidStakingError, state::stake_system::StakeSystem, State};
@@ -57,6 +59,12 @@ pub struct MergeStakes<'info> {
     pub stake_history: UncheckedAccount<'info>,
 
     pub stake_program: Program<'info, Stake>,
+
+    #[account(mut, has_one = state)]
+    pub crank_reward_vault: Option<Account<'info, CrankRewardVault>>,
+    /// CHECK: any writable account of the caller, receives the crank reward
+    #[account(mut)]
+    pub crank_reward_receiver: Option<UncheckedAccount<'info>>,
 }
 
 impl<'info> MergeStakes<'info> {
@@ -66,8 +74,17 @@ impl<'info> MergeStakes<'info> {
         source_stake_index: u32,
         validator_index: u32,
     ) -> Result<()> {
-        require!(!self.state.paused, StaderLiquidStakingError::ProgramIsPaused);
+        self.state.check_not_paused(State::PAUSE_CRANKS)?;
 
+        let validator_index = self.state.validator_system.resolve_index(
+            &self.validator_list.to_account_info().data.as_ref().borrow(),
+            validator_index,
+            &self
+                .destination_stake
+                .delegation()
+                .map(|delegation| delegation.voter_pubkey)
+                .unwrap_or_default(),
+        )?;
         let mut validator = self.state.validator_system.get(
             &self.validator_list.to_account_info().data.as_ref().borrow(),
             validator_index,
@@ -80,6 +97,11 @@ impl<'info> MergeStakes<'info> {
         let total_active_balance = self.state.validator_system.total_active_balance;
         let operational_sol_balance = self.operational_sol_account.lamports();
 
+        let destination_stake_index = self.state.stake_system.resolve_index(
+            &self.stake_list.to_account_info().data.as_ref().borrow(),
+            destination_stake_index,
+            self.destination_stake.to_account_info().key,
+        )?;
         let mut destination_stake_info = self.state.stake_system.get_checked(
             &self.stake_list.to_account_info().data.as_ref().borrow(),
             destination_stake_index,
@@ -113,6 +135,11 @@ impl<'info> MergeStakes<'info> {
         );
 
         // Source stake
+        let source_stake_index = self.state.stake_system.resolve_index(
+            &self.stake_list.to_account_info().data.as_ref().borrow(),
+            source_stake_index,
+            self.source_stake.to_account_info().key,
+        )?;
         let source_stake_info = self.state.stake_system.get_checked(
             &self.stake_list.to_account_info().data.as_ref().borrow(),
             source_stake_index,
@@ -204,6 +231,7 @@ impl<'info> MergeStakes<'info> {
             destination_stake_info,
         )?;
         // Call this last because of index invalidation
+        self.state.on_stake_record_removed(&source_stake_info, self.clock.epoch);
         self.state.stake_system.remove(
             &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
             source_stake_index,
@@ -230,6 +258,12 @@ impl<'info> MergeStakes<'info> {
                 None,
             )?;
         }
+        pay_crank_reward(
+            &mut self.state,
+            &self.crank_reward_vault,
+            &self.crank_reward_receiver,
+            self.clock.epoch,
+        )?;
         emit!(MergeStakesEvent {
             state: self.state.key(),
             epoch: self.clock.epoch,
@@ -1,21 +1,200 @@
 pub mod deactivate_stake;
 pub mod merge_stakes;
+pub mod rebalance;
 pub mod redelegate;
 pub mod stake_reserve;
+pub mod stake_reserve_multi;
+pub mod sweep_reserve_surplus;
 pub mod update_active;  
+pub mod update_active_batch;
 pub mod update_deactivated;
+pub mod update_deactivated_batch;
 
 pub use deactivate_stake::*;
 pub use merge_stakes::*;
+pub use rebalance::*;
 pub use redelegate::*;
 pub use stake_reserve::*;
+pub use stake_reserve_multi::*;
+pub use sweep_reserve_surplus::*;
 pub use update_active::*;
+pub use update_active_batch::*;
 pub use update_deactivated::*;
+pub use update_deactivated_batch::*;
 
-use crate::state::stake_system::StakeRecord;
+use anchor_lang::prelude::*;
+use anchor_spl::stake::{withdraw, Withdraw};
+use anchor_spl::token::{mint_to, MintTo};
+
+use crate::events::crank::CrankRewardEvent;
+use crate::state::circuit_breaker::CircuitBreaker;
+use crate::state::crank_reward_vault::CrankRewardVault;
+use crate::state::stake_system::{StakeRecord, StakeSystem};
+use crate::State;
 
 
 pub struct BeginOutput {
     stake: StakeRecord,
     is_treasury_stader_sol_ready_for_transfer: bool,
 }
+
+/// Pays the crank reward from the vault to the receiver when both are passed.
+/// An empty vault or a reached epoch cap is not an error, the crank itself must not fail
+pub fn pay_crank_reward<'info>(
+    state: &mut Account<'info, State>,
+    crank_reward_vault: &Option<Account<'info, CrankRewardVault>>,
+    crank_reward_receiver: &Option<UncheckedAccount<'info>>,
+    epoch: u64,
+) -> Result<()> {
+    let (vault, receiver) = match (crank_reward_vault, crank_reward_receiver) {
+        (Some(vault), Some(receiver)) => (vault.to_account_info(), receiver.to_account_info()),
+        _ => return Ok(()),
+    };
+    let amount = state.take_crank_reward(epoch, CrankRewardVault::available_lamports(&vault)?);
+    if amount == 0 {
+        return Ok(());
+    }
+    // the vault is owned by this program, no CPI needed
+    **vault.try_borrow_mut_lamports()? -= amount;
+    **receiver.try_borrow_mut_lamports()? += amount;
+    emit!(CrankRewardEvent {
+        state: state.key(),
+        epoch,
+        receiver: receiver.key(),
+        amount,
+        crank_rewards_paid_in_epoch: state.crank_rewards_paid_in_epoch,
+    });
+    Ok(())
+}
+
+/// Accounts shared by update_active, update_deactivated and their batches
+pub struct UpdateAccounts<'info> {
+    pub reserve_pda: AccountInfo<'info>,
+    pub stake_withdraw_authority: AccountInfo<'info>,
+    pub stader_sol_mint: AccountInfo<'info>,
+    pub stader_sol_mint_authority: AccountInfo<'info>,
+    pub treasury_stader_sol_account: AccountInfo<'info>,
+    pub clock: Sysvar<'info, Clock>,
+    pub stake_history: AccountInfo<'info>,
+    pub stake_program: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+}
+
+impl<'info> UpdateAccounts<'info> {
+    /// Checks the reserve and the staderSOL supply before an update, once per instruction.
+    /// Returns if the treasury can receive the protocol fee
+    pub fn begin(&self, state: &mut Account<'info, State>, stader_sol_mint_supply: u64) -> bool {
+        let is_treasury_stader_sol_ready_for_transfer = state
+            .get_treasury_stader_sol_balance(&self.treasury_stader_sol_account)
+            .is_some();
+
+        let virtual_reserve_balance =
+            state.available_reserve_balance + state.rent_exempt_for_token_acc;
+
+        // impossible to happen check outside bug
+        if self.reserve_pda.lamports() < virtual_reserve_balance {
+            msg!(
+                "Warning: Reserve must have {} lamports but got {}",
+                virtual_reserve_balance,
+                self.reserve_pda.lamports()
+            );
+            let state_address = state.key();
+            emit!(state.trip_circuit_breaker(
+                state_address,
+                CircuitBreaker::REASON_RESERVE_DEFICIT,
+                self.clock.epoch,
+                virtual_reserve_balance,
+                self.reserve_pda.lamports(),
+            ));
+            // the missing lamports are lost, align the virtual balance down
+            state.available_reserve_balance = self
+                .reserve_pda
+                .lamports()
+                .saturating_sub(state.rent_exempt_for_token_acc);
+        }
+        // a surplus is not absorbed here, see sweep_reserve_surplus
+        // Update staderSOL supply
+        // impossible to happen check outside bug (staderSOL mint auth is a PDA)
+        if stader_sol_mint_supply > state.stader_sol_supply {
+            msg!(
+                "Warning: staderSOL minted {} lamports outside of stader",
+                stader_sol_mint_supply - state.stader_sol_supply
+            );
+            let state_address = state.key();
+            let stader_sol_supply = state.stader_sol_supply;
+            emit!(state.trip_circuit_breaker(
+                state_address,
+                CircuitBreaker::REASON_EXTERNAL_MINT,
+                self.clock.epoch,
+                stader_sol_supply,
+                stader_sol_mint_supply,
+            ));
+        }
+        state.stader_sol_supply = stader_sol_mint_supply;
+
+        is_treasury_stader_sol_ready_for_transfer
+    }
+
+    /// Withdraws from the stake account to the reserve.
+    /// The caller accounts the lamports in the reserve, the batches do it once
+    pub fn withdraw_to_reserve(
+        &self,
+        state: &Account<'info, State>,
+        stake_account: &AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        withdraw(
+            CpiContext::new_with_signer(
+                self.stake_program.clone(),
+                Withdraw {
+                    stake: stake_account.clone(),
+                    withdrawer: self.stake_withdraw_authority.clone(),
+                    to: self.reserve_pda.clone(),
+                    clock: self.clock.to_account_info(),
+                    stake_history: self.stake_history.clone(),
+                },
+                &[&[
+                    &state.key().to_bytes(),
+                    StakeSystem::STAKE_WITHDRAW_SEED,
+                    &[state.stake_system.stake_withdraw_bump_seed],
+                ]],
+            ),
+            amount,
+            None,
+        )
+    }
+
+    /// Mints the protocol fee on `lamports_incoming` rewards to the treasury, returns it in staderSOL.
+    /// The fee is valued at the current price, so the caller mints it before adding the rewards
+    /// to the staked balance (update_active) or to the reserve (update_deactivated)
+    pub fn mint_protocol_fees(
+        &self,
+        state: &mut Account<'info, State>,
+        lamports_incoming: u64,
+    ) -> Result<u64> {
+        let protocol_rewards_fee = state.reward_fee.apply(lamports_incoming);
+        msg!("protocol_rewards_fee {}", protocol_rewards_fee);
+        // compute staderSOL amount for protocol_rewards_fee
+        let fee_as_stader_sol_amount = state.calc_stader_sol_from_lamports(protocol_rewards_fee)?;
+        if fee_as_stader_sol_amount > 0 {
+            mint_to(
+                CpiContext::new_with_signer(
+                    self.token_program.clone(),
+                    MintTo {
+                        mint: self.stader_sol_mint.clone(),
+                        to: self.treasury_stader_sol_account.clone(),
+                        authority: self.stader_sol_mint_authority.clone(),
+                    },
+                    &[&[
+                        &state.key().to_bytes(),
+                        State::STADER_SOL_MINT_AUTHORITY_SEED,
+                        &[state.stader_sol_mint_authority_bump_seed],
+                    ]],
+                ),
+                fee_as_stader_sol_amount,
+            )?;
+            state.on_stader_sol_mint(fee_as_stader_sol_amount);
+        }
+        Ok(fee_as_stader_sol_amount)
+    }
+}
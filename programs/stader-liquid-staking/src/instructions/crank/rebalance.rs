@@ -0,0 +1,386 @@
+use crate::{
+    checks::check_stake_amount_and_validator,
+    error::StaderLiquidStakingError,
+    events::{
+        crank::{RebalanceEvent, SplitStakeAccountInfo},
+        U64ValueChange,
+    },
+    state::{
+        directed_stake::DirectedStake,
+        stake_system::{StakeList, StakeSystem},
+        validator_system::ValidatorList,
+    },
+    RedelegationAccounts, State,
+};
+use std::convert::TryFrom;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    program::invoke_signed,
+    stake::{self, state::StakeStateV2},
+    system_program,
+};
+use anchor_spl::stake::{Stake, StakeAccount};
+
+/// Same accounts as ReDelegate, the amount is computed from the stake targets
+#[derive(Accounts)]
+pub struct Rebalance<'info> {
+    #[account(mut)]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+    #[account(
+        mut,
+        address = state.stake_system.stake_list.account,
+    )]
+    pub stake_list: Account<'info, StakeList>,
+    #[account(mut)]
+    pub stake_account: Box<Account<'info, StakeAccount>>,
+    /// CHECK: PDA
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            StakeSystem::STAKE_DEPOSIT_SEED
+        ],
+        bump = state.stake_system.stake_deposit_bump_seed
+    )]
+    pub stake_deposit_authority: UncheckedAccount<'info>,
+    // Readonly. For stake delta calculation
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            State::RESERVE_SEED
+        ],
+        bump = state.reserve_bump_seed
+    )]
+    pub reserve_pda: SystemAccount<'info>,
+
+    #[account(
+        init,
+        payer = split_stake_rent_payer,
+        space = StakeStateV2::size_of(),
+        owner = stake::program::ID,
+    )]
+    pub split_stake_account: Account<'info, StakeAccount>,
+    #[account(
+        mut,
+        owner = system_program::ID
+    )]
+    pub split_stake_rent_payer: Signer<'info>,
+
+    /// CHECK: compared to value stored in list
+    pub dest_validator_account: UncheckedAccount<'info>,
+    // new stake account to make the reDelegation
+    #[account(
+        init,
+        payer = split_stake_rent_payer,
+        space = StakeStateV2::size_of(),
+        owner = stake::program::ID,
+    )]
+    pub redelegate_stake_account: Account<'info, StakeAccount>,
+
+    pub clock: Sysvar<'info, Clock>,
+    /// CHECK: have no CPU budget to parse
+    pub stake_history: UncheckedAccount<'info>,
+    /// CHECK: have no CPU budget to parse
+    pub stake_config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub stake_program: Program<'info, Stake>,
+
+    /// CHECK: may not exist, see DirectedStake::load_shares
+    pub source_directed_stake: UncheckedAccount<'info>,
+    /// CHECK: may not exist, see DirectedStake::load_shares
+    pub dest_directed_stake: UncheckedAccount<'info>,
+}
+
+impl<'info> Rebalance<'info> {
+    /// Moves stake from a source validator over its stake target to a dest validator under it.
+    /// Both gaps must exceed State::rebalance_threshold. The amount is the smaller gap,
+    /// capped by the stake account, the stake moves left in the epoch and the max validator stake share
+    pub fn process(
+        &mut self,
+        stake_index: u32,
+        source_validator_index: u32,
+        dest_validator_index: u32,
+    ) -> Result<()> {
+        self.state.check_not_paused(State::PAUSE_STAKE_MOVES)?;
+        // during the wind-down the stake only leaves the validators
+        self.state.check_not_winding_down()?;
+        let redelegation = self.redelegation_accounts();
+
+        let stake_index = self.state.stake_system.resolve_index(
+            &self.stake_list.to_account_info().data.as_ref().borrow(),
+            stake_index,
+            self.stake_account.to_account_info().key,
+        )?;
+        let mut stake = self.state.stake_system.get_checked(
+            &self.stake_list.to_account_info().data.as_ref().borrow(),
+            stake_index,
+            self.stake_account.to_account_info().key,
+        )?;
+        require_eq!(
+            stake.is_emergency_unstaking,
+            0,
+            StaderLiquidStakingError::StakeAccountIsEmergencyUnstaking
+        );
+
+        let source_validator_index = self.state.validator_system.resolve_index(
+            &self.validator_list.to_account_info().data.as_ref().borrow(),
+            source_validator_index,
+            &self
+                .stake_account
+                .delegation()
+                .map(|delegation| delegation.voter_pubkey)
+                .unwrap_or_default(),
+        )?;
+        let mut source_validator = self.state.validator_system.get(
+            &self.validator_list.to_account_info().data.as_ref().borrow(),
+            source_validator_index,
+        )?;
+        // the stake is updated and delegated to the source validator
+        check_stake_amount_and_validator(
+            &self.stake_account,
+            stake.last_update_delegated_lamports,
+            &source_validator.validator_account,
+        )?;
+
+        let dest_validator_index = self.state.validator_system.resolve_index(
+            &self.validator_list.to_account_info().data.as_ref().borrow(),
+            dest_validator_index,
+            &self.dest_validator_account.key(),
+        )?;
+        require_neq!(
+            source_validator_index,
+            dest_validator_index,
+            StaderLiquidStakingError::SourceAndDestValidatorsAreTheSame
+        );
+        let mut dest_validator = self
+            .state
+            .validator_system
+            .get_checked(
+                &self.validator_list.to_account_info().data.as_ref().borrow(),
+                dest_validator_index,
+                &self.dest_validator_account.key(),
+            )
+            .map_err(|e| e.with_account_name("dest_validator_account"))?;
+
+        // same total target as redelegate: total active balance +/- stake delta
+        let total_stake_target = u64::try_from(
+            self.state.validator_system.total_active_balance as i128
+                + self.state.stake_delta(self.reserve_pda.lamports()),
+        )
+        .expect("total_stake_target+stake_delta");
+
+        let source_validator_directed_lamports = self.state.validator_directed_lamports(
+            &source_validator,
+            DirectedStake::load_shares(
+                &self.source_directed_stake,
+                &self.state.key(),
+                &source_validator.validator_account,
+            )
+            .map_err(|e| e.with_account_name("source_directed_stake"))?,
+        )?;
+        let source_validator_stake_target = self.state.validator_system.validator_stake_target(
+            &source_validator,
+            total_stake_target,
+            self.state.max_validator_stake_share,
+            source_validator_directed_lamports,
+            self.state.honored_directed_lamports()?,
+        )?;
+        let dest_validator_directed_lamports = self.state.validator_directed_lamports(
+            &dest_validator,
+            DirectedStake::load_shares(
+                &self.dest_directed_stake,
+                &self.state.key(),
+                &dest_validator.validator_account,
+            )
+            .map_err(|e| e.with_account_name("dest_directed_stake"))?,
+        )?;
+        let dest_validator_stake_target = self.state.validator_system.validator_stake_target(
+            &dest_validator,
+            total_stake_target,
+            self.state.max_validator_stake_share,
+            dest_validator_directed_lamports,
+            self.state.honored_directed_lamports()?,
+        )?;
+
+        let source_excess = source_validator
+            .active_balance
+            .saturating_sub(source_validator_stake_target);
+        let dest_shortfall =
+            dest_validator_stake_target.saturating_sub(dest_validator.active_balance);
+        if source_excess
+            <= self
+                .state
+                .rebalance_threshold(source_validator_stake_target)
+            || dest_shortfall <= self.state.rebalance_threshold(dest_validator_stake_target)
+        {
+            msg!(
+                "Source validator {} is over target by {}, dest validator {} is under target by {}. Nothing to rebalance",
+                source_validator.validator_account,
+                source_excess,
+                dest_validator.validator_account,
+                dest_shortfall
+            );
+            redelegation
+                .return_rent_unused_stake_account(self.split_stake_account.to_account_info())?;
+            redelegation.return_rent_unused_stake_account(
+                self.redelegate_stake_account.to_account_info(),
+            )?;
+            return Ok(()); // Not an error. Don't fail other instructions in tx
+        }
+
+        let (amount, move_whole_account) = self.state.rebalance_amount(
+            self.clock.epoch,
+            source_excess,
+            dest_shortfall,
+            dest_validator.active_balance,
+            stake.last_update_delegated_lamports,
+        );
+        let min_stake = self.state.stake_system.min_stake;
+        if amount < min_stake {
+            msg!(
+                "Rebalance amount {} is lower than min stake {}",
+                amount,
+                min_stake
+            );
+            redelegation
+                .return_rent_unused_stake_account(self.split_stake_account.to_account_info())?;
+            redelegation.return_rent_unused_stake_account(
+                self.redelegate_stake_account.to_account_info(),
+            )?;
+            return Ok(()); // Not an error. Don't fail other instructions in tx
+        }
+        self.state.on_stake_moved(amount, &self.clock)?;
+
+        let (source_account, split_stake_index) = if move_whole_account {
+            msg!("Rebalance whole stake {}", stake.stake_account);
+            redelegation
+                .return_rent_unused_stake_account(self.split_stake_account.to_account_info())?;
+            // the account enters redelegate-deactivating mode, see ReDelegate
+            stake.last_update_delegated_lamports = 0;
+            self.state
+                .on_stake_record_deactivating(&stake, self.clock.epoch);
+            (self.stake_account.to_account_info(), None)
+        } else {
+            let split_stake_index =
+                redelegation.split_stake_for_redelegation(&mut self.state, &mut stake, amount)?;
+            (
+                self.split_stake_account.to_account_info(),
+                Some(split_stake_index),
+            )
+        };
+
+        let redelegate_instruction = &stake::instruction::redelegate(
+            &source_account.key(),
+            &self.stake_deposit_authority.key(),
+            &self.dest_validator_account.key(),
+            &self.redelegate_stake_account.key(),
+        )
+        .last()
+        .unwrap()
+        .clone();
+        invoke_signed(
+            redelegate_instruction,
+            &[
+                source_account.clone(),
+                self.dest_validator_account.to_account_info(),
+                self.redelegate_stake_account.to_account_info(),
+                self.stake_config.to_account_info(),
+                self.stake_deposit_authority.to_account_info(),
+            ],
+            &[&[
+                &self.state.key().to_bytes(),
+                StakeSystem::STAKE_DEPOSIT_SEED,
+                &[self.state.stake_system.stake_deposit_bump_seed],
+            ]],
+        )?;
+
+        let redelegate_stake_index = self.state.stake_system.add(
+            &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
+            &self.redelegate_stake_account.key(),
+            amount,
+            &self.clock,
+            0, // is_emergency_unstaking
+        )?;
+        self.state.on_stake_record_added(self.clock.epoch);
+
+        source_validator.active_balance -= amount;
+        dest_validator.active_balance += amount;
+
+        self.state.stake_system.set(
+            &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
+            stake_index,
+            stake,
+        )?;
+        self.state.validator_system.set(
+            &mut self
+                .validator_list
+                .to_account_info()
+                .data
+                .as_ref()
+                .borrow_mut(),
+            source_validator_index,
+            source_validator,
+        )?;
+        self.state.validator_system.set(
+            &mut self
+                .validator_list
+                .to_account_info()
+                .data
+                .as_ref()
+                .borrow_mut(),
+            dest_validator_index,
+            dest_validator,
+        )?;
+
+        emit!(RebalanceEvent {
+            state: self.state.key(),
+            epoch: self.clock.epoch,
+            stake_index,
+            stake_account: self.stake_account.key(),
+            source_validator_index,
+            source_validator_vote: source_validator.validator_account,
+            source_validator_stake_target,
+            source_validator_gap: U64ValueChange {
+                old: source_excess,
+                new: source_validator
+                    .active_balance
+                    .saturating_sub(source_validator_stake_target),
+            },
+            dest_validator_index,
+            dest_validator_vote: dest_validator.validator_account,
+            dest_validator_stake_target,
+            dest_validator_gap: U64ValueChange {
+                old: dest_shortfall,
+                new: dest_validator_stake_target.saturating_sub(dest_validator.active_balance),
+            },
+            amount,
+            split_stake_account: split_stake_index.map(|index| SplitStakeAccountInfo {
+                account: self.split_stake_account.key(),
+                index,
+            }),
+            redelegate_stake_index,
+            redelegate_stake_account: self.redelegate_stake_account.key(),
+        });
+
+        Ok(())
+    }
+
+    fn redelegation_accounts(&self) -> RedelegationAccounts<'info> {
+        RedelegationAccounts {
+            stake_list: self.stake_list.to_account_info(),
+            stake_account: self.stake_account.to_account_info(),
+            stake_deposit_authority: self.stake_deposit_authority.to_account_info(),
+            split_stake_account: self.split_stake_account.to_account_info(),
+            split_stake_rent_payer: self.split_stake_rent_payer.to_account_info(),
+            clock: self.clock.clone(),
+            stake_history: self.stake_history.to_account_info(),
+            stake_program: self.stake_program.to_account_info(),
+        }
+    }
+}
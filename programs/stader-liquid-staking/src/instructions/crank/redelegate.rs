@@ -3,6 +3,7 @@ use crate::{
     error::StaderLiquidStakingError,
     events::crank::{RedelegateEvent, SplitStakeAccountInfo},
     state::{
+        directed_stake::DirectedStake,
         stake_system::{StakeList, StakeRecord, StakeSystem},
         validator_system::ValidatorList,
     },
@@ -85,6 +86,11 @@ pub struct ReDelegate<'info> {
 
     pub system_program: Program<'info, System>,
     pub stake_program: Program<'info, Stake>,
+
+    /// CHECK: may not exist, see DirectedStake::load_shares
+    pub source_directed_stake: UncheckedAccount<'info>,
+    /// CHECK: may not exist, see DirectedStake::load_shares
+    pub dest_directed_stake: UncheckedAccount<'info>,
 }
 
 impl<'info> ReDelegate<'info> {
@@ -105,7 +111,9 @@ impl<'info> ReDelegate<'info> {
         source_validator_index: u32,
         dest_validator_index: u32,
     ) -> Result<()> {
-        require!(!self.state.paused, StaderLiquidStakingError::ProgramIsPaused);
+        self.state.check_not_paused(State::PAUSE_STAKE_MOVES)?;
+        // during the wind-down the stake only leaves the validators
+        self.state.check_not_winding_down()?;
 
         require_neq!(
             source_validator_index,
@@ -125,6 +133,11 @@ impl<'info> ReDelegate<'info> {
             // );
         }
 
+        let stake_index = self.state.stake_system.resolve_index(
+            &self.stake_list.to_account_info().data.as_ref().borrow(),
+            stake_index,
+            self.stake_account.to_account_info().key,
+        )?;
         let mut stake = self.state.stake_system.get_checked(
             &self.stake_list.to_account_info().data.as_ref().borrow(),
             stake_index,
@@ -139,6 +152,15 @@ impl<'info> ReDelegate<'info> {
             StaderLiquidStakingError::StakeAccountIsEmergencyUnstaking
         );
 
+        let source_validator_index = self.state.validator_system.resolve_index(
+            &self.validator_list.to_account_info().data.as_ref().borrow(),
+            source_validator_index,
+            &self
+                .stake_account
+                .delegation()
+                .map(|delegation| delegation.voter_pubkey)
+                .unwrap_or_default(),
+        )?;
         let mut source_validator = self.state.validator_system.get(
             &self.validator_list.to_account_info().data.as_ref().borrow(),
             source_validator_index,
@@ -163,10 +185,25 @@ impl<'info> ReDelegate<'info> {
             u64::try_from(total_stake_target_i128).expect("total_stake_target+stake_delta");
 
         // compute target for this particular validator (total_stake_target * score/total_score)
+        let source_validator_directed_lamports = self.state.validator_directed_lamports(
+            &source_validator,
+            DirectedStake::load_shares(
+                &self.source_directed_stake,
+                &self.state.key(),
+                &source_validator.validator_account,
+            )
+            .map_err(|e| e.with_account_name("source_directed_stake"))?,
+        )?;
         let source_validator_stake_target = self
             .state
             .validator_system
-            .validator_stake_target(&source_validator, total_stake_target)?;
+            .validator_stake_target(
+                &source_validator,
+                total_stake_target,
+                self.state.max_validator_stake_share,
+                source_validator_directed_lamports,
+                self.state.honored_directed_lamports()?,
+            )?;
         // if validator is already on-target (or the split will be lower than min_stake), exit now
         if source_validator.active_balance
             < source_validator_stake_target + self.state.stake_system.min_stake
@@ -191,6 +228,11 @@ impl<'info> ReDelegate<'info> {
         );
 
         // get dest validator from index
+        let dest_validator_index = self.state.validator_system.resolve_index(
+            &self.validator_list.to_account_info().data.as_ref().borrow(),
+            dest_validator_index,
+            &self.dest_validator_account.key(),
+        )?;
         let mut dest_validator = self
             .state
             .validator_system
@@ -203,10 +245,25 @@ impl<'info> ReDelegate<'info> {
         let dest_validator_balance = dest_validator.active_balance;
 
         // compute dest validator target
+        let dest_validator_directed_lamports = self.state.validator_directed_lamports(
+            &dest_validator,
+            DirectedStake::load_shares(
+                &self.dest_directed_stake,
+                &self.state.key(),
+                &dest_validator.validator_account,
+            )
+            .map_err(|e| e.with_account_name("dest_directed_stake"))?,
+        )?;
         let dest_validator_stake_target = self
             .state
             .validator_system
-            .validator_stake_target(&dest_validator, total_stake_target)?;
+            .validator_stake_target(
+                &dest_validator,
+                total_stake_target,
+                self.state.max_validator_stake_share,
+                dest_validator_directed_lamports,
+                self.state.honored_directed_lamports()?,
+            )?;
         // verify: dest validator must be under target
         if dest_validator.active_balance + self.state.stake_system.min_stake
             > dest_validator_stake_target
@@ -235,7 +292,7 @@ impl<'info> ReDelegate<'info> {
             stake.last_update_delegated_lamports - redelegate_amount_theoretical;
         // select if we redelegate all or if we split first
         // (do not leave less than min_stake in the account)
-        let (source_account, redelegate_amount_effective) =
+        let (source_account, redelegate_amount_effective, split_stake_index) =
             if stake_account_after < self.state.stake_system.min_stake {
                 // redelegate all if what will remain in the account is < min_stake
                 msg!("ReDelegate whole stake {}", stake.stake_account);
@@ -251,24 +308,36 @@ impl<'info> ReDelegate<'info> {
                 // so we set last_update_delegated_lamports = 0 because all lamports are gone
                 // after completing deactivation, whatever is there minus rent is considered last rewards for the account
                 stake.last_update_delegated_lamports = 0;
+                self.state
+                    .on_stake_record_deactivating(&stake, self.clock.epoch);
 
                 // account to redelegate is the whole source account
                 (
                     self.stake_account.to_account_info(),
                     amount_to_redelegate_whole_account,
+                    None,
                 )
                 //
                 //
             } else {
                 // not whole account,
                 // we need to split first
-                self.split_stake_for_redelegation(&mut stake, redelegate_amount_theoretical)?;
+                let split_stake_index =
+                    self.split_stake_for_redelegation(&mut stake, redelegate_amount_theoretical)?;
                 // account to redelegate is the splitted account
                 (
                     self.split_stake_account.to_account_info(),
                     redelegate_amount_theoretical,
+                    Some(split_stake_index),
                 )
             };
+        // moving the whole account can exceed the dest target by less than min_stake
+        require_gte!(
+            self.state
+                .max_validator_stake(self.state.validator_system.total_active_balance),
+            dest_validator.active_balance + redelegate_amount_effective,
+            StaderLiquidStakingError::ValidatorStakeShareExceeded
+        );
         self.state
             .on_stake_moved(redelegate_amount_effective, &self.clock)?;
 
@@ -301,13 +370,14 @@ impl<'info> ReDelegate<'info> {
 
         // add new warming-up re-delegated account to Stader stake-accounts list
         // warn - the lamports are accounted here, and no longer in the source account
-        self.state.stake_system.add(
+        let redelegate_stake_index = self.state.stake_system.add(
             &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
             &self.redelegate_stake_account.key(),
             redelegate_amount_effective,
             &self.clock,
             0, // is_emergency_unstaking
         )?;
+        self.state.on_stake_record_added(self.clock.epoch);
 
         // we now consider amount no longer "active" for this specific validator
         source_validator.active_balance -= redelegate_amount_effective;
@@ -358,21 +428,64 @@ impl<'info> ReDelegate<'info> {
             dest_validator_balance,
             dest_validator_stake_target,
             redelegate_amount: redelegate_amount_effective,
-            split_stake_account: if source_account.key() == self.split_stake_account.key() {
-                Some(SplitStakeAccountInfo {
-                    account: self.split_stake_account.key(),
-                    index: self.state.stake_system.stake_count() - 2,
-                })
-            } else {
-                None
-            },
-            redelegate_stake_index: self.state.stake_system.stake_count() - 1,
+            split_stake_account: split_stake_index.map(|index| SplitStakeAccountInfo {
+                account: self.split_stake_account.key(),
+                index,
+            }),
+            redelegate_stake_index,
             redelegate_stake_account: self.redelegate_stake_account.key(),
         });
 
         Ok(())
     }
 
+    fn redelegation_accounts(&self) -> RedelegationAccounts<'info> {
+        RedelegationAccounts {
+            stake_list: self.stake_list.to_account_info(),
+            stake_account: self.stake_account.to_account_info(),
+            stake_deposit_authority: self.stake_deposit_authority.to_account_info(),
+            split_stake_account: self.split_stake_account.to_account_info(),
+            split_stake_rent_payer: self.split_stake_rent_payer.to_account_info(),
+            clock: self.clock.clone(),
+            stake_history: self.stake_history.to_account_info(),
+            stake_program: self.stake_program.to_account_info(),
+        }
+    }
+
+    pub fn return_rent_unused_stake_account(
+        &self,
+        unused_stake_account: AccountInfo<'info>,
+    ) -> Result<()> {
+        self.redelegation_accounts()
+            .return_rent_unused_stake_account(unused_stake_account)
+    }
+
+    /// Returns the stake list index of the split account
+    #[inline] // separated for readability
+    pub fn split_stake_for_redelegation(
+        &mut self,
+        stake: &mut StakeRecord,
+        amount: u64,
+    ) -> Result<u32> {
+        self.redelegation_accounts()
+            .split_stake_for_redelegation(&mut self.state, stake, amount)
+    }
+}
+
+/// Accounts splitting the source stake and refunding the unused stake accounts,
+/// shared by redelegate and rebalance
+pub struct RedelegationAccounts<'info> {
+    pub stake_list: AccountInfo<'info>,
+    pub stake_account: AccountInfo<'info>,
+    pub stake_deposit_authority: AccountInfo<'info>,
+    pub split_stake_account: AccountInfo<'info>,
+    pub split_stake_rent_payer: AccountInfo<'info>,
+    pub clock: Sysvar<'info, Clock>,
+    pub stake_history: AccountInfo<'info>,
+    pub stake_program: AccountInfo<'info>,
+}
+
+impl<'info> RedelegationAccounts<'info> {
     pub fn return_rent_unused_stake_account(
         &self,
         unused_stake_account: AccountInfo<'info>,
@@ -380,13 +493,13 @@ impl<'info> ReDelegate<'info> {
         // Return back the rent reserve of unused stake account (split or redelegate reserve)
         withdraw(
             CpiContext::new(
-                self.stake_program.to_account_info(),
+                self.stake_program.clone(),
                 Withdraw {
                     stake: unused_stake_account.clone(),
                     withdrawer: unused_stake_account.clone(),
-                    to: self.split_stake_rent_payer.to_account_info(),
+                    to: self.split_stake_rent_payer.clone(),
                     clock: self.clock.to_account_info(),
-                    stake_history: self.stake_history.to_account_info(),
+                    stake_history: self.stake_history.clone(),
                 },
             ),
             unused_stake_account.lamports(),
@@ -394,12 +507,14 @@ impl<'info> ReDelegate<'info> {
         )
     }
 
-    #[inline] // separated for readability
+    /// Splits `amount` from the stake account into the split stake account, which is redelegated.
+    /// Returns the stake list index of the split account
     pub fn split_stake_for_redelegation(
-        &mut self,
+        &self,
+        state: &mut Account<'info, State>,
         stake: &mut StakeRecord,
         amount: u64,
-    ) -> Result<()> {
+    ) -> Result<u32> {
         msg!(
             "Split {} lamports from stake {} to {}",
             amount,
@@ -408,9 +523,9 @@ impl<'info> ReDelegate<'info> {
         );
 
         // add the split account as new account to Stader stake-accounts list
-        self.state.stake_system.add(
-            &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
-            &self.split_stake_account.key(),
+        let split_stake_index = state.stake_system.add(
+            &mut self.stake_list.data.as_ref().borrow_mut(),
+            self.split_stake_account.key,
             0, // this account will be deactivating,
             // all lamports will be moved to the re-delegated account,
             // but even with no lamports, we expect the redelegate-deactivating account to provide rewards at the end of the epoch.
@@ -419,13 +534,14 @@ impl<'info> ReDelegate<'info> {
             // TODO: deprecate "is_emergency_unstaking"
             0,
         )?;
+        state.on_deactivating_stake_record_added(self.clock.epoch);
 
         // split stake account
         let split_instruction = stake::instruction::split(
-            self.stake_account.to_account_info().key,
-            &self.stake_deposit_authority.key(),
+            self.stake_account.key,
+            self.stake_deposit_authority.key,
             amount,
-            &self.split_stake_account.key(),
+            self.split_stake_account.key,
         )
         .last()
         .unwrap()
@@ -433,21 +549,21 @@ impl<'info> ReDelegate<'info> {
         invoke_signed(
             &split_instruction,
             &[
-                self.stake_program.to_account_info(),
-                self.stake_account.to_account_info(),
-                self.split_stake_account.to_account_info(),
-                self.stake_deposit_authority.to_account_info(),
+                self.stake_program.clone(),
+                self.stake_account.clone(),
+                self.split_stake_account.clone(),
+                self.stake_deposit_authority.clone(),
             ],
             &[&[
-                &self.state.key().to_bytes(),
+                &state.key().to_bytes(),
                 StakeSystem::STAKE_DEPOSIT_SEED,
-                &[self.state.stake_system.stake_deposit_bump_seed],
+                &[state.stake_system.stake_deposit_bump_seed],
             ]],
         )?;
 
         // update amount accounted for source stake account
         stake.last_update_delegated_lamports -= amount;
 
-        Ok(())
+        Ok(split_stake_index)
     }
 }
@@ -1,9 +1,13 @@
 use crate::{
     error::StaderLiquidStakingError,
     events::crank::StakeReserveEvent,
+    pay_crank_reward,
     state::{
+        crank_reward_vault::CrankRewardVault,
+        directed_stake::DirectedStake,
         stake_system::{StakeList, StakeSystem},
-        validator_system::ValidatorList,
+        validator_system::{ValidatorList, ValidatorRecord},
+        vote_account::VoteAccountSummary,
     },
     State, ID,
 };
@@ -86,6 +90,15 @@ pub struct StakeReserve<'info> {
 
     pub system_program: Program<'info, System>,
     pub stake_program: Program<'info, Stake>,
+
+    /// CHECK: may not exist, see DirectedStake::load_shares
+    pub directed_stake: UncheckedAccount<'info>,
+
+    #[account(mut, has_one = state)]
+    pub crank_reward_vault: Option<Account<'info, CrankRewardVault>>,
+    /// CHECK: any writable account of the caller, receives the crank reward
+    #[account(mut)]
+    pub crank_reward_receiver: Option<UncheckedAccount<'info>>,
 }
 
 impl<'info> StakeReserve<'info> {
@@ -94,33 +107,14 @@ impl<'info> StakeReserve<'info> {
     /// stakes from available delta-stake in data.validator_index
     /// pub fn stake_reserve()
     pub fn process(&mut self, validator_index: u32) -> Result<()> {
-        require!(!self.state.paused, StaderLiquidStakingError::ProgramIsPaused);
+        self.state.check_not_paused(State::PAUSE_CRANKS)?;
+        self.state.check_not_winding_down()?;
 
         sol_log_compute_units();
 
         // record for event
         let total_active_balance = self.state.validator_system.total_active_balance;
 
-        let staker = Pubkey::create_program_address(
-            &[
-                &self.state.key().to_bytes(),
-                StakeSystem::STAKE_DEPOSIT_SEED,
-                &[self.state.stake_system.stake_deposit_bump_seed],
-            ],
-            &ID,
-        )
-        .unwrap();
-
-        let withdrawer = Pubkey::create_program_address(
-            &[
-                &self.state.key().to_bytes(),
-                StakeSystem::STAKE_WITHDRAW_SEED,
-                &[self.state.stake_system.stake_withdraw_bump_seed],
-            ],
-            &ID,
-        )
-        .unwrap();
-
         let reserve_balance = self.reserve_pda.lamports();
         let stake_delta = self.state.stake_delta(reserve_balance);
         if stake_delta <= 0 {
@@ -138,33 +132,21 @@ impl<'info> StakeReserve<'info> {
         let total_stake_delta = u64::try_from(stake_delta).expect("Stake delta overflow");
         let total_stake_target = total_active_balance.saturating_add(total_stake_delta);
 
-        let mut validator = self
-            .state
-            .validator_system
-            .get_checked(
-                &self.validator_list.to_account_info().data.as_ref().borrow(),
-                validator_index,
-                self.validator_vote.key,
-            )
-            .map_err(|e| e.with_account_name("validator_vote"))?;
+        let staking = self.reserve_staking();
+        let candidate = if let Some(candidate) = staking.candidate(
+            &self.state,
+            validator_index,
+            &self.validator_vote,
+            &self.directed_stake,
+            total_stake_target,
+        )? {
+            candidate
+        } else {
+            self.return_unused_stake_account_rent()?;
+            return Ok(()); // Not an error. Don't fail other instructions in tx
+        };
         // record for event
-        let validator_active_balance = validator.active_balance;
-
-        if validator.last_stake_delta_epoch == self.clock.epoch {
-            // check if we have some extra stake runs allowed
-            if self.state.stake_system.extra_stake_delta_runs == 0 {
-                msg!(
-                    "Double delta stake command for validator {} in epoch {}",
-                    validator.validator_account,
-                    self.clock.epoch
-                );
-                self.return_unused_stake_account_rent()?;
-                return Ok(()); // Not an error. Don't fail other instructions in tx
-            } else {
-                // some extra runs allowed. Use one
-                self.state.stake_system.extra_stake_delta_runs -= 1;
-            }
-        }
+        let validator_active_balance = candidate.validator.active_balance;
 
         let last_slot = self.epoch_schedule.get_last_slot_in_epoch(self.clock.epoch);
 
@@ -174,35 +156,11 @@ impl<'info> StakeReserve<'info> {
             StaderLiquidStakingError::TooEarlyForStakeDelta
         );
 
-        let validator_stake_target = self
-            .state
-            .validator_system
-            .validator_stake_target(&validator, total_stake_target)?;
-
-        //verify the validator is under-staked
-        if validator_active_balance >= validator_stake_target {
-            msg!(
-                    "Validator {} has already reached stake target {}. Please stake into another validator",
-                    validator.validator_account,
-                    validator_stake_target
-                );
-            self.return_unused_stake_account_rent()?;
-            return Ok(()); // Not an error. Don't fail other instructions in tx
-        }
-
-        // compute stake_target
-        // stake_target = validator_target - validator_actual_balance, at most total_stake_delta
-        let stake_target = validator_stake_target
-            .saturating_sub(validator_active_balance)
-            .min(total_stake_delta);
-
-        // if what's left in stake_delta after this operation is < state.min_stake, take all the remainder from stake_delta
-        let stake_target = if total_stake_delta - stake_target < self.state.stake_system.min_stake {
-            total_stake_delta
-        } else {
-            stake_target
-        };
-
+        let stake_target = self.state.reserve_stake_amount(
+            candidate.missing_stake(),
+            total_stake_delta,
+            validator_active_balance,
+        );
         // if the amount to stake is < stake_system.min_stake (e.g. less than 1 SOL)
         // we don't stake to avoid creating a stake account with less than 1 SOL
         if stake_target < self.state.stake_system.min_stake {
@@ -215,124 +173,295 @@ impl<'info> StakeReserve<'info> {
             return Ok(()); // Not an error. Don't fail other instructions in tx
         }
 
+        let stake_index = staking.stake(
+            &mut self.state,
+            &candidate,
+            &self.stake_account.to_account_info(),
+            &self.validator_vote,
+            stake_target,
+        )?;
+
+        pay_crank_reward(
+            &mut self.state,
+            &self.crank_reward_vault,
+            &self.crank_reward_receiver,
+            self.clock.epoch,
+        )?;
+        emit!(StakeReserveEvent {
+            state: self.state.key(),
+            epoch: self.clock.epoch,
+            stake_index,
+            stake_account: self.stake_account.key(),
+            validator_index: candidate.validator_index,
+            validator_vote: self.validator_vote.key(),
+            amount: stake_target,
+            total_stake_target,
+            validator_stake_target: candidate.validator_stake_target,
+            reserve_balance,
+            total_active_balance,
+            validator_active_balance,
+            total_stake_delta,
+        });
+        Ok(())
+    }
+
+    fn reserve_staking(&self) -> ReserveStaking<'info> {
+        ReserveStaking {
+            validator_list: self.validator_list.to_account_info(),
+            stake_list: self.stake_list.to_account_info(),
+            reserve_pda: self.reserve_pda.to_account_info(),
+            stake_deposit_authority: self.stake_deposit_authority.to_account_info(),
+            clock: self.clock.clone(),
+            rent: self.rent.to_account_info(),
+            stake_history: self.stake_history.to_account_info(),
+            stake_config: self.stake_config.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            stake_program: self.stake_program.to_account_info(),
+        }
+    }
+
+    pub fn return_unused_stake_account_rent(&self) -> Result<()> {
+        // Return back the rent reserve of unused stake account in case of early return
+        withdraw(
+            CpiContext::new(
+                self.stake_program.to_account_info(),
+                Withdraw {
+                    stake: self.stake_account.to_account_info(),
+                    withdrawer: self.stake_account.to_account_info(),
+                    to: self.rent_payer.to_account_info(),
+                    clock: self.clock.to_account_info(),
+                    stake_history: self.stake_history.to_account_info(),
+                },
+            ),
+            self.stake_account.to_account_info().lamports(),
+            None,
+        )
+    }
+}
+
+/// A validator able to receive stake from the reserve now, see ReserveStaking::candidate
+pub struct ReserveStakeCandidate {
+    pub validator_index: u32,
+    pub validator: ValidatorRecord,
+    pub validator_stake_target: u64,
+}
+
+impl ReserveStakeCandidate {
+    pub fn missing_stake(&self) -> u64 {
+        self.validator_stake_target - self.validator.active_balance
+    }
+}
+
+/// Accounts staking the reserve into new stake accounts, shared by stake_reserve and stake_reserve_multi
+pub struct ReserveStaking<'info> {
+    pub validator_list: AccountInfo<'info>,
+    pub stake_list: AccountInfo<'info>,
+    pub reserve_pda: AccountInfo<'info>,
+    pub stake_deposit_authority: AccountInfo<'info>,
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: AccountInfo<'info>,
+    pub stake_history: AccountInfo<'info>,
+    pub stake_config: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub stake_program: AccountInfo<'info>,
+}
+
+impl<'info> ReserveStaking<'info> {
+    /// Checks the validator and records its commission in the metadata.
+    /// None (logged) when it can't receive stake now: commission over the max,
+    /// already staked this epoch without extra runs left or stake target reached
+    pub fn candidate(
+        &self,
+        state: &Account<'info, State>,
+        validator_index: u32,
+        validator_vote: &AccountInfo<'info>,
+        directed_stake: &AccountInfo<'info>,
+        total_stake_target: u64,
+    ) -> Result<Option<ReserveStakeCandidate>> {
+        let validator_index = state.validator_system.resolve_index(
+            &self.validator_list.data.as_ref().borrow(),
+            validator_index,
+            validator_vote.key,
+        )?;
+        let validator = state
+            .validator_system
+            .get_checked(
+                &self.validator_list.data.as_ref().borrow(),
+                validator_index,
+                validator_vote.key,
+            )
+            .map_err(|e| e.with_account_name("validator_vote"))?;
+
+        let vote = VoteAccountSummary::parse(&validator_vote.data.borrow())
+            .map_err(|e| e.with_account_name("validator_vote"))?;
+        state.validator_system.update_metadata(
+            &mut self.validator_list.data.borrow_mut(),
+            validator_index,
+            |metadata| metadata.observe_commission(vote.commission),
+        )?;
+        if state.is_validator_commission_too_high(vote.commission) {
+            msg!(
+                "Validator {} commission {}% is over the max {}%",
+                validator.validator_account,
+                vote.commission,
+                state.max_validator_commission
+            );
+            return Ok(None);
+        }
+
+        if validator.last_stake_delta_epoch == self.clock.epoch
+            && state.stake_system.extra_stake_delta_runs == 0
+        {
+            msg!(
+                "Double delta stake command for validator {} in epoch {}",
+                validator.validator_account,
+                self.clock.epoch
+            );
+            return Ok(None);
+        }
+
+        let validator_directed_lamports = state.validator_directed_lamports(
+            &validator,
+            DirectedStake::load_shares(directed_stake, &state.key(), &validator.validator_account)
+                .map_err(|e| e.with_account_name("directed_stake"))?,
+        )?;
+        let validator_stake_target = state.validator_system.validator_stake_target(
+            &validator,
+            total_stake_target,
+            state.max_validator_stake_share,
+            validator_directed_lamports,
+            state.honored_directed_lamports()?,
+        )?;
+        //verify the validator is under-staked
+        if validator.active_balance >= validator_stake_target {
+            msg!(
+                "Validator {} has already reached stake target {}. Please stake into another validator",
+                validator.validator_account,
+                validator_stake_target
+            );
+            return Ok(None);
+        }
+
+        Ok(Some(ReserveStakeCandidate {
+            validator_index,
+            validator,
+            validator_stake_target,
+        }))
+    }
+
+    /// Moves `amount` from the reserve into the rent exempt `stake_account`,
+    /// initializes and delegates it to the candidate, then records the new stake.
+    /// Uses an extra stake delta run when the validator was already staked this epoch.
+    /// Returns the index of the stake record
+    pub fn stake(
+        &self,
+        state: &mut Account<'info, State>,
+        candidate: &ReserveStakeCandidate,
+        stake_account: &AccountInfo<'info>,
+        validator_vote: &AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<u32> {
+        let mut validator = candidate.validator;
+        if validator.last_stake_delta_epoch == self.clock.epoch {
+            // some extra runs allowed. Use one
+            state.stake_system.extra_stake_delta_runs -= 1;
+        }
+
+        let staker = Pubkey::create_program_address(
+            &[
+                &state.key().to_bytes(),
+                StakeSystem::STAKE_DEPOSIT_SEED,
+                &[state.stake_system.stake_deposit_bump_seed],
+            ],
+            &ID,
+        )
+        .unwrap();
+        let withdrawer = Pubkey::create_program_address(
+            &[
+                &state.key().to_bytes(),
+                StakeSystem::STAKE_WITHDRAW_SEED,
+                &[state.stake_system.stake_withdraw_bump_seed],
+            ],
+            &ID,
+        )
+        .unwrap();
+
         // transfer SOL from reserve_pda to the stake-account
         sol_log_compute_units();
         msg!("Transfer to stake account");
         transfer(
             CpiContext::new_with_signer(
-                self.system_program.to_account_info(),
+                self.system_program.clone(),
                 Transfer {
-                    from: self.reserve_pda.to_account_info(),
-                    to: self.stake_account.to_account_info(),
+                    from: self.reserve_pda.clone(),
+                    to: stake_account.clone(),
                 },
                 &[&[
-                    &self.state.key().to_bytes(),
+                    &state.key().to_bytes(),
                     State::RESERVE_SEED,
-                    &[self.state.reserve_bump_seed],
+                    &[state.reserve_bump_seed],
                 ]],
             ),
-            stake_target,
+            amount,
         )?;
-        self.state.on_transfer_from_reserve(stake_target);
+        state.on_transfer_from_reserve(amount);
 
         sol_log_compute_units();
         msg!("Initialize stake");
         invoke(
             &stake::instruction::initialize(
-                &self.stake_account.key(),
+                stake_account.key,
                 &Authorized { staker, withdrawer },
                 &Lockup::default(),
             ),
             &[
-                self.stake_program.to_account_info(),
-                self.stake_account.to_account_info(),
-                self.rent.to_account_info(),
+                self.stake_program.clone(),
+                stake_account.clone(),
+                self.rent.clone(),
             ],
         )?;
 
         sol_log_compute_units();
         msg!("Delegate stake");
         invoke_signed(
-            &stake::instruction::delegate_stake(
-                &self.stake_account.key(),
-                &staker,
-                self.validator_vote.key,
-            ),
+            &stake::instruction::delegate_stake(stake_account.key, &staker, validator_vote.key),
             &[
-                self.stake_program.to_account_info(),
-                self.stake_account.to_account_info(),
-                self.stake_deposit_authority.to_account_info(),
-                self.validator_vote.to_account_info(),
+                self.stake_program.clone(),
+                stake_account.clone(),
+                self.stake_deposit_authority.clone(),
+                validator_vote.clone(),
                 self.clock.to_account_info(),
-                self.stake_history.to_account_info(),
-                self.stake_config.to_account_info(),
+                self.stake_history.clone(),
+                self.stake_config.clone(),
             ],
             &[&[
-                &self.state.key().to_bytes(),
+                &state.key().to_bytes(),
                 StakeSystem::STAKE_DEPOSIT_SEED,
-                &[self.state.stake_system.stake_deposit_bump_seed],
+                &[state.stake_system.stake_deposit_bump_seed],
             ]],
         )?;
 
-        self.state.stake_system.add(
-            &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
-            &self.stake_account.key(),
-            stake_target,
+        let stake_index = state.stake_system.add(
+            &mut self.stake_list.data.as_ref().borrow_mut(),
+            stake_account.key,
+            amount,
             &self.clock,
             0, // is_emergency_unstaking? no
         )?;
+        state.on_stake_record_added(self.clock.epoch);
 
         // update validator record and store in list
-        validator.active_balance += stake_target;
+        validator.active_balance += amount;
         validator.last_stake_delta_epoch = self.clock.epoch;
         // Any stake-delta activity must activate stake delta mode
-        self.state.stake_system.last_stake_delta_epoch = self.clock.epoch;
-        self.state.validator_system.set(
-            &mut self
-                .validator_list
-                .to_account_info()
-                .data
-                .as_ref()
-                .borrow_mut(),
-            validator_index,
+        state.stake_system.last_stake_delta_epoch = self.clock.epoch;
+        state.validator_system.set(
+            &mut self.validator_list.data.as_ref().borrow_mut(),
+            candidate.validator_index,
             validator,
         )?;
         // update also total_active_balance
-        self.state.validator_system.total_active_balance += stake_target;
-
-        emit!(StakeReserveEvent {
-            state: self.state.key(),
-            epoch: self.clock.epoch,
-            stake_index: self.state.stake_system.stake_count() - 1,
-            stake_account: self.stake_account.key(),
-            validator_index,
-            validator_vote: self.validator_vote.key(),
-            amount: stake_target,
-            total_stake_target,
-            validator_stake_target,
-            reserve_balance,
-            total_active_balance,
-            validator_active_balance,
-            total_stake_delta,
-        });
-        Ok(())
-    }
-
-    pub fn return_unused_stake_account_rent(&self) -> Result<()> {
-        // Return back the rent reserve of unused stake account in case of early return
-        withdraw(
-            CpiContext::new(
-                self.stake_program.to_account_info(),
-                Withdraw {
-                    stake: self.stake_account.to_account_info(),
-                    withdrawer: self.stake_account.to_account_info(),
-                    to: self.rent_payer.to_account_info(),
-                    clock: self.clock.to_account_info(),
-                    stake_history: self.stake_history.to_account_info(),
-                },
-            ),
-            self.stake_account.to_account_info().lamports(),
-            None,
-        )
+        state.validator_system.total_active_balance += amount;
+        Ok(stake_index)
     }
 }
@@ -0,0 +1,273 @@
+use crate::{
+    error::StaderLiquidStakingError,
+    events::crank::StakeReserveEvent,
+    pay_crank_reward,
+    state::{
+        crank_reward_vault::CrankRewardVault,
+        stake_system::{StakeList, StakeSystem},
+        validator_system::ValidatorList,
+    },
+    ReserveStakeCandidate, ReserveStaking, State,
+};
+use anchor_lang::{
+    prelude::*,
+    system_program::{create_account, CreateAccount},
+};
+use anchor_lang::{
+    solana_program::{
+        stake::{self, state::StakeStateV2},
+        sysvar::stake_history,
+    },
+    system_program,
+};
+use anchor_spl::stake::Stake;
+use std::convert::TryFrom;
+
+/// Same accounts as StakeReserve without the validator and the stake account.
+/// remaining_accounts: (validator_vote, directed_stake) for every candidate,
+/// followed by the fresh stake account keypairs (signers), one used per delegation
+#[derive(Accounts)]
+pub struct StakeReserveMulti<'info> {
+    #[account(mut)]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+    #[account(
+        mut,
+        address = state.stake_system.stake_list.account,
+    )]
+    pub stake_list: Account<'info, StakeList>,
+    #[account(
+        mut,
+        seeds = [
+            &state.key().to_bytes(),
+            State::RESERVE_SEED
+        ],
+        bump = state.reserve_bump_seed
+    )]
+    pub reserve_pda: SystemAccount<'info>,
+    /// CHECK: PDA
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            StakeSystem::STAKE_DEPOSIT_SEED
+        ],
+        bump = state.stake_system.stake_deposit_bump_seed
+    )]
+    pub stake_deposit_authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        owner = system_program::ID
+    )]
+    pub rent_payer: Signer<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub epoch_schedule: Sysvar<'info, EpochSchedule>,
+    pub rent: Sysvar<'info, Rent>,
+    /// CHECK: have no CPU budget to parse
+    #[account(address = stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+    /// CHECK: CPI
+    #[account(address = stake::config::ID)]
+    pub stake_config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub stake_program: Program<'info, Stake>,
+
+    #[account(mut, has_one = state)]
+    pub crank_reward_vault: Option<Account<'info, CrankRewardVault>>,
+    /// CHECK: any writable account of the caller, receives the crank reward
+    #[account(mut)]
+    pub crank_reward_receiver: Option<UncheckedAccount<'info>>,
+}
+
+struct Candidate<'a, 'info> {
+    validator_vote: &'a AccountInfo<'info>,
+    reserve_stake: ReserveStakeCandidate,
+}
+
+impl<'info> StakeReserveMulti<'info> {
+    /// called by the bot
+    /// Like stake_reserve for every candidate, the most under-staked first,
+    /// until the stake delta or the stake accounts run out.
+    /// Candidates that can't receive stake are skipped, not an error.
+    /// Stake accounts are created only when used, the unused ones cost no rent.
+    /// Every delegation earns a crank reward
+    pub fn process(
+        &mut self,
+        remaining_accounts: &[AccountInfo<'info>],
+        validator_indices: Vec<u32>,
+    ) -> Result<()> {
+        self.state.check_not_paused(State::PAUSE_CRANKS)?;
+        self.state.check_not_winding_down()?;
+
+        require!(
+            !validator_indices.is_empty()
+                && remaining_accounts.len() >= 2 * validator_indices.len(),
+            StaderLiquidStakingError::InvalidStakeReserveCandidates
+        );
+        let (candidate_accounts, stake_accounts) =
+            remaining_accounts.split_at(2 * validator_indices.len());
+
+        let last_slot = self.epoch_schedule.get_last_slot_in_epoch(self.clock.epoch);
+        require_gte!(
+            self.clock.slot,
+            last_slot.saturating_sub(self.state.stake_system.slots_for_stake_delta),
+            StaderLiquidStakingError::TooEarlyForStakeDelta
+        );
+
+        let reserve_balance = self.reserve_pda.lamports();
+        let stake_delta = self.state.stake_delta(reserve_balance);
+        if stake_delta <= 0 {
+            msg!("Nothing to stake");
+            return Ok(()); // Not an error. Don't fail other instructions in tx
+        }
+        let total_stake_delta = u64::try_from(stake_delta).expect("Stake delta overflow");
+        let total_stake_target = self
+            .state
+            .validator_system
+            .total_active_balance
+            .saturating_add(total_stake_delta);
+
+        let staking = self.reserve_staking();
+        let mut candidates: Vec<Candidate> = Vec::with_capacity(validator_indices.len());
+        for (validator_index, accounts) in validator_indices
+            .into_iter()
+            .zip(candidate_accounts.chunks(2))
+        {
+            let (validator_vote, directed_stake) = (&accounts[0], &accounts[1]);
+            require!(
+                candidates
+                    .iter()
+                    .all(|candidate| candidate.validator_vote.key != validator_vote.key),
+                StaderLiquidStakingError::InvalidStakeReserveCandidates
+            );
+            if let Some(reserve_stake) = staking.candidate(
+                &self.state,
+                validator_index,
+                validator_vote,
+                directed_stake,
+                total_stake_target,
+            )? {
+                candidates.push(Candidate {
+                    validator_vote,
+                    reserve_stake,
+                });
+            }
+        }
+        // most under-staked first
+        candidates
+            .sort_by_key(|candidate| std::cmp::Reverse(candidate.reserve_stake.missing_stake()));
+
+        let mut stake_accounts = stake_accounts.iter();
+        let mut remaining_stake_delta = total_stake_delta;
+        for Candidate {
+            validator_vote,
+            reserve_stake,
+        } in candidates
+        {
+            if remaining_stake_delta < self.state.stake_system.min_stake {
+                break;
+            }
+            // the extra runs may have been used by the previous candidates
+            if reserve_stake.validator.last_stake_delta_epoch == self.clock.epoch
+                && self.state.stake_system.extra_stake_delta_runs == 0
+            {
+                continue;
+            }
+            // record for event
+            let validator_active_balance = reserve_stake.validator.active_balance;
+            let total_active_balance = self.state.validator_system.total_active_balance;
+
+            let stake_target = self.state.reserve_stake_amount(
+                reserve_stake.missing_stake(),
+                remaining_stake_delta,
+                validator_active_balance,
+            );
+            if stake_target < self.state.stake_system.min_stake {
+                msg!(
+                    "Resulting stake {} for validator {} is lower than min stake allowed {}",
+                    stake_target,
+                    validator_vote.key,
+                    self.state.stake_system.min_stake
+                );
+                continue;
+            }
+
+            let stake_account = if let Some(stake_account) = stake_accounts.next() {
+                stake_account
+            } else {
+                msg!("No stake accounts left");
+                break;
+            };
+            self.create_stake_account(stake_account)?;
+            let stake_index = staking.stake(
+                &mut self.state,
+                &reserve_stake,
+                stake_account,
+                validator_vote,
+                stake_target,
+            )?;
+            remaining_stake_delta -= stake_target;
+            pay_crank_reward(
+                &mut self.state,
+                &self.crank_reward_vault,
+                &self.crank_reward_receiver,
+                self.clock.epoch,
+            )?;
+
+            emit!(StakeReserveEvent {
+                state: self.state.key(),
+                epoch: self.clock.epoch,
+                stake_index,
+                stake_account: stake_account.key(),
+                validator_index: reserve_stake.validator_index,
+                validator_vote: validator_vote.key(),
+                amount: stake_target,
+                total_stake_target,
+                validator_stake_target: reserve_stake.validator_stake_target,
+                reserve_balance,
+                total_active_balance,
+                validator_active_balance,
+                total_stake_delta,
+            });
+        }
+        Ok(())
+    }
+
+    fn reserve_staking(&self) -> ReserveStaking<'info> {
+        ReserveStaking {
+            validator_list: self.validator_list.to_account_info(),
+            stake_list: self.stake_list.to_account_info(),
+            reserve_pda: self.reserve_pda.to_account_info(),
+            stake_deposit_authority: self.stake_deposit_authority.to_account_info(),
+            clock: self.clock.clone(),
+            rent: self.rent.to_account_info(),
+            stake_history: self.stake_history.to_account_info(),
+            stake_config: self.stake_config.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            stake_program: self.stake_program.to_account_info(),
+        }
+    }
+
+    /// Creates the rent exempt stake account paid by the rent payer
+    fn create_stake_account(&self, stake_account: &AccountInfo<'info>) -> Result<()> {
+        let space = StakeStateV2::size_of();
+        create_account(
+            CpiContext::new(
+                self.system_program.to_account_info(),
+                CreateAccount {
+                    from: self.rent_payer.to_account_info(),
+                    to: stake_account.clone(),
+                },
+            ),
+            self.rent.minimum_balance(space),
+            space as u64,
+            &stake::program::ID,
+        )
+        .map_err(|e| e.with_account_name("stake_account"))
+    }
+}
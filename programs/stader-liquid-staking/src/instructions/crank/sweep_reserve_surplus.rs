@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::{
+    events::crank::{ReserveSurplusDonationEvent, ReserveSurplusSweptEvent},
+    events::U64ValueChange,
+    State,
+};
+
+#[derive(Accounts)]
+pub struct SweepReserveSurplus<'info> {
+    #[account(
+        mut,
+        has_one = operational_sol_account
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [
+            &state.key().to_bytes(),
+            State::RESERVE_SEED
+        ],
+        bump = state.reserve_bump_seed
+    )]
+    pub reserve_pda: SystemAccount<'info>,
+    #[account(mut)]
+    pub operational_sol_account: SystemAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SweepReserveSurplus<'info> {
+    /// Handles lamports transferred into the reserve from outside the program
+    /// according to state.reserve_surplus_policy
+    pub fn process(&mut self) -> Result<()> {
+        self.state.check_not_paused(State::PAUSE_CRANKS)?;
+
+        let surplus = self.state.reserve_surplus(self.reserve_pda.lamports());
+        if surplus == 0 {
+            msg!("No reserve surplus");
+            return Ok(()); // Not an error. Don't fail other instructions in tx
+        }
+
+        if self.state.reserve_surplus_policy == State::RESERVE_SURPLUS_TO_OPERATIONAL {
+            transfer(
+                CpiContext::new_with_signer(
+                    self.system_program.to_account_info(),
+                    Transfer {
+                        from: self.reserve_pda.to_account_info(),
+                        to: self.operational_sol_account.to_account_info(),
+                    },
+                    &[&[
+                        &self.state.key().to_bytes(),
+                        State::RESERVE_SEED,
+                        &[self.state.reserve_bump_seed],
+                    ]],
+                ),
+                surplus,
+            )?;
+            emit!(ReserveSurplusSweptEvent {
+                state: self.state.key(),
+                epoch: self.clock.epoch,
+                amount: surplus,
+                operational_sol_account: self.operational_sol_account.key(),
+                operational_sol_balance: self.operational_sol_account.lamports(),
+            });
+        } else {
+            // donation: the lamports become part of the staderSOL price
+            self.state.on_transfer_to_reserve(surplus);
+            let old = self.state.stader_sol_price;
+            self.state.stader_sol_price = self.state.stader_sol_to_sol(State::PRICE_DENOMINATOR)?;
+            emit!(ReserveSurplusDonationEvent {
+                state: self.state.key(),
+                epoch: self.clock.epoch,
+                amount: surplus,
+                stader_sol_price_change: U64ValueChange {
+                    old,
+                    new: self.state.stader_sol_price,
+                },
+                total_virtual_staked_lamports: self.state.total_virtual_staked_lamports(),
+                stader_sol_supply: self.state.stader_sol_supply,
+            });
+        }
+
+        Ok(())
+    }
+}
@@ -2,14 +2,16 @@
 
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::sysvar::stake_history;
-use anchor_spl::stake::{withdraw, Stake, StakeAccount, Withdraw};
-use anchor_spl::token::{mint_to, Mint, MintTo, Token};
+use anchor_spl::stake::{Stake, StakeAccount};
+use anchor_spl::token::{Mint, Token};
 
-use crate::events::crank::UpdateActiveEvent;
+use crate::events::crank::{EpochFullyUpdatedEvent, UpdateActiveEvent};
 use crate::events::U64ValueChange;
+use crate::state::circuit_breaker::CircuitBreaker;
+use crate::state::crank_reward_vault::CrankRewardVault;
 use crate::state::stake_system::StakeList;
 use crate::state::validator_system::ValidatorList;
-use crate::BeginOutput;
+use crate::{pay_crank_reward, BeginOutput, UpdateAccounts};
 use crate::{
     error::StaderLiquidStakingError,
     state::stake_system::StakeSystem,
@@ -81,43 +83,35 @@ pub struct UpdateActive<'info> {
     pub stake_program: Program<'info, Stake>,
 
     pub token_program: Program<'info, Token>,
+
+    #[account(mut, has_one = state)]
+    pub crank_reward_vault: Option<Account<'info, CrankRewardVault>>,
+    /// CHECK: any writable account of the caller, receives the crank reward
+    #[account(mut)]
+    pub crank_reward_receiver: Option<UncheckedAccount<'info>>,
 }
 
 
 impl<'info> UpdateActive<'info> {
 
+    fn update_accounts(&self) -> UpdateAccounts<'info> {
+        UpdateAccounts {
+            reserve_pda: self.reserve_pda.to_account_info(),
+            stake_withdraw_authority: self.stake_withdraw_authority.to_account_info(),
+            stader_sol_mint: self.stader_sol_mint.to_account_info(),
+            stader_sol_mint_authority: self.stader_sol_mint_authority.to_account_info(),
+            treasury_stader_sol_account: self.treasury_stader_sol_account.to_account_info(),
+            clock: self.clock.clone(),
+            stake_history: self.stake_history.to_account_info(),
+            stake_program: self.stake_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        }
+    }
+
     fn begin(&mut self, stake_index: u32) -> Result<BeginOutput> {
         let is_treasury_stader_sol_ready_for_transfer = self
-            .state
-            .get_treasury_stader_sol_balance(&self.treasury_stader_sol_account)
-            .is_some();
-
-        let virtual_reserve_balance =
-            self.state.available_reserve_balance + self.state.rent_exempt_for_token_acc;
-
-        // impossible to happen check outside bug
-        if self.reserve_pda.lamports() < virtual_reserve_balance {
-            msg!(
-                "Warning: Reserve must have {} lamports but got {}",
-                virtual_reserve_balance,
-                self.reserve_pda.lamports()
-            );
-        }
-        // Update reserve balance
-        self.state.available_reserve_balance = self
-            .reserve_pda
-            .lamports()
-            .saturating_sub(self.state.rent_exempt_for_token_acc);
-        // Update staderSOL supply
-        // impossible to happen check outside bug (staderSOL mint auth is a PDA)
-        if self.stader_sol_mint.supply > self.state.stader_sol_supply {
-            msg!(
-                "Warning: staderSOL minted {} lamports outside of stader",
-                self.stader_sol_mint.supply - self.state.stader_sol_supply
-            );
-            self.state.staking_sol_cap = 0;
-        }
-        self.state.stader_sol_supply = self.stader_sol_mint.supply;
+            .update_accounts()
+            .begin(&mut self.state, self.stader_sol_mint.supply);
 
         let stake = self.state.stake_system.get_checked(
             &self.stake_list.to_account_info().data.as_ref().borrow(),
@@ -138,53 +132,16 @@ impl<'info> UpdateActive<'info> {
     pub fn withdraw_to_reserve(&mut self, amount: u64) -> Result<()> {
         if amount > 0 {
             // Move unstaked + rewards for restaking
-            withdraw(
-                CpiContext::new_with_signer(
-                    self.stake_program.to_account_info(),
-                    Withdraw {
-                        stake: self.stake_account.to_account_info(),
-                        withdrawer: self.stake_withdraw_authority.to_account_info(),
-                        to: self.reserve_pda.to_account_info(),
-                        clock: self.clock.to_account_info(),
-                        stake_history: self.stake_history.to_account_info(),
-                    },
-                    &[&[
-                        &self.state.key().to_bytes(),
-                        StakeSystem::STAKE_WITHDRAW_SEED,
-                        &[self.state.stake_system.stake_withdraw_bump_seed],
-                    ]],
-                ),
+            self.update_accounts().withdraw_to_reserve(
+                &self.state,
+                &self.stake_account.to_account_info(),
                 amount,
-                None,
             )?;
             self.state.on_transfer_to_reserve(amount);
         }
         Ok(())
     }
 
-    pub fn mint_to_treasury(&mut self, stader_sol_lamports: u64) -> Result<()> {
-        if stader_sol_lamports > 0 {
-            mint_to(
-                CpiContext::new_with_signer(
-                    self.token_program.to_account_info(),
-                    MintTo {
-                        mint: self.stader_sol_mint.to_account_info(),
-                        to: self.treasury_stader_sol_account.to_account_info(),
-                        authority: self.stader_sol_mint_authority.to_account_info(),
-                    },
-                    &[&[
-                        &self.state.key().to_bytes(),
-                        State::STADER_SOL_MINT_AUTHORITY_SEED,
-                        &[self.state.stader_sol_mint_authority_bump_seed],
-                    ]],
-                ),
-                stader_sol_lamports,
-            )?;
-            self.state.on_stader_sol_mint(stader_sol_lamports);
-        }
-        Ok(())
-    }
-
     #[inline]
     pub fn update_stader_sol_price(&mut self) -> Result<U64ValueChange> {
         // price is computed as:
@@ -201,12 +158,8 @@ impl<'info> UpdateActive<'info> {
     // returns fees in staderSOL
     pub fn mint_protocol_fees(&mut self, lamports_incoming: u64) -> Result<u64> {
         // apply x% protocol fee on staking rewards (do this before updating validators' balance, so it's 1% at old, lower, price)
-        let protocol_rewards_fee = self.state.reward_fee.apply(lamports_incoming);
-        msg!("protocol_rewards_fee {}", protocol_rewards_fee);
-        // compute staderSOL amount for protocol_rewards_fee
-        let fee_as_stader_sol_amount = self.state.calc_stader_sol_from_lamports(protocol_rewards_fee)?;
-        self.mint_to_treasury(fee_as_stader_sol_amount)?;
-        Ok(fee_as_stader_sol_amount)
+        self.update_accounts()
+            .mint_protocol_fees(&mut self.state, lamports_incoming)
     }
 
     /// Compute rewards for a single stake account
@@ -217,7 +170,14 @@ impl<'info> UpdateActive<'info> {
     //
     // fn update_active()
     pub fn process(&mut self, stake_index: u32, validator_index: u32) -> Result<()> {
-        require!(!self.state.paused, StaderLiquidStakingError::ProgramIsPaused);
+        self.state.check_not_paused(State::PAUSE_CRANKS)?;
+        let was_epoch_fully_updated = self.state.is_epoch_fully_updated(self.clock.epoch);
+
+        let stake_index = self.state.stake_system.resolve_index(
+            &self.stake_list.to_account_info().data.as_ref().borrow(),
+            stake_index,
+            self.stake_account.to_account_info().key,
+        )?;
 
         let total_virtual_staked_lamports = self.state.total_virtual_staked_lamports();
         let stader_sol_supply = self.state.stader_sol_supply;
@@ -230,6 +190,11 @@ impl<'info> UpdateActive<'info> {
             error!(StaderLiquidStakingError::RequiredDelegatedStake).with_account_name("stake_account")
         })?;
 
+        let validator_index = self.state.validator_system.resolve_index(
+            &self.validator_list.to_account_info().data.as_ref().borrow(),
+            validator_index,
+            &delegation.voter_pubkey,
+        )?;
         let mut validator = self.state.validator_system.get_checked(
             &self.validator_list.to_account_info().data.as_ref().borrow(),
             validator_index,
@@ -275,6 +240,8 @@ impl<'info> UpdateActive<'info> {
         };
 
         msg!("current staked lamports {}", delegated_lamports);
+        let delegation_rewards =
+            delegated_lamports.saturating_sub(stake.last_update_delegated_lamports);
         let delegation_growth_stader_sol_fees =
             if delegated_lamports >= stake.last_update_delegated_lamports {
                 // re-delegated by solana rewards
@@ -296,6 +263,19 @@ impl<'info> UpdateActive<'info> {
                 //slashed
                 let slashed = stake.last_update_delegated_lamports - delegated_lamports;
                 msg!("slashed {}", slashed);
+                if self.state.is_slashing_anomaly(
+                    stake.last_update_delegated_lamports,
+                    delegated_lamports,
+                ) {
+                    let state_address = self.state.key();
+                    emit!(self.state.trip_circuit_breaker(
+                        state_address,
+                        CircuitBreaker::REASON_SLASHING,
+                        self.clock.epoch,
+                        stake.last_update_delegated_lamports,
+                        delegated_lamports,
+                    ));
+                }
                 //validator balance is updated with slashed
                 validator.active_balance = validator.active_balance.saturating_sub(slashed);
                 self.state.validator_system.total_active_balance =
@@ -307,7 +287,9 @@ impl<'info> UpdateActive<'info> {
                 }
             };
 
-        // mark stake-account as visited
+        // mark stake-account as visited, only the first update of the epoch is rewarded
+        let is_first_update_in_epoch = stake.last_update_epoch != self.clock.epoch;
+        self.state.on_stake_record_updated(&stake, self.clock.epoch);
         stake.last_update_epoch = self.clock.epoch;
         let delegation_change = {
             let old = stake.last_update_delegated_lamports;
@@ -329,6 +311,20 @@ impl<'info> UpdateActive<'info> {
             validator_index,
             validator,
         )?;
+        self.state.validator_system.update_metadata(
+            &mut self
+                .validator_list
+                .to_account_info()
+                .data
+                .as_ref()
+                .borrow_mut(),
+            validator_index,
+            |metadata| {
+                metadata.cumulative_rewards = metadata
+                    .cumulative_rewards
+                    .saturating_add(extra_lamports + delegation_rewards)
+            },
+        )?;
 
         // set new staderSOL price
         let stader_sol_price_change = self.update_stader_sol_price()?;
@@ -339,9 +335,10 @@ impl<'info> UpdateActive<'info> {
             stake,
         )?;
 
-        assert_eq!(
-            self.state.available_reserve_balance + self.state.rent_exempt_for_token_acc,
-            self.reserve_pda.lamports()
+        // the reserve may hold a surplus waiting for sweep_reserve_surplus
+        assert!(
+            self.state.available_reserve_balance + self.state.rent_exempt_for_token_acc
+                <= self.reserve_pda.lamports()
         );
         emit!(UpdateActiveEvent {
             state: self.state.key(),
@@ -361,6 +358,21 @@ impl<'info> UpdateActive<'info> {
             total_virtual_staked_lamports,
             stader_sol_supply,
         });
+        if is_first_update_in_epoch {
+            pay_crank_reward(
+                &mut self.state,
+                &self.crank_reward_vault,
+                &self.crank_reward_receiver,
+                self.clock.epoch,
+            )?;
+        }
+        if !was_epoch_fully_updated && self.state.is_epoch_fully_updated(self.clock.epoch) {
+            emit!(EpochFullyUpdatedEvent {
+                state: self.state.key(),
+                epoch: self.clock.epoch,
+                updated_stake_count: self.state.updated_stake_count,
+            });
+        }
         Ok(())
     }
 }
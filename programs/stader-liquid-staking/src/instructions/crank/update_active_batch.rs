@@ -0,0 +1,333 @@
+//get staking rewards of many stake accounts & update staderSOL price once
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::stake_history;
+use anchor_spl::stake::{Stake, StakeAccount};
+use anchor_spl::token::{Mint, Token};
+
+use crate::events::crank::{
+    EpochFullyUpdatedEvent, UpdateActiveBatchEvent, UpdateActiveStakeDetails,
+};
+use crate::events::U64ValueChange;
+use crate::state::circuit_breaker::CircuitBreaker;
+use crate::state::crank_reward_vault::CrankRewardVault;
+use crate::state::stake_system::StakeList;
+use crate::state::validator_system::ValidatorList;
+use crate::{
+    error::StaderLiquidStakingError, pay_crank_reward, state::stake_system::StakeSystem, State,
+    UpdateAccounts,
+};
+
+#[derive(Clone, Copy, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateActiveBatchEntry {
+    pub stake_index: u32, // List::FIND_BY_KEY is not accepted, see List::find
+    pub validator_index: u32,
+}
+
+/// Same accounts as UpdateActive without the stake account.
+/// remaining_accounts: the (writable) stake account of every entry, in order
+#[derive(Accounts)]
+pub struct UpdateActiveBatch<'info> {
+    #[account(
+        mut,
+        has_one = treasury_stader_sol_account,
+        has_one = stader_sol_mint
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        address = state.stake_system.stake_list.account,
+    )]
+    pub stake_list: Account<'info, StakeList>,
+
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+
+    /// CHECK: PDA
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            StakeSystem::STAKE_WITHDRAW_SEED
+        ],
+        bump = state.stake_system.stake_withdraw_bump_seed
+    )]
+    pub stake_withdraw_authority: UncheckedAccount<'info>, // for getting non delegated SOLs
+    #[account(
+        mut,
+        seeds = [
+            &state.key().to_bytes(),
+            State::RESERVE_SEED
+        ],
+        bump = state.reserve_bump_seed
+    )]
+    pub reserve_pda: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub stader_sol_mint: Box<Account<'info, Mint>>,
+    /// CHECK: PDA
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            State::STADER_SOL_MINT_AUTHORITY_SEED
+        ],
+        bump = state.stader_sol_mint_authority_bump_seed
+    )]
+    pub stader_sol_mint_authority: UncheckedAccount<'info>,
+    /// CHECK: in code
+    #[account(mut)]
+    pub treasury_stader_sol_account: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    /// CHECK: have no CPU budget to parse
+    #[account(address = stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+
+    pub stake_program: Program<'info, Stake>,
+
+    pub token_program: Program<'info, Token>,
+
+    #[account(mut, has_one = state)]
+    pub crank_reward_vault: Option<Account<'info, CrankRewardVault>>,
+    /// CHECK: any writable account of the caller, receives the crank reward
+    #[account(mut)]
+    pub crank_reward_receiver: Option<UncheckedAccount<'info>>,
+}
+
+impl<'info> UpdateActiveBatch<'info> {
+    fn update_accounts(&self) -> UpdateAccounts<'info> {
+        UpdateAccounts {
+            reserve_pda: self.reserve_pda.to_account_info(),
+            stake_withdraw_authority: self.stake_withdraw_authority.to_account_info(),
+            stader_sol_mint: self.stader_sol_mint.to_account_info(),
+            stader_sol_mint_authority: self.stader_sol_mint_authority.to_account_info(),
+            treasury_stader_sol_account: self.treasury_stader_sol_account.to_account_info(),
+            clock: self.clock.clone(),
+            stake_history: self.stake_history.to_account_info(),
+            stake_program: self.stake_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        }
+    }
+
+    /// update_active for every entry.
+    /// The protocol fee is minted once for the rewards of the whole batch, in the same order as UpdateActive:
+    /// after the extra lamports are in the reserve and before the delegation rewards are in the
+    /// active balance. Then the staderSOL price is updated once
+    pub fn process(
+        &mut self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        entries: Vec<UpdateActiveBatchEntry>,
+    ) -> Result<()> {
+        self.state.check_not_paused(State::PAUSE_CRANKS)?;
+        let was_epoch_fully_updated = self.state.is_epoch_fully_updated(self.clock.epoch);
+        require!(
+            !entries.is_empty() && remaining_accounts.len() == entries.len(),
+            StaderLiquidStakingError::InvalidUpdateBatch
+        );
+
+        let total_virtual_staked_lamports = self.state.total_virtual_staked_lamports();
+        let stader_sol_supply = self.state.stader_sol_supply;
+        let update_accounts = self.update_accounts();
+        let is_treasury_stader_sol_ready_for_transfer =
+            update_accounts.begin(&mut self.state, self.stader_sol_mint.supply);
+        // record for event
+        let total_active_balance = self.state.validator_system.total_active_balance;
+
+        let mut stakes = Vec::with_capacity(entries.len());
+        let mut total_extra_lamports: u64 = 0;
+        let mut total_delegation_rewards: u64 = 0;
+        let mut total_slashed: u64 = 0;
+        let mut rewarded_updates: u32 = 0;
+        for (entry, stake_account_info) in entries.into_iter().zip(remaining_accounts) {
+            require!(stake_account_info.is_writable, ErrorCode::ConstraintMut);
+            let stake_account = Account::<StakeAccount>::try_from(stake_account_info)
+                .map_err(|e| e.with_account_name("stake_account"))?;
+
+            // no List::FIND_BY_KEY, scanning the stake list for every entry costs too much
+            let stake_index = entry.stake_index;
+            let mut stake = self.state.stake_system.get_checked(
+                &self.stake_list.to_account_info().data.as_ref().borrow(),
+                stake_index,
+                stake_account_info.key,
+            )?;
+
+            let delegation = stake_account.delegation().ok_or_else(|| {
+                error!(StaderLiquidStakingError::RequiredDelegatedStake)
+                    .with_account_name("stake_account")
+            })?;
+            // require stake is active (deactivation_epoch == u64::MAX)
+            require_eq!(
+                delegation.deactivation_epoch,
+                std::u64::MAX,
+                StaderLiquidStakingError::RequiredActiveStake
+            );
+            let validator_index = self.state.validator_system.resolve_index(
+                &self.validator_list.to_account_info().data.as_ref().borrow(),
+                entry.validator_index,
+                &delegation.voter_pubkey,
+            )?;
+            let mut validator = self.state.validator_system.get_checked(
+                &self.validator_list.to_account_info().data.as_ref().borrow(),
+                validator_index,
+                &delegation.voter_pubkey,
+            )?;
+            // record for event
+            let validator_active_balance = validator.active_balance;
+
+            let delegated_lamports = delegation.stake;
+            let stake_balance_without_rent =
+                stake_account_info.lamports() - stake_account.meta().unwrap().rent_exempt_reserve;
+            // normally extra-lamports in the native stake means MEV rewards
+            let extra_lamports = stake_balance_without_rent.saturating_sub(delegated_lamports);
+            if extra_lamports > 0 {
+                update_accounts.withdraw_to_reserve(
+                    &self.state,
+                    stake_account_info,
+                    extra_lamports,
+                )?;
+                total_extra_lamports += extra_lamports;
+            }
+
+            let delegation_rewards = if delegated_lamports >= stake.last_update_delegated_lamports {
+                // re-delegated by solana rewards
+                let rewards = delegated_lamports - stake.last_update_delegated_lamports;
+                validator.active_balance += rewards;
+                total_delegation_rewards += rewards;
+                rewards
+            } else {
+                //slashed
+                let slashed = stake.last_update_delegated_lamports - delegated_lamports;
+                msg!("Stake {} slashed {}", stake.stake_account, slashed);
+                if self
+                    .state
+                    .is_slashing_anomaly(stake.last_update_delegated_lamports, delegated_lamports)
+                {
+                    let state_address = self.state.key();
+                    emit!(self.state.trip_circuit_breaker(
+                        state_address,
+                        CircuitBreaker::REASON_SLASHING,
+                        self.clock.epoch,
+                        stake.last_update_delegated_lamports,
+                        delegated_lamports,
+                    ));
+                }
+                validator.active_balance = validator.active_balance.saturating_sub(slashed);
+                total_slashed += slashed;
+                0
+            };
+
+            // mark stake-account as visited, only the first update of the epoch is rewarded
+            if stake.last_update_epoch != self.clock.epoch {
+                rewarded_updates += 1;
+            }
+            self.state.on_stake_record_updated(&stake, self.clock.epoch);
+            stake.last_update_epoch = self.clock.epoch;
+            let delegation_change = {
+                let old = stake.last_update_delegated_lamports;
+                stake.last_update_delegated_lamports = delegated_lamports;
+                U64ValueChange {
+                    old,
+                    new: delegated_lamports,
+                }
+            };
+
+            self.state.validator_system.set(
+                &mut self
+                    .validator_list
+                    .to_account_info()
+                    .data
+                    .as_ref()
+                    .borrow_mut(),
+                validator_index,
+                validator,
+            )?;
+            self.state.validator_system.update_metadata(
+                &mut self
+                    .validator_list
+                    .to_account_info()
+                    .data
+                    .as_ref()
+                    .borrow_mut(),
+                validator_index,
+                |metadata| {
+                    metadata.cumulative_rewards = metadata
+                        .cumulative_rewards
+                        .saturating_add(extra_lamports + delegation_rewards)
+                },
+            )?;
+            self.state.stake_system.set(
+                &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
+                stake_index,
+                stake,
+            )?;
+
+            stakes.push(UpdateActiveStakeDetails {
+                stake_index,
+                stake_account: stake.stake_account,
+                validator_index,
+                validator_vote: validator.validator_account,
+                delegation_change,
+                extra_lamports,
+                validator_active_balance,
+            });
+        }
+
+        // like UpdateActive::withdraw_to_reserve, the extra lamports count before the fee is minted
+        self.state.on_transfer_to_reserve(total_extra_lamports);
+        // the active balance still gives the price before the delegation rewards
+        let total_rewards = total_extra_lamports + total_delegation_rewards;
+        let stader_sol_fees = if is_treasury_stader_sol_ready_for_transfer {
+            Some(update_accounts.mint_protocol_fees(&mut self.state, total_rewards)?)
+        } else {
+            None
+        };
+        self.state.validator_system.total_active_balance =
+            (total_active_balance + total_delegation_rewards).saturating_sub(total_slashed);
+
+        let stader_sol_price_change = {
+            let old = self.state.stader_sol_price;
+            self.state.stader_sol_price = self.state.stader_sol_to_sol(State::PRICE_DENOMINATOR)?;
+            U64ValueChange {
+                old,
+                new: self.state.stader_sol_price,
+            }
+        };
+
+        // the reserve may hold a surplus waiting for sweep_reserve_surplus
+        assert!(
+            self.state.available_reserve_balance + self.state.rent_exempt_for_token_acc
+                <= self.reserve_pda.lamports()
+        );
+        emit!(UpdateActiveBatchEvent {
+            state: self.state.key(),
+            epoch: self.clock.epoch,
+            stakes,
+            total_rewards,
+            stader_sol_fees,
+            total_active_balance,
+            stader_sol_price_change,
+            reward_fee_used: self.state.reward_fee,
+            total_virtual_staked_lamports,
+            stader_sol_supply,
+        });
+        // one crank reward per stake account updated for the first time in the epoch, like update_active
+        for _ in 0..rewarded_updates {
+            pay_crank_reward(
+                &mut self.state,
+                &self.crank_reward_vault,
+                &self.crank_reward_receiver,
+                self.clock.epoch,
+            )?;
+        }
+        if !was_epoch_fully_updated && self.state.is_epoch_fully_updated(self.clock.epoch) {
+            emit!(EpochFullyUpdatedEvent {
+                state: self.state.key(),
+                epoch: self.clock.epoch,
+                updated_stake_count: self.state.updated_stake_count,
+            });
+        }
+        Ok(())
+    }
+}
@@ -3,13 +3,15 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::sysvar::stake_history;
 use anchor_lang::system_program::{transfer, Transfer};
-use anchor_spl::stake::{withdraw, Stake, StakeAccount, Withdraw};
-use anchor_spl::token::{mint_to, Mint, MintTo, Token};
+use anchor_spl::stake::{Stake, StakeAccount};
+use anchor_spl::token::{Mint, Token};
 
-use crate::events::crank::UpdateDeactivatedEvent;
+use crate::events::crank::{EpochFullyUpdatedEvent, UpdateDeactivatedEvent};
 use crate::events::U64ValueChange;
+use crate::state::circuit_breaker::CircuitBreaker;
+use crate::state::crank_reward_vault::CrankRewardVault;
 use crate::state::stake_system::StakeList;
-use crate::BeginOutput;
+use crate::{pay_crank_reward, BeginOutput, UpdateAccounts};
 use crate::{
     error::StaderLiquidStakingError,
     state::stake_system::StakeSystem,
@@ -82,42 +84,34 @@ pub struct UpdateDeactivated<'info> {
     pub token_program: Program<'info, Token>,
 
     pub system_program: Program<'info, System>,
+
+    #[account(mut, has_one = state)]
+    pub crank_reward_vault: Option<Account<'info, CrankRewardVault>>,
+    /// CHECK: any writable account of the caller, receives the crank reward
+    #[account(mut)]
+    pub crank_reward_receiver: Option<UncheckedAccount<'info>>,
 }
 
 
 impl<'info> UpdateDeactivated<'info> {
+    fn update_accounts(&self) -> UpdateAccounts<'info> {
+        UpdateAccounts {
+            reserve_pda: self.reserve_pda.to_account_info(),
+            stake_withdraw_authority: self.stake_withdraw_authority.to_account_info(),
+            stader_sol_mint: self.stader_sol_mint.to_account_info(),
+            stader_sol_mint_authority: self.stader_sol_mint_authority.to_account_info(),
+            treasury_stader_sol_account: self.treasury_stader_sol_account.to_account_info(),
+            clock: self.clock.clone(),
+            stake_history: self.stake_history.to_account_info(),
+            stake_program: self.stake_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        }
+    }
+
     fn begin(&mut self, stake_index: u32) -> Result<BeginOutput> {
         let is_treasury_stader_sol_ready_for_transfer = self
-            .state
-            .get_treasury_stader_sol_balance(&self.treasury_stader_sol_account)
-            .is_some();
-
-        let virtual_reserve_balance =
-            self.state.available_reserve_balance + self.state.rent_exempt_for_token_acc;
-
-        // impossible to happen check outside bug
-        if self.reserve_pda.lamports() < virtual_reserve_balance {
-            msg!(
-                "Warning: Reserve must have {} lamports but got {}",
-                virtual_reserve_balance,
-                self.reserve_pda.lamports()
-            );
-        }
-        // Update reserve balance
-        self.state.available_reserve_balance = self
-            .reserve_pda
-            .lamports()
-            .saturating_sub(self.state.rent_exempt_for_token_acc);
-        // Update staderSOL supply
-        // impossible to happen check outside bug (staderSOL mint auth is a PDA)
-        if self.stader_sol_mint.supply > self.state.stader_sol_supply {
-            msg!(
-                "Warning: staderSOL minted {} lamports outside of stader",
-                self.stader_sol_mint.supply - self.state.stader_sol_supply
-            );
-            self.state.staking_sol_cap = 0;
-        }
-        self.state.stader_sol_supply = self.stader_sol_mint.supply;
+            .update_accounts()
+            .begin(&mut self.state, self.stader_sol_mint.supply);
 
         let stake = self.state.stake_system.get_checked(
             &self.stake_list.to_account_info().data.as_ref().borrow(),
@@ -138,53 +132,16 @@ impl<'info> UpdateDeactivated<'info> {
     pub fn withdraw_to_reserve(&mut self, amount: u64) -> Result<()> {
         if amount > 0 {
             // Move unstaked + rewards for restaking
-            withdraw(
-                CpiContext::new_with_signer(
-                    self.stake_program.to_account_info(),
-                    Withdraw {
-                        stake: self.stake_account.to_account_info(),
-                        withdrawer: self.stake_withdraw_authority.to_account_info(),
-                        to: self.reserve_pda.to_account_info(),
-                        clock: self.clock.to_account_info(),
-                        stake_history: self.stake_history.to_account_info(),
-                    },
-                    &[&[
-                        &self.state.key().to_bytes(),
-                        StakeSystem::STAKE_WITHDRAW_SEED,
-                        &[self.state.stake_system.stake_withdraw_bump_seed],
-                    ]],
-                ),
+            self.update_accounts().withdraw_to_reserve(
+                &self.state,
+                &self.stake_account.to_account_info(),
                 amount,
-                None,
             )?;
             self.state.on_transfer_to_reserve(amount);
         }
         Ok(())
     }
 
-    pub fn mint_to_treasury(&mut self, stader_sol_lamports: u64) -> Result<()> {
-        if stader_sol_lamports > 0 {
-            mint_to(
-                CpiContext::new_with_signer(
-                    self.token_program.to_account_info(),
-                    MintTo {
-                        mint: self.stader_sol_mint.to_account_info(),
-                        to: self.treasury_stader_sol_account.to_account_info(),
-                        authority: self.stader_sol_mint_authority.to_account_info(),
-                    },
-                    &[&[
-                        &self.state.key().to_bytes(),
-                        State::STADER_SOL_MINT_AUTHORITY_SEED,
-                        &[self.state.stader_sol_mint_authority_bump_seed],
-                    ]],
-                ),
-                stader_sol_lamports,
-            )?;
-            self.state.on_stader_sol_mint(stader_sol_lamports);
-        }
-        Ok(())
-    }
-
     #[inline]
     pub fn update_stader_sol_price(&mut self) -> Result<U64ValueChange> {
         // price is computed as:
@@ -201,12 +158,8 @@ impl<'info> UpdateDeactivated<'info> {
     // returns fees in staderSOL
     pub fn mint_protocol_fees(&mut self, lamports_incoming: u64) -> Result<u64> {
         // apply x% protocol fee on staking rewards (do this before updating validators' balance, so it's 1% at old, lower, price)
-        let protocol_rewards_fee = self.state.reward_fee.apply(lamports_incoming);
-        msg!("protocol_rewards_fee {}", protocol_rewards_fee);
-        // compute staderSOL amount for protocol_rewards_fee
-        let fee_as_stader_sol_amount = self.state.calc_stader_sol_from_lamports(protocol_rewards_fee)?;
-        self.mint_to_treasury(fee_as_stader_sol_amount)?;
-        Ok(fee_as_stader_sol_amount)
+        self.update_accounts()
+            .mint_protocol_fees(&mut self.state, lamports_incoming)
     }
 
     /// Compute rewards for a single deactivated stake-account
@@ -215,7 +168,14 @@ impl<'info> UpdateDeactivated<'info> {
     /// Optional Future Expansion: Partial: If the stake-account is a fully-deactivated stake account ready to withdraw,
     /// (cool-down period is complete) delete-withdraw the stake-account, send SOL to reserve-account
     pub fn process(&mut self, stake_index: u32) -> Result<()> {
-        require!(!self.state.paused, StaderLiquidStakingError::ProgramIsPaused);
+        self.state.check_not_paused(State::PAUSE_CRANKS)?;
+        let was_epoch_fully_updated = self.state.is_epoch_fully_updated(self.clock.epoch);
+
+        let stake_index = self.state.stake_system.resolve_index(
+            &self.stake_list.to_account_info().data.as_ref().borrow(),
+            stake_index,
+            self.stake_account.to_account_info().key,
+        )?;
 
         let total_virtual_staked_lamports = self.state.total_virtual_staked_lamports();
         let stader_sol_supply = self.state.stader_sol_supply;
@@ -265,6 +225,19 @@ impl<'info> UpdateDeactivated<'info> {
             // less than observed last time
             let slashed = stake.last_update_delegated_lamports - stake_balance_without_rent;
             msg!("Slashed {}", slashed);
+            if self.state.is_slashing_anomaly(
+                stake.last_update_delegated_lamports,
+                stake_balance_without_rent,
+            ) {
+                let state_address = self.state.key();
+                emit!(self.state.trip_circuit_breaker(
+                    state_address,
+                    CircuitBreaker::REASON_SLASHING,
+                    self.clock.epoch,
+                    stake.last_update_delegated_lamports,
+                    stake_balance_without_rent,
+                ));
+            }
             if is_treasury_stader_sol_ready_for_transfer {
                 Some(0)
             } else {
@@ -309,6 +282,7 @@ impl<'info> UpdateDeactivated<'info> {
         let stader_sol_price_change = self.update_stader_sol_price()?;
 
         //remove deleted stake-account from our list
+        self.state.on_deactivated_stake_record_removed(self.clock.epoch);
         self.state.stake_system.remove(
             &mut self
                 .stake_list
@@ -318,6 +292,12 @@ impl<'info> UpdateDeactivated<'info> {
                 .borrow_mut(),
             stake_index,
         )?;
+        pay_crank_reward(
+            &mut self.state,
+            &self.crank_reward_vault,
+            &self.crank_reward_receiver,
+            self.clock.epoch,
+        )?;
         emit!(UpdateDeactivatedEvent {
             state: self.state.key(),
             epoch: self.clock.epoch,
@@ -333,6 +313,13 @@ impl<'info> UpdateDeactivated<'info> {
             stader_sol_supply,
         });
 
+        if !was_epoch_fully_updated && self.state.is_epoch_fully_updated(self.clock.epoch) {
+            emit!(EpochFullyUpdatedEvent {
+                state: self.state.key(),
+                epoch: self.clock.epoch,
+                updated_stake_count: self.state.updated_stake_count,
+            });
+        }
         Ok(())
     }
 }
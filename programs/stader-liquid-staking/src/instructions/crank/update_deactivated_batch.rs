@@ -0,0 +1,281 @@
+//get staking rewards of many deactivated stake accounts & update staderSOL price once
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::stake_history;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::stake::{Stake, StakeAccount};
+use anchor_spl::token::{Mint, Token};
+
+use crate::events::crank::{
+    EpochFullyUpdatedEvent, UpdateDeactivatedBatchEvent, UpdateDeactivatedStakeDetails,
+};
+use crate::events::U64ValueChange;
+use crate::state::circuit_breaker::CircuitBreaker;
+use crate::state::crank_reward_vault::CrankRewardVault;
+use crate::state::stake_system::StakeList;
+use crate::{
+    error::StaderLiquidStakingError, pay_crank_reward, state::stake_system::StakeSystem, State,
+    UpdateAccounts,
+};
+
+/// Same accounts as UpdateDeactivated without the stake account.
+/// remaining_accounts: the (writable) stake account of every stake index, in order
+#[derive(Accounts)]
+pub struct UpdateDeactivatedBatch<'info> {
+    #[account(
+        mut,
+        has_one = treasury_stader_sol_account,
+        has_one = stader_sol_mint
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        address = state.stake_system.stake_list.account,
+    )]
+    pub stake_list: Account<'info, StakeList>,
+    /// CHECK: PDA
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            StakeSystem::STAKE_WITHDRAW_SEED
+        ],
+        bump = state.stake_system.stake_withdraw_bump_seed
+    )]
+    pub stake_withdraw_authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            &state.key().to_bytes(),
+            State::RESERVE_SEED
+        ],
+        bump = state.reserve_bump_seed
+    )]
+    pub reserve_pda: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub stader_sol_mint: Box<Account<'info, Mint>>,
+    /// CHECK: PDA
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            State::STADER_SOL_MINT_AUTHORITY_SEED
+        ],
+        bump = state.stader_sol_mint_authority_bump_seed
+    )]
+    pub stader_sol_mint_authority: UncheckedAccount<'info>,
+    /// CHECK: in code
+    #[account(mut)]
+    pub treasury_stader_sol_account: UncheckedAccount<'info>,
+
+    /// CHECK: not important
+    #[account(
+        mut,
+        address = state.operational_sol_account
+    )]
+    pub operational_sol_account: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    /// CHECK: have no CPU budget to parse
+    #[account(address = stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+
+    pub stake_program: Program<'info, Stake>,
+
+    pub token_program: Program<'info, Token>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(mut, has_one = state)]
+    pub crank_reward_vault: Option<Account<'info, CrankRewardVault>>,
+    /// CHECK: any writable account of the caller, receives the crank reward
+    #[account(mut)]
+    pub crank_reward_receiver: Option<UncheckedAccount<'info>>,
+}
+
+impl<'info> UpdateDeactivatedBatch<'info> {
+    fn update_accounts(&self) -> UpdateAccounts<'info> {
+        UpdateAccounts {
+            reserve_pda: self.reserve_pda.to_account_info(),
+            stake_withdraw_authority: self.stake_withdraw_authority.to_account_info(),
+            stader_sol_mint: self.stader_sol_mint.to_account_info(),
+            stader_sol_mint_authority: self.stader_sol_mint_authority.to_account_info(),
+            treasury_stader_sol_account: self.treasury_stader_sol_account.to_account_info(),
+            clock: self.clock.clone(),
+            stake_history: self.stake_history.to_account_info(),
+            stake_program: self.stake_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        }
+    }
+
+    /// update_deactivated for every stake index.
+    /// The protocol fee is minted once for the rewards of the whole batch at the price before the batch,
+    /// before the withdrawn lamports are in the reserve like UpdateDeactivated,
+    /// the rents go to operational_sol_account in one transfer and the staderSOL price is updated once
+    pub fn process(
+        &mut self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        stake_indices: Vec<u32>,
+    ) -> Result<()> {
+        self.state.check_not_paused(State::PAUSE_CRANKS)?;
+        let was_epoch_fully_updated = self.state.is_epoch_fully_updated(self.clock.epoch);
+        require!(
+            !stake_indices.is_empty() && remaining_accounts.len() == stake_indices.len(),
+            StaderLiquidStakingError::InvalidUpdateBatch
+        );
+
+        let total_virtual_staked_lamports = self.state.total_virtual_staked_lamports();
+        let stader_sol_supply = self.state.stader_sol_supply;
+        let operational_sol_balance = self.operational_sol_account.lamports();
+        let update_accounts = self.update_accounts();
+        let is_treasury_stader_sol_ready_for_transfer =
+            update_accounts.begin(&mut self.state, self.stader_sol_mint.supply);
+
+        let mut stakes = Vec::with_capacity(stake_indices.len());
+        let mut total_withdrawn: u64 = 0;
+        let mut total_rent: u64 = 0;
+        let mut total_rewards: u64 = 0;
+        let mut delayed_unstake_released: u64 = 0;
+        let mut emergency_released: u64 = 0;
+        for (stake_index, stake_account_info) in stake_indices.into_iter().zip(remaining_accounts) {
+            require!(stake_account_info.is_writable, ErrorCode::ConstraintMut);
+            let stake_account = Account::<StakeAccount>::try_from(stake_account_info)
+                .map_err(|e| e.with_account_name("stake_account"))?;
+
+            // no List::FIND_BY_KEY, scanning the stake list for every entry costs too much
+            let stake = self.state.stake_system.get_checked(
+                &self.stake_list.to_account_info().data.as_ref().borrow(),
+                stake_index,
+                stake_account_info.key,
+            )?;
+
+            let delegation = stake_account.delegation().ok_or_else(|| {
+                error!(StaderLiquidStakingError::RequiredDelegatedStake)
+                    .with_account_name("stake_account")
+            })?;
+            // require deactivated or deactivating (deactivation_epoch != u64::MAX)
+            require_neq!(
+                delegation.deactivation_epoch,
+                std::u64::MAX,
+                StaderLiquidStakingError::RequiredDeactivatingStake
+            );
+
+            // rewards are (lamports - rent) versus last_update_delegated_lamports, see UpdateDeactivated
+            let rent = stake_account.meta().unwrap().rent_exempt_reserve;
+            let balance = stake_account_info.lamports();
+            let stake_balance_without_rent = balance - rent;
+            if stake_balance_without_rent >= stake.last_update_delegated_lamports {
+                let rewards = stake_balance_without_rent - stake.last_update_delegated_lamports;
+                msg!("Stake {} rewards: {}", stake.stake_account, rewards);
+                total_rewards += rewards;
+            } else {
+                // less than observed last time
+                let slashed = stake.last_update_delegated_lamports - stake_balance_without_rent;
+                msg!("Stake {} slashed {}", stake.stake_account, slashed);
+                if self.state.is_slashing_anomaly(
+                    stake.last_update_delegated_lamports,
+                    stake_balance_without_rent,
+                ) {
+                    let state_address = self.state.key();
+                    emit!(self.state.trip_circuit_breaker(
+                        state_address,
+                        CircuitBreaker::REASON_SLASHING,
+                        self.clock.epoch,
+                        stake.last_update_delegated_lamports,
+                        stake_balance_without_rent,
+                    ));
+                }
+            }
+
+            // withdraw all to reserve (the stake account will be marked for deletion by the system)
+            update_accounts.withdraw_to_reserve(&self.state, stake_account_info, balance)?;
+            total_withdrawn += balance;
+            total_rent += rent;
+            if stake.is_emergency_unstaking == 0 {
+                delayed_unstake_released += stake.last_update_delegated_lamports;
+            } else {
+                emergency_released += stake.last_update_delegated_lamports;
+            }
+
+            //remove deleted stake-account from our list, other indices are kept
+            self.state
+                .on_deactivated_stake_record_removed(self.clock.epoch);
+            self.state.stake_system.remove(
+                &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
+                stake_index,
+            )?;
+            stakes.push(UpdateDeactivatedStakeDetails {
+                stake_index,
+                stake_account: stake.stake_account,
+                balance_without_rent_exempt: stake_balance_without_rent,
+                last_update_delegated_lamports: stake.last_update_delegated_lamports,
+            });
+        }
+
+        // the totals still give the price before the batch
+        let stader_sol_fees = if is_treasury_stader_sol_ready_for_transfer {
+            Some(update_accounts.mint_protocol_fees(&mut self.state, total_rewards)?)
+        } else {
+            None
+        };
+        self.state.on_transfer_to_reserve(total_withdrawn);
+        // send the rent-exempt lamports to operational_sol_account for the future recreation of the accounts
+        transfer(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: self.reserve_pda.to_account_info(),
+                    to: self.operational_sol_account.to_account_info(),
+                },
+                &[&[
+                    &self.state.key().to_bytes(),
+                    State::RESERVE_SEED,
+                    &[self.state.reserve_bump_seed],
+                ]],
+            ),
+            total_rent,
+        )?;
+        self.state.on_transfer_from_reserve(total_rent);
+        // the amounts are now in the reserve, no longer cooling-down
+        self.state.stake_system.delayed_unstake_cooling_down -= delayed_unstake_released;
+        self.state.emergency_cooling_down -= emergency_released;
+
+        let stader_sol_price_change = {
+            let old = self.state.stader_sol_price;
+            self.state.stader_sol_price = self.state.stader_sol_to_sol(State::PRICE_DENOMINATOR)?;
+            U64ValueChange {
+                old,
+                new: self.state.stader_sol_price,
+            }
+        };
+
+        // one crank reward per removed stake account, like update_deactivated
+        for _ in 0..stakes.len() {
+            pay_crank_reward(
+                &mut self.state,
+                &self.crank_reward_vault,
+                &self.crank_reward_receiver,
+                self.clock.epoch,
+            )?;
+        }
+        emit!(UpdateDeactivatedBatchEvent {
+            state: self.state.key(),
+            epoch: self.clock.epoch,
+            stakes,
+            total_rewards,
+            stader_sol_fees,
+            stader_sol_price_change,
+            reward_fee_used: self.state.reward_fee,
+            operational_sol_balance,
+            total_virtual_staked_lamports,
+            stader_sol_supply,
+        });
+        if !was_epoch_fully_updated && self.state.is_epoch_fully_updated(self.clock.epoch) {
+            emit!(EpochFullyUpdatedEvent {
+                state: self.state.key(),
+                epoch: self.clock.epoch,
+                updated_stake_count: self.state.updated_stake_count,
+            });
+        }
+        Ok(())
+    }
+}
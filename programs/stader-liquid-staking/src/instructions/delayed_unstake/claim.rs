@@ -6,13 +6,6 @@ use crate::state::delayed_unstake_ticket::TicketAccountData;
 use crate::StaderLiquidStakingError;
 use crate::State;
 
-///How many epochs to wats for ticket. e.g.: Ticket created on epoch 14, ticket is due on epoch 15
-const WAIT_EPOCHS: u64 = 0; //  **  This is for test
-                            // const WAIT_EPOCHS: u64 = 1;
-///Wait 30 extra minutes from epochs start so the bot has time to withdraw SOL from inactive stake-accounts
-const EXTRA_WAIT_SECONDS: i64 = 1; //  **  This is for test
-                                   // const EXTRA_WAIT_SECONDS: i64 = 30 * 60;
-
 #[derive(Accounts)]
 pub struct Claim<'info> {
     #[account(mut)]
@@ -69,17 +62,20 @@ impl<'info> Claim<'info> {
         );
 
         //check if ticket is due
+        let due_epoch = self
+            .state
+            .ticket_due_epoch(self.ticket_account.created_epoch);
         require_gte!(
             self.clock.epoch,
-            self.ticket_account.created_epoch + WAIT_EPOCHS,
+            due_epoch,
             StaderLiquidStakingError::TicketNotDue
         );
 
         // Wait X MORE HOURS FROM THE beginning of the EPOCH to give the bot time to withdraw inactive-stake-accounts
-        if self.ticket_account.created_epoch + WAIT_EPOCHS == self.clock.epoch {
+        if due_epoch == self.clock.epoch {
             require_gte!(
                 self.clock.unix_timestamp - self.clock.epoch_start_timestamp,
-                EXTRA_WAIT_SECONDS,
+                self.state.ticket_extra_wait_seconds as i64,
                 StaderLiquidStakingError::TicketNotReady
             );
         }
@@ -89,7 +85,7 @@ impl<'info> Claim<'info> {
 
     // fn claim()
     pub fn process(&mut self) -> Result<()> {
-        require!(!self.state.paused, StaderLiquidStakingError::ProgramIsPaused);
+        self.state.check_not_paused(State::PAUSE_DELAYED_UNSTAKE)?;
 
         self.check_ticket_account()
             .map_err(|e| e.with_account_name("ticket_account"))?;
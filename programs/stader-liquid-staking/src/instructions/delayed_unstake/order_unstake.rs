@@ -40,7 +40,7 @@ pub struct OrderUnstake<'info> {
 impl<'info> OrderUnstake<'info> {
     // fn order_unstake() // create delayed-unstake Ticket-account
     pub fn process(&mut self, stader_sol_amount: u64) -> Result<()> {
-        require!(!self.state.paused, StaderLiquidStakingError::ProgramIsPaused);
+        self.state.check_not_paused(State::PAUSE_DELAYED_UNSTAKE)?;
 
         check_token_source_account(
             &self.burn_stader_sol_from,
@@ -62,8 +62,12 @@ impl<'info> OrderUnstake<'info> {
             .state
             .delayed_unstake_fee
             .apply(sol_value_of_stader_sol_burned);
+        let stale_price_fee = self
+            .state
+            .stale_price_fee(&self.clock, sol_value_of_stader_sol_burned)?;
         // the fee value will be burned but not delivered, thus increasing staderSOL value slightly for all staderSOL holders
-        let lamports_for_user = sol_value_of_stader_sol_burned - delay_unstake_fee_lamports;
+        let lamports_for_user =
+            sol_value_of_stader_sol_burned - delay_unstake_fee_lamports - stale_price_fee;
 
         require_gte!(
             lamports_for_user,
@@ -91,6 +95,8 @@ impl<'info> OrderUnstake<'info> {
             stader_sol_amount,
         )?;
         self.state.on_stader_sol_burn(stader_sol_amount);
+        self.state
+            .unwind_directed_stake(sol_value_of_stader_sol_burned, total_virtual_staked_lamports)?;
 
         // initialize new_ticket_account
         let created_epoch = self.clock.epoch
@@ -108,6 +114,8 @@ impl<'info> OrderUnstake<'info> {
         emit!(OrderUnstakeEvent {
             state: self.state.key(),
             ticket_epoch: created_epoch,
+            ticket_due_epoch: self.state.ticket_due_epoch(created_epoch),
+            ticket_extra_wait_seconds: self.state.ticket_extra_wait_seconds,
             ticket: self.new_ticket_account.key(),
             beneficiary: ticket_beneficiary,
             user_stader_sol_balance,
@@ -118,6 +126,7 @@ impl<'info> OrderUnstake<'info> {
             fee_bp_cents: self.state.delayed_unstake_fee.bp_cents,
             total_virtual_staked_lamports,
             stader_sol_supply,
+            stale_price_fee,
         });
 
         Ok(())
@@ -63,7 +63,8 @@ pub struct AddLiquidity<'info> {
 impl<'info> AddLiquidity<'info> {
     // fn add_liquidity()
     pub fn process(&mut self, lamports: u64) -> Result<()> {
-        require!(!self.state.paused, StaderLiquidStakingError::ProgramIsPaused);
+        self.state.check_not_paused(State::PAUSE_LIQ_POOL)?;
+        self.state.check_not_winding_down()?;
 
         require_gte!(
             lamports,
@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program::{transfer, Transfer};
 use anchor_spl::token::{
-    transfer as transfer_token, Mint, Token, TokenAccount, Transfer as TransferToken,
+    burn, transfer as transfer_token, Burn, Mint, Token, TokenAccount, Transfer as TransferToken,
 };
 
 use crate::{
@@ -58,7 +58,7 @@ pub struct LiquidUnstake<'info> {
 impl<'info> LiquidUnstake<'info> {
     // fn liquid_unstake()
     pub fn process(&mut self, stader_sol_amount: u64) -> Result<()> {
-        require!(!self.state.paused, StaderLiquidStakingError::ProgramIsPaused);
+        self.state.check_not_paused(State::PAUSE_LIQUID_UNSTAKE)?;
 
         check_token_source_account(
             &self.get_stader_sol_from,
@@ -72,6 +72,12 @@ impl<'info> LiquidUnstake<'info> {
             .state
             .get_treasury_stader_sol_balance(&self.treasury_stader_sol_account);
 
+        // the fee part is burned, credited to staderSOL holders, the rest is swapped
+        let stale_price_fee = self
+            .state
+            .stale_price_fee(&Clock::get()?, stader_sol_amount)?;
+        let stader_sol_amount = stader_sol_amount - stale_price_fee;
+
         let liq_pool_stader_sol_balance = self.liq_pool_stader_sol_leg.amount;
         let liq_pool_sol_balance = self.liq_pool_sol_leg_pda.lamports();
         let liq_pool_available_sol_balance =
@@ -79,6 +85,9 @@ impl<'info> LiquidUnstake<'info> {
 
         // fee is computed based on the liquidity *after* the user takes the sol
         let user_remove_lamports = self.state.stader_sol_to_sol(stader_sol_amount)?;
+        let total_virtual_staked_lamports = self.state.total_virtual_staked_lamports();
+        self.state
+            .unwind_directed_stake(user_remove_lamports, total_virtual_staked_lamports)?;
         let liquid_unstake_fee = if user_remove_lamports >= liq_pool_available_sol_balance {
             // user is removing all liquidity
             self.state.liq_pool.lp_max_fee
@@ -163,6 +172,21 @@ impl<'info> LiquidUnstake<'info> {
             )?;
         }
 
+        if stale_price_fee > 0 {
+            burn(
+                CpiContext::new(
+                    self.token_program.to_account_info(),
+                    Burn {
+                        mint: self.stader_sol_mint.to_account_info(),
+                        from: self.get_stader_sol_from.to_account_info(),
+                        authority: self.get_stader_sol_from_authority.to_account_info(),
+                    },
+                ),
+                stale_price_fee,
+            )?;
+            self.state.on_stader_sol_burn(stale_price_fee);
+        }
+
         emit!(LiquidUnstakeEvent {
             state: self.state.key(),
             stader_sol_owner: self.get_stader_sol_from.owner,
@@ -178,7 +202,8 @@ impl<'info> LiquidUnstake<'info> {
             lp_liquidity_target: self.state.liq_pool.lp_liquidity_target,
             lp_max_fee: self.state.liq_pool.lp_max_fee,
             lp_min_fee: self.state.liq_pool.lp_min_fee,
-            treasury_cut: self.state.liq_pool.treasury_cut
+            treasury_cut: self.state.liq_pool.treasury_cut,
+            stale_price_fee,
         });
 
         Ok(())
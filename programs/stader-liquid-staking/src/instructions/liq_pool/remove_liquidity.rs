@@ -66,7 +66,7 @@ pub struct RemoveLiquidity<'info> {
 
 impl<'info> RemoveLiquidity<'info> {
     pub fn process(&mut self, tokens: u64) -> Result<()> {
-        require!(!self.state.paused, StaderLiquidStakingError::ProgramIsPaused);
+        self.state.check_not_paused(State::PAUSE_LIQ_POOL)?;
 
         check_token_source_account(&self.burn_from, self.burn_from_authority.key, tokens)
             .map_err(|e| e.with_account_name("burn_from"))?;
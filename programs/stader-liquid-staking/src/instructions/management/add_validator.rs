@@ -1,9 +1,12 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::system_program;
+use anchor_lang::solana_program::{system_program, vote};
 
+use crate::error::StaderLiquidStakingError;
 use crate::events::management::AddValidatorEvent;
-use crate::state::validator_system::{ValidatorList, ValidatorRecord};
-use crate::{error::StaderLiquidStakingError, State};
+use crate::state::validator_blocklist::ValidatorBlocklistEntry;
+use crate::state::validator_system::{ValidatorList, ValidatorMetadata, ValidatorRecord};
+use crate::state::vote_account::VoteAccountSummary;
+use crate::State;
 
 #[derive(Accounts)]
 pub struct AddValidator<'info> {
@@ -34,6 +37,16 @@ pub struct AddValidator<'info> {
         bump,
     )]
     pub duplication_flag: UncheckedAccount<'info>,
+    /// CHECK: must not exist, see block_validator
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            ValidatorBlocklistEntry::SEED,
+            &validator_vote.key().to_bytes(),
+        ],
+        bump,
+    )]
+    pub blocklist_entry: UncheckedAccount<'info>,
     #[account(
         mut,
         owner = system_program::ID
@@ -48,23 +61,41 @@ pub struct AddValidator<'info> {
 
 impl<'info> AddValidator<'info> {
     pub fn process(&mut self, score: u32) -> Result<()> {
-        require!(!self.state.paused, StaderLiquidStakingError::ProgramIsPaused);
+        self.state.check_not_paused(State::PAUSE_STAKE_MOVES)?;
+
+        require!(
+            self.blocklist_entry.data_is_empty(),
+            StaderLiquidStakingError::ValidatorIsBlocklisted
+        );
 
         msg!("Add validator {}", self.validator_vote.key);
 
+        let mut metadata = ValidatorMetadata {
+            added_epoch: self.clock.epoch,
+            ..Default::default()
+        };
+        // the vote account is not required to be valid here, the metadata stays unknown then
+        if *self.validator_vote.owner == vote::program::ID {
+            if let Ok(vote) = VoteAccountSummary::parse(&self.validator_vote.data.borrow()) {
+                metadata.node_pubkey = vote.node_pubkey;
+                metadata.observe_commission(vote.commission);
+            }
+        }
+
         let state_address = self.state.key();
-        self.state.validator_system.add(
+        let index = self.state.validator_system.add(
             &mut self.validator_list.to_account_info().data.borrow_mut(),
             self.validator_vote.key(),
             score,
             &state_address,
             self.duplication_flag.key,
+            metadata,
         )?;
 
         emit!(AddValidatorEvent {
             state: self.state.key(),
             validator: self.validator_vote.key(),
-            index: self.state.validator_system.validator_count() - 1,
+            index,
             score
         });
 
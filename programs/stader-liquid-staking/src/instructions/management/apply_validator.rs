@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{system_program, vote};
+
+use crate::{
+    error::StaderLiquidStakingError,
+    events::management::ApplyValidatorEvent,
+    state::{
+        validator_application::ValidatorApplication, validator_blocklist::ValidatorBlocklistEntry,
+        validator_system::ValidatorRecord, vote_account::VoteAccountSummary,
+    },
+    State,
+};
+
+#[derive(Accounts)]
+pub struct ApplyValidator<'info> {
+    pub state: Account<'info, State>,
+
+    /// CHECK: parsed manually, see VoteAccountSummary
+    #[account(owner = vote::program::ID)]
+    pub validator_vote: UncheckedAccount<'info>,
+    /// authorized withdrawer or node identity of validator_vote
+    pub applicant: Signer<'info>,
+
+    #[account(
+        init,
+        payer = rent_payer,
+        space = ValidatorApplication::LEN,
+        seeds = [
+            &state.key().to_bytes(),
+            ValidatorApplication::SEED,
+            &validator_vote.key().to_bytes(),
+        ],
+        bump,
+    )]
+    pub application: Account<'info, ValidatorApplication>,
+    /// CHECK: must not exist, see block_validator
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            ValidatorBlocklistEntry::SEED,
+            &validator_vote.key().to_bytes(),
+        ],
+        bump,
+    )]
+    pub blocklist_entry: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        owner = system_program::ID
+    )]
+    pub rent_payer: Signer<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: must not exist, the validator is already in the list otherwise
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            ValidatorRecord::DUPLICATE_FLAG_SEED,
+            &validator_vote.key().to_bytes(),
+        ],
+        bump,
+    )]
+    pub duplication_flag: UncheckedAccount<'info>,
+}
+
+impl<'info> ApplyValidator<'info> {
+    /// Permissionless: queues the validator for approve_validator_application by the manager
+    pub fn process(&mut self) -> Result<()> {
+        require!(
+            self.blocklist_entry.data_is_empty(),
+            StaderLiquidStakingError::ValidatorIsBlocklisted
+        );
+        require!(
+            // the flag is owned by the program while the validator is in the list,
+            // lamports sent to the address by anyone must not block the application
+            *self.duplication_flag.owner != crate::ID,
+            StaderLiquidStakingError::ValidatorAlreadyAdded
+        );
+
+        let vote = VoteAccountSummary::parse(&self.validator_vote.data.borrow())
+            .map_err(|e| e.with_account_name("validator_vote"))?;
+        require!(
+            *self.applicant.key == vote.authorized_withdrawer
+                || *self.applicant.key == vote.node_pubkey,
+            StaderLiquidStakingError::InvalidValidatorApplicant
+        );
+
+        let last_epoch_credits = vote.credits_in_epoch(self.clock.epoch.saturating_sub(1));
+        self.application.set_inner(ValidatorApplication {
+            state: self.state.key(),
+            validator_vote: self.validator_vote.key(),
+            applicant: self.applicant.key(),
+            rent_payer: self.rent_payer.key(),
+            node_pubkey: vote.node_pubkey,
+            commission: vote.commission,
+            last_epoch_credits,
+            applied_epoch: self.clock.epoch,
+        });
+
+        emit!(ApplyValidatorEvent {
+            state: self.state.key(),
+            validator: self.validator_vote.key(),
+            applicant: self.applicant.key(),
+            node_pubkey: vote.node_pubkey,
+            commission: vote.commission,
+            last_epoch_credits,
+            applied_epoch: self.clock.epoch,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,138 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{system_program, vote};
+
+use crate::{
+    error::StaderLiquidStakingError,
+    events::management::ApproveValidatorApplicationEvent,
+    state::{
+        validator_application::ValidatorApplication,
+        validator_blocklist::ValidatorBlocklistEntry,
+        validator_system::{ValidatorList, ValidatorMetadata, ValidatorRecord},
+        vote_account::VoteAccountSummary,
+    },
+    State,
+};
+
+#[derive(Accounts)]
+pub struct ApproveValidatorApplication<'info> {
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    #[account(
+        address = state.validator_system.manager_authority
+            @ StaderLiquidStakingError::InvalidValidatorManager
+    )]
+    pub manager_authority: Signer<'info>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+
+    #[account(
+        mut,
+        close = application_rent_receiver,
+        has_one = state,
+        seeds = [
+            &state.key().to_bytes(),
+            ValidatorApplication::SEED,
+            &application.validator_vote.to_bytes(),
+        ],
+        bump,
+    )]
+    pub application: Account<'info, ValidatorApplication>,
+    /// CHECK: receives the rent of the application
+    #[account(mut, address = application.rent_payer)]
+    pub application_rent_receiver: UncheckedAccount<'info>,
+
+    /// CHECK: no discriminator used
+    /// by initializing this account we mark the validator as added
+    #[account(
+        init, // will ensure it is system account
+        payer = rent_payer,
+        space = 0,
+        seeds = [
+            &state.key().to_bytes(),
+            ValidatorRecord::DUPLICATE_FLAG_SEED,
+            &application.validator_vote.to_bytes(),
+        ],
+        bump,
+    )]
+    pub duplication_flag: UncheckedAccount<'info>,
+    /// CHECK: must not exist, see block_validator
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            ValidatorBlocklistEntry::SEED,
+            &application.validator_vote.to_bytes(),
+        ],
+        bump,
+    )]
+    pub blocklist_entry: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        owner = system_program::ID
+    )]
+    pub rent_payer: Signer<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: parsed manually, see VoteAccountSummary
+    #[account(
+        owner = vote::program::ID,
+        address = application.validator_vote,
+    )]
+    pub validator_vote: UncheckedAccount<'info>,
+}
+
+impl<'info> ApproveValidatorApplication<'info> {
+    /// Same as add_validator for the applying validator.
+    /// The vote account is read again, it may have changed since the application
+    pub fn process(&mut self, score: u32) -> Result<()> {
+        self.state.check_not_paused(State::PAUSE_STAKE_MOVES)?;
+
+        require!(
+            self.blocklist_entry.data_is_empty(),
+            StaderLiquidStakingError::ValidatorIsBlocklisted
+        );
+
+        let vote = VoteAccountSummary::parse(&self.validator_vote.data.borrow())
+            .map_err(|e| e.with_account_name("validator_vote"))?;
+        require!(
+            !self.state.is_validator_commission_too_high(vote.commission),
+            StaderLiquidStakingError::ValidatorCommissionIsTooHigh
+        );
+
+        let validator_vote = self.application.validator_vote;
+        msg!("Add validator {}", validator_vote);
+
+        let state_address = self.state.key();
+        let index = self.state.validator_system.add(
+            &mut self.validator_list.to_account_info().data.borrow_mut(),
+            validator_vote,
+            score,
+            &state_address,
+            self.duplication_flag.key,
+            ValidatorMetadata {
+                node_pubkey: vote.node_pubkey,
+                added_epoch: self.clock.epoch,
+                last_commission: vote.commission,
+                cumulative_rewards: 0,
+                flags: ValidatorMetadata::FLAG_COMMISSION_OBSERVED
+                    | ValidatorMetadata::FLAG_FROM_APPLICATION,
+            },
+        )?;
+
+        emit!(ApproveValidatorApplicationEvent {
+            state: self.state.key(),
+            validator: validator_vote,
+            index,
+            score,
+            applied_epoch: self.application.applied_epoch,
+        });
+
+        Ok(())
+    }
+}
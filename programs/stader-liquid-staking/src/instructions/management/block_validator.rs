@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_program;
+
+use crate::{
+    error::StaderLiquidStakingError, events::management::BlockValidatorEvent,
+    state::validator_blocklist::ValidatorBlocklistEntry, State,
+};
+
+#[derive(Accounts)]
+pub struct BlockValidator<'info> {
+    pub state: Account<'info, State>,
+    #[account(
+        address = state.validator_system.manager_authority
+            @ StaderLiquidStakingError::InvalidValidatorManager
+    )]
+    pub manager_authority: Signer<'info>,
+
+    /// CHECK: only the key is used, the validator does not need to be in the list
+    pub validator_vote: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = rent_payer,
+        space = ValidatorBlocklistEntry::LEN,
+        seeds = [
+            &state.key().to_bytes(),
+            ValidatorBlocklistEntry::SEED,
+            &validator_vote.key().to_bytes(),
+        ],
+        bump,
+    )]
+    pub blocklist_entry: Account<'info, ValidatorBlocklistEntry>,
+    #[account(
+        mut,
+        owner = system_program::ID
+    )]
+    pub rent_payer: Signer<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> BlockValidator<'info> {
+    /// Does not remove the validator from the list, use remove_validator for that
+    pub fn process(&mut self, reason: u8) -> Result<()> {
+        require_gte!(
+            ValidatorBlocklistEntry::MAX_REASON,
+            reason,
+            StaderLiquidStakingError::InvalidBlocklistReason
+        );
+        self.blocklist_entry.set_inner(ValidatorBlocklistEntry {
+            state: self.state.key(),
+            validator_vote: self.validator_vote.key(),
+            reason,
+            blocked_epoch: self.clock.epoch,
+        });
+
+        emit!(BlockValidatorEvent {
+            state: self.state.key(),
+            validator: self.validator_vote.key(),
+            reason,
+            blocked_epoch: self.clock.epoch,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    events::management::CancelValidatorApplicationEvent,
+    state::validator_application::ValidatorApplication, State,
+};
+
+#[derive(Accounts)]
+pub struct CancelValidatorApplication<'info> {
+    pub state: Account<'info, State>,
+    pub applicant: Signer<'info>,
+
+    #[account(
+        mut,
+        close = application_rent_receiver,
+        has_one = state,
+        has_one = applicant,
+        seeds = [
+            &state.key().to_bytes(),
+            ValidatorApplication::SEED,
+            &application.validator_vote.to_bytes(),
+        ],
+        bump,
+    )]
+    pub application: Account<'info, ValidatorApplication>,
+    /// CHECK: receives the rent of the application
+    #[account(mut, address = application.rent_payer)]
+    pub application_rent_receiver: UncheckedAccount<'info>,
+}
+
+impl<'info> CancelValidatorApplication<'info> {
+    /// The applicant withdraws the application, it can apply again afterwards
+    pub fn process(&mut self) -> Result<()> {
+        emit!(CancelValidatorApplicationEvent {
+            state: self.state.key(),
+            validator: self.application.validator_vote,
+            applicant: self.applicant.key(),
+            applied_epoch: self.application.applied_epoch,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::StaderLiquidStakingError,
+    events::{management::CompactListsEvent, U32ValueChange},
+    state::{stake_system::StakeList, validator_system::ValidatorList},
+    State,
+};
+
+#[derive(Accounts)]
+pub struct CompactLists<'info> {
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    #[account(
+        address = state.validator_system.manager_authority
+            @ StaderLiquidStakingError::InvalidValidatorManager
+    )]
+    pub manager_authority: Signer<'info>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+    #[account(
+        mut,
+        address = state.stake_system.stake_list.account,
+    )]
+    pub stake_list: Account<'info, StakeList>,
+}
+
+impl<'info> CompactLists<'info> {
+    /// Drops the removed validators and stake accounts, changing the indices of the others.
+    /// Only while the instructions taking indices are paused, bots must reload the lists after it
+    pub fn process(&mut self) -> Result<()> {
+        require_eq!(
+            self.state.paused_groups & State::PAUSE_LIST_COMPACTION,
+            State::PAUSE_LIST_COMPACTION,
+            StaderLiquidStakingError::ListCompactionNotSafe
+        );
+
+        let validator_count_change = {
+            let old = self.state.validator_system.validator_count();
+            self.state
+                .validator_system
+                .validator_list
+                .compact(&mut self.validator_list.to_account_info().data.borrow_mut())
+                .map_err(|e| e.with_account_name("validator_list"))?;
+            U32ValueChange {
+                old,
+                new: self.state.validator_system.validator_count(),
+            }
+        };
+        let stake_count_change = {
+            let old = self.state.stake_system.stake_count();
+            self.state
+                .stake_system
+                .stake_list
+                .compact(&mut self.stake_list.to_account_info().data.borrow_mut())
+                .map_err(|e| e.with_account_name("stake_list"))?;
+            U32ValueChange {
+                old,
+                new: self.state.stake_system.stake_count(),
+            }
+        };
+
+        emit!(CompactListsEvent {
+            state: self.state.key(),
+            validator_count_change,
+            stake_count_change,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::StaderLiquidStakingError, events::management::ConfigValidatorScoringEvent,
+    state::validator_system::ValidatorScoringConfig, State,
+};
+
+#[derive(Accounts)]
+pub struct ConfigValidatorScoring<'info> {
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    #[account(
+        address = state.validator_system.manager_authority
+            @ StaderLiquidStakingError::InvalidValidatorManager
+    )]
+    pub manager_authority: Signer<'info>,
+}
+
+impl<'info> ConfigValidatorScoring<'info> {
+    pub fn process(&mut self, config: ValidatorScoringConfig) -> Result<()> {
+        if config.enabled {
+            config.validate()?;
+        }
+        let old = self.state.validator_scoring;
+        self.state.validator_scoring = config;
+        emit!(ConfigValidatorScoringEvent {
+            state: self.state.key(),
+            old,
+            new: config,
+        });
+        Ok(())
+    }
+}
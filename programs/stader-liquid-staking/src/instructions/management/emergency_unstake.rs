@@ -49,14 +49,28 @@ pub struct EmergencyUnstake<'info> {
 
 impl<'info> EmergencyUnstake<'info> {
     pub fn process(&mut self, stake_index: u32, validator_index: u32) -> Result<()> {
-        require!(!self.state.paused, StaderLiquidStakingError::ProgramIsPaused);
+        self.state.check_not_paused(State::PAUSE_STAKE_MOVES)?;
 
+        let stake_index = self.state.stake_system.resolve_index(
+            &self.stake_list.to_account_info().data.as_ref().borrow(),
+            stake_index,
+            self.stake_account.to_account_info().key,
+        )?;
         let mut stake = self.state.stake_system.get_checked(
             &self.stake_list.to_account_info().data.as_ref().borrow(),
             stake_index,
             self.stake_account.to_account_info().key,
         )?;
 
+        let validator_index = self.state.validator_system.resolve_index(
+            &self.validator_list.to_account_info().data.as_ref().borrow(),
+            validator_index,
+            &self
+                .stake_account
+                .delegation()
+                .map(|delegation| delegation.voter_pubkey)
+                .unwrap_or_default(),
+        )?;
         let mut validator = self.state.validator_system.get(
             &self.validator_list.to_account_info().data.as_ref().borrow(),
             validator_index,
@@ -100,6 +114,8 @@ impl<'info> EmergencyUnstake<'info> {
             StaderLiquidStakingError::StakeAccountIsEmergencyUnstaking
         );
         stake.is_emergency_unstaking = 1;
+        self.state
+            .on_stake_record_deactivating(&stake, self.clock.epoch);
 
         // we now consider amount no longer "active" for this specific validator
         validator.active_balance -= unstake_amount;
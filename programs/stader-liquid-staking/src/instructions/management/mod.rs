@@ -1,11 +1,35 @@
 pub mod add_validator;
+pub mod apply_validator;
+pub mod approve_validator_application;
+pub mod block_validator;
+pub mod cancel_validator_application;
+pub mod compact_lists;
+pub mod config_validator_scoring;
 pub mod emergency_unstake;
 pub mod partial_unstake;
+pub mod reject_validator_application;
 pub mod remove_validator;
+pub mod rescore_validator;
 pub mod set_validator_score;
+pub mod set_validator_scores;
+pub mod unblock_validator;
+pub mod unstake_delinquent_validator;
+pub mod zero_score_high_commission;
 
 pub use add_validator::*;
+pub use apply_validator::*;
+pub use approve_validator_application::*;
+pub use block_validator::*;
+pub use cancel_validator_application::*;
+pub use compact_lists::*;
+pub use config_validator_scoring::*;
 pub use emergency_unstake::*;
 pub use partial_unstake::*;
+pub use reject_validator_application::*;
 pub use remove_validator::*;
+pub use rescore_validator::*;
 pub use set_validator_score::*;
+pub use set_validator_scores::*;
+pub use unblock_validator::*;
+pub use unstake_delinquent_validator::*;
+pub use zero_score_high_commission::*;
@@ -2,6 +2,7 @@ use crate::{
     checks::check_stake_amount_and_validator,
     error::StaderLiquidStakingError,
     state::{
+        directed_stake::DirectedStake,
         stake_system::{StakeList, StakeSystem},
         validator_system::ValidatorList,
     },
@@ -77,6 +78,9 @@ pub struct PartialUnstake<'info> {
 
     pub system_program: Program<'info, System>,
     pub stake_program: Program<'info, Stake>,
+
+    /// CHECK: may not exist, see DirectedStake::load_shares
+    pub directed_stake: UncheckedAccount<'info>,
 }
 
 impl<'info> PartialUnstake<'info> {
@@ -86,18 +90,32 @@ impl<'info> PartialUnstake<'info> {
         validator_index: u32,
         desired_unstake_amount: u64,
     ) -> Result<()> {
-        require!(!self.state.paused, StaderLiquidStakingError::ProgramIsPaused);
+        self.state.check_not_paused(State::PAUSE_STAKE_MOVES)?;
 
         assert!(
             desired_unstake_amount >= self.state.stake_system.min_stake,
             "desired_unstake_amount too low"
         );
 
+        let validator_index = self.state.validator_system.resolve_index(
+            &self.validator_list.to_account_info().data.as_ref().borrow(),
+            validator_index,
+            &self
+                .stake_account
+                .delegation()
+                .map(|delegation| delegation.voter_pubkey)
+                .unwrap_or_default(),
+        )?;
         let mut validator = self.state.validator_system.get(
             &self.validator_list.to_account_info().data.as_ref().borrow(),
             validator_index,
         )?;
 
+        let stake_index = self.state.stake_system.resolve_index(
+            &self.stake_list.to_account_info().data.as_ref().borrow(),
+            stake_index,
+            self.stake_account.to_account_info().key,
+        )?;
         let mut stake = self.state.stake_system.get_checked(
             &self.stake_list.to_account_info().data.as_ref().borrow(),
             stake_index,
@@ -126,10 +144,25 @@ impl<'info> PartialUnstake<'info> {
         let total_stake_target =
             u64::try_from(total_stake_target_i128).expect("total_stake_target+stake_delta");
         // compute target for this particular validator (total_stake_target * score/total_score)
+        let validator_directed_lamports = self.state.validator_directed_lamports(
+            &validator,
+            DirectedStake::load_shares(
+                &self.directed_stake,
+                &self.state.key(),
+                &validator.validator_account,
+            )
+            .map_err(|e| e.with_account_name("directed_stake"))?,
+        )?;
         let validator_stake_target = self
             .state
             .validator_system
-            .validator_stake_target(&validator, total_stake_target)?;
+            .validator_stake_target(
+                &validator,
+                total_stake_target,
+                self.state.max_validator_stake_share,
+                validator_directed_lamports,
+                self.state.honored_directed_lamports()?,
+            )?;
         // if validator is already on-target (or the split will be lower than min_stake), exit now
         if validator.active_balance <= validator_stake_target + self.state.stake_system.min_stake {
             msg!(
@@ -173,6 +206,8 @@ impl<'info> PartialUnstake<'info> {
                     &[self.state.stake_system.stake_deposit_bump_seed],
                 ]],
             ))?;
+            self.state
+                .on_stake_record_deactivating(&stake, self.clock.epoch);
 
             // mark as emergency_unstaking, so the SOL will be re-staked ASAP
             stake.is_emergency_unstaking = 1;
@@ -198,6 +233,8 @@ impl<'info> PartialUnstake<'info> {
                 &self.clock,
                 1, // is_emergency_unstaking
             )?;
+            self.state
+                .on_deactivating_stake_record_added(self.clock.epoch);
 
             // split & deactivate stake account
             let split_instruction = stake::instruction::split(
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::StaderLiquidStakingError, events::management::RejectValidatorApplicationEvent,
+    state::validator_application::ValidatorApplication, State,
+};
+
+#[derive(Accounts)]
+pub struct RejectValidatorApplication<'info> {
+    pub state: Account<'info, State>,
+    #[account(
+        address = state.validator_system.manager_authority
+            @ StaderLiquidStakingError::InvalidValidatorManager
+    )]
+    pub manager_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        close = application_rent_receiver,
+        has_one = state,
+        seeds = [
+            &state.key().to_bytes(),
+            ValidatorApplication::SEED,
+            &application.validator_vote.to_bytes(),
+        ],
+        bump,
+    )]
+    pub application: Account<'info, ValidatorApplication>,
+    /// CHECK: receives the rent of the application
+    #[account(mut, address = application.rent_payer)]
+    pub application_rent_receiver: UncheckedAccount<'info>,
+}
+
+impl<'info> RejectValidatorApplication<'info> {
+    /// The validator can apply again afterwards, use block_validator to prevent it
+    pub fn process(&mut self) -> Result<()> {
+        emit!(RejectValidatorApplicationEvent {
+            state: self.state.key(),
+            validator: self.application.validator_vote,
+            applied_epoch: self.application.applied_epoch,
+        });
+
+        Ok(())
+    }
+}
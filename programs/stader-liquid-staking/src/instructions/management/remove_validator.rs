@@ -3,7 +3,10 @@ use anchor_lang::{prelude::*, system_program};
 use crate::{
     error::StaderLiquidStakingError,
     events::management::RemoveValidatorEvent,
-    state::validator_system::{ValidatorList, ValidatorRecord},
+    state::{
+        directed_stake::DirectedStake,
+        validator_system::{ValidatorList, ValidatorRecord},
+    },
     State, ID,
 };
 
@@ -41,12 +44,21 @@ pub struct RemoveValidator<'info> {
     /// CHECK: not important
     #[account(mut)]
     pub operational_sol_account: UncheckedAccount<'info>,
+    /// CHECK: may not exist, releases the stake directed to the removed validator,
+    /// see DirectedStake::release_shares
+    #[account(mut)]
+    pub directed_stake: UncheckedAccount<'info>,
 }
 
 impl<'info> RemoveValidator<'info> {
     pub fn process(&mut self, index: u32, validator_vote: Pubkey) -> Result<()> {
-        require!(!self.state.paused, StaderLiquidStakingError::ProgramIsPaused);
+        self.state.check_not_paused(State::PAUSE_STAKE_MOVES)?;
 
+        let index = self.state.validator_system.resolve_index(
+            &self.validator_list.to_account_info().data.borrow(),
+            index,
+            &validator_vote,
+        )?;
         let validator = self.state.validator_system.get_checked(
             &self.validator_list.to_account_info().data.borrow(),
             index,
@@ -70,6 +82,15 @@ impl<'info> RemoveValidator<'info> {
             validator,
         )?;
 
+        let released_shares = DirectedStake::release_shares(
+            &self.directed_stake,
+            &self.state.key(),
+            &validator_vote,
+        )
+        .map_err(|e| e.with_account_name("directed_stake"))?;
+        self.state
+            .on_directed_release(released_shares, validator.score)?;
+
         // record for event, then remove all flag-account lamports to remove flag
         let operational_sol_balance = self.operational_sol_account.lamports();
         let rent_return = self.duplication_flag.lamports();
@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::vote;
+
+use crate::{
+    error::StaderLiquidStakingError,
+    events::{management::RescoreValidatorEvent, U32ValueChange},
+    state::{
+        directed_stake::DirectedStake,
+        validator_system::{ValidatorList, ValidatorMetadata, ValidatorSystem},
+        vote_account::VoteAccountSummary,
+    },
+    State,
+};
+
+#[derive(Accounts)]
+pub struct RescoreValidator<'info> {
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+    /// CHECK: parsed manually, see VoteAccountSummary
+    #[account(owner = vote::program::ID)]
+    pub validator_vote: UncheckedAccount<'info>,
+    pub clock: Sysvar<'info, Clock>,
+    /// CHECK: may not exist, see DirectedStake::load_shares
+    pub directed_stake: UncheckedAccount<'info>,
+}
+
+impl<'info> RescoreValidator<'info> {
+    /// Permissionless: sets the validator score from its vote account
+    /// using the formula in state.validator_scoring.
+    /// Scores set by the manager with set_validator_score are kept until release_validator_score
+    /// (lists without metadata can not keep them, see migrate_validator_list)
+    pub fn process(&mut self, index: u32) -> Result<()> {
+        self.state.check_not_paused(State::PAUSE_STAKE_MOVES)?;
+        require!(
+            self.state.validator_scoring.enabled,
+            StaderLiquidStakingError::ValidatorScoringDisabled
+        );
+
+        let index = self.state.validator_system.resolve_index(
+            &self.validator_list.to_account_info().data.borrow(),
+            index,
+            self.validator_vote.key,
+        )?;
+        let mut validator = self.state.validator_system.get_checked(
+            &self.validator_list.to_account_info().data.borrow(),
+            index,
+            self.validator_vote.key,
+        )?;
+        if let Some(metadata) = self.state.validator_system.get_metadata(
+            &self.validator_list.to_account_info().data.borrow(),
+            index,
+        )? {
+            require!(
+                metadata.flags & ValidatorMetadata::FLAG_MANUAL_SCORE == 0,
+                StaderLiquidStakingError::ValidatorScoreIsManual
+            );
+        }
+        let vote = VoteAccountSummary::parse(&self.validator_vote.data.borrow())
+            .map_err(|e| e.with_account_name("validator_vote"))?;
+        let score =
+            ValidatorSystem::compute_score(&self.state.validator_scoring, &vote, &self.clock);
+
+        let directed_shares = DirectedStake::load_shares(
+            &self.directed_stake,
+            &self.state.key(),
+            self.validator_vote.key,
+        )
+        .map_err(|e| e.with_account_name("directed_stake"))?;
+        self.state
+            .on_validator_score_change(validator.score, score, directed_shares);
+        self.state
+            .validator_system
+            .replace_score(validator.score, score)?;
+        let score_change = {
+            let old = validator.score;
+            validator.score = score;
+            U32ValueChange { old, new: score }
+        };
+        self.state.validator_system.set(
+            &mut self.validator_list.to_account_info().data.borrow_mut(),
+            index,
+            validator,
+        )?;
+        self.state.validator_system.update_metadata(
+            &mut self.validator_list.to_account_info().data.borrow_mut(),
+            index,
+            |metadata| metadata.observe_commission(vote.commission),
+        )?;
+
+        emit!(RescoreValidatorEvent {
+            state: self.state.key(),
+            epoch: self.clock.epoch,
+            validator: validator.validator_account,
+            index,
+            score_change,
+            total_validator_score: self.state.validator_system.total_validator_score,
+            commission: vote.commission,
+            last_vote_slot: vote.last_vote_slot,
+            last_epoch_credits: vote.credits_in_epoch(self.clock.epoch.saturating_sub(1)),
+        });
+
+        Ok(())
+    }
+}
@@ -2,8 +2,14 @@ use anchor_lang::prelude::*;
 
 use crate::{
     error::StaderLiquidStakingError,
-    events::{management::SetValidatorScoreEvent, U32ValueChange},
-    state::validator_system::ValidatorList,
+    events::{
+        management::{ReleaseValidatorScoreEvent, SetValidatorScoreEvent},
+        U32ValueChange,
+    },
+    state::{
+        directed_stake::DirectedStake,
+        validator_system::{ValidatorList, ValidatorMetadata},
+    },
     State,
 };
 
@@ -21,30 +27,52 @@ pub struct SetValidatorScore<'info> {
         address = state.validator_system.validator_list.account,
     )]
     pub validator_list: Account<'info, ValidatorList>,
+    /// CHECK: may not exist, see DirectedStake::load_shares
+    pub directed_stake: UncheckedAccount<'info>,
 }
 
 impl<'info> SetValidatorScore<'info> {
+    /// The score is kept by rescore_validator until release_validator_score
     pub fn process(&mut self, index: u32, validator_vote: Pubkey, score: u32) -> Result<()> {
-        require!(!self.state.paused, StaderLiquidStakingError::ProgramIsPaused);
+        self.state.check_not_paused(State::PAUSE_STAKE_MOVES)?;
 
+        let index = self.state.validator_system.resolve_index(
+            &self.validator_list.to_account_info().data.borrow(),
+            index,
+            &validator_vote,
+        )?;
         let mut validator = self.state.validator_system.get_checked(
             &self.validator_list.to_account_info().data.borrow(),
             index,
             &validator_vote,
         )?;
 
-        self.state.validator_system.total_validator_score -= validator.score;
+        let directed_shares = DirectedStake::load_shares(
+            &self.directed_stake,
+            &self.state.key(),
+            &validator_vote,
+        )
+        .map_err(|e| e.with_account_name("directed_stake"))?;
+        self.state
+            .on_validator_score_change(validator.score, score, directed_shares);
+        self.state
+            .validator_system
+            .replace_score(validator.score, score)?;
         let score_change = {
             let old = validator.score;
             validator.score = score;
             U32ValueChange { old, new: score }
         };
-        self.state.validator_system.total_validator_score += score;
         self.state.validator_system.set(
             &mut self.validator_list.to_account_info().data.borrow_mut(),
             index,
             validator,
         )?;
+        self.state.validator_system.update_metadata(
+            &mut self.validator_list.to_account_info().data.borrow_mut(),
+            index,
+            |metadata| metadata.flags |= ValidatorMetadata::FLAG_MANUAL_SCORE,
+        )?;
 
         emit!(SetValidatorScoreEvent {
             state: self.state.key(),
@@ -53,6 +81,32 @@ impl<'info> SetValidatorScore<'info> {
             score_change,
         });
 
+        Ok(())
+    }
+    /// lets rescore_validator score the validator again
+    pub fn release(&mut self, index: u32, validator_vote: Pubkey) -> Result<()> {
+        let index = self.state.validator_system.resolve_index(
+            &self.validator_list.to_account_info().data.borrow(),
+            index,
+            &validator_vote,
+        )?;
+        self.state.validator_system.get_checked(
+            &self.validator_list.to_account_info().data.borrow(),
+            index,
+            &validator_vote,
+        )?;
+        self.state.validator_system.update_metadata(
+            &mut self.validator_list.to_account_info().data.borrow_mut(),
+            index,
+            |metadata| metadata.flags &= !ValidatorMetadata::FLAG_MANUAL_SCORE,
+        )?;
+
+        emit!(ReleaseValidatorScoreEvent {
+            state: self.state.key(),
+            validator: validator_vote,
+            index,
+        });
+
         Ok(())
     }
 }
@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::StaderLiquidStakingError,
+    events::{
+        management::{SetValidatorScoresEvent, ValidatorScoreChange},
+        U32ValueChange,
+    },
+    state::{
+        directed_stake::DirectedStake,
+        validator_system::{ValidatorList, ValidatorMetadata},
+    },
+    State,
+};
+
+/// remaining_accounts: the directed_stake account of every update, in the same order
+#[derive(Accounts)]
+pub struct SetValidatorScores<'info> {
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    #[account(
+        address = state.validator_system.manager_authority
+            @ StaderLiquidStakingError::InvalidValidatorManager
+    )]
+    pub manager_authority: Signer<'info>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct ValidatorScoreUpdate {
+    pub index: u32,
+    pub validator_vote: Pubkey,
+    pub score: u32,
+}
+
+impl<'info> SetValidatorScores<'info> {
+    /// All the updates are applied or none of them (any invalid index/vote pair fails the tx)
+    pub fn process(
+        &mut self,
+        directed_stake_accounts: &[AccountInfo<'info>],
+        updates: Vec<ValidatorScoreUpdate>,
+    ) -> Result<()> {
+        self.state.check_not_paused(State::PAUSE_STAKE_MOVES)?;
+        require!(
+            !updates.is_empty(),
+            StaderLiquidStakingError::EmptyValidatorScoreUpdates
+        );
+        // removed records are zeroed, a default key would match them
+        require!(
+            updates
+                .iter()
+                .all(|update| update.validator_vote != Pubkey::default()),
+            StaderLiquidStakingError::WrongValidatorAccountOrIndex
+        );
+        require_eq!(
+            directed_stake_accounts.len(),
+            updates.len(),
+            StaderLiquidStakingError::MissingDirectedStakeAccounts
+        );
+
+        let mut total_validator_score = self.state.validator_system.total_validator_score as u64;
+        let mut changes = Vec::with_capacity(updates.len());
+        for (
+            ValidatorScoreUpdate {
+                index,
+                validator_vote,
+                score,
+            },
+            directed_stake,
+        ) in updates.into_iter().zip(directed_stake_accounts)
+        {
+            let index = self.state.validator_system.resolve_index(
+                &self.validator_list.to_account_info().data.borrow(),
+                index,
+                &validator_vote,
+            )?;
+            // read the record again every time, the same validator can repeat in the batch
+            let mut validator = self.state.validator_system.get_checked(
+                &self.validator_list.to_account_info().data.borrow(),
+                index,
+                &validator_vote,
+            )?;
+            let directed_shares =
+                DirectedStake::load_shares(directed_stake, &self.state.key(), &validator_vote)
+                    .map_err(|e| e.with_account_name("directed_stake"))?;
+            self.state
+                .on_validator_score_change(validator.score, score, directed_shares);
+            total_validator_score = total_validator_score - validator.score as u64 + score as u64;
+            changes.push(ValidatorScoreChange {
+                validator: validator_vote,
+                index,
+                score_change: U32ValueChange {
+                    old: validator.score,
+                    new: score,
+                },
+            });
+            validator.score = score;
+            self.state.validator_system.set(
+                &mut self.validator_list.to_account_info().data.borrow_mut(),
+                index,
+                validator,
+            )?;
+            self.state.validator_system.update_metadata(
+                &mut self.validator_list.to_account_info().data.borrow_mut(),
+                index,
+                |metadata| metadata.flags |= ValidatorMetadata::FLAG_MANUAL_SCORE,
+            )?;
+        }
+
+        let total_validator_score_change = {
+            let old = self.state.validator_system.total_validator_score;
+            self.state.validator_system.total_validator_score =
+                u32::try_from(total_validator_score)
+                    .map_err(|_| error!(StaderLiquidStakingError::CalculationFailure))?;
+            U32ValueChange {
+                old,
+                new: self.state.validator_system.total_validator_score,
+            }
+        };
+
+        emit!(SetValidatorScoresEvent {
+            state: self.state.key(),
+            changes,
+            total_validator_score_change,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::StaderLiquidStakingError, events::management::UnblockValidatorEvent,
+    state::validator_blocklist::ValidatorBlocklistEntry, State,
+};
+
+#[derive(Accounts)]
+pub struct UnblockValidator<'info> {
+    pub state: Account<'info, State>,
+    #[account(
+        address = state.validator_system.manager_authority
+            @ StaderLiquidStakingError::InvalidValidatorManager
+    )]
+    pub manager_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        close = rent_receiver,
+        has_one = state,
+        seeds = [
+            &state.key().to_bytes(),
+            ValidatorBlocklistEntry::SEED,
+            &blocklist_entry.validator_vote.to_bytes(),
+        ],
+        bump,
+    )]
+    pub blocklist_entry: Account<'info, ValidatorBlocklistEntry>,
+    /// CHECK: receives the rent of the blocklist entry
+    #[account(mut)]
+    pub rent_receiver: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+}
+
+impl<'info> UnblockValidator<'info> {
+    pub fn process(&mut self) -> Result<()> {
+        emit!(UnblockValidatorEvent {
+            state: self.state.key(),
+            validator: self.blocklist_entry.validator_vote,
+            reason: self.blocklist_entry.reason,
+            blocked_epoch: self.blocklist_entry.blocked_epoch,
+            unblocked_epoch: self.clock.epoch,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::vote;
+use anchor_spl::stake::{deactivate_stake, DeactivateStake, Stake, StakeAccount};
+
+use crate::{
+    checks::check_stake_amount_and_validator,
+    error::StaderLiquidStakingError,
+    events::{management::UnstakeDelinquentValidatorEvent, U32ValueChange},
+    state::{
+        directed_stake::DirectedStake,
+        stake_system::{StakeList, StakeSystem},
+        validator_system::ValidatorList,
+        vote_account::VoteAccountSummary,
+    },
+    State,
+};
+
+#[derive(Accounts)]
+pub struct UnstakeDelinquentValidator<'info> {
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+    #[account(
+        mut,
+        address = state.stake_system.stake_list.account,
+    )]
+    pub stake_list: Account<'info, StakeList>,
+    #[account(mut)]
+    pub stake_account: Account<'info, StakeAccount>,
+    /// CHECK: parsed manually, see VoteAccountSummary
+    #[account(owner = vote::program::ID)]
+    pub validator_vote: UncheckedAccount<'info>,
+    /// CHECK: PDA
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            StakeSystem::STAKE_DEPOSIT_SEED
+        ],
+        bump = state.stake_system.stake_deposit_bump_seed
+    )]
+    pub stake_deposit_authority: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+
+    pub stake_program: Program<'info, Stake>,
+    /// CHECK: may not exist, see DirectedStake::load_shares
+    pub directed_stake: UncheckedAccount<'info>,
+}
+
+impl<'info> UnstakeDelinquentValidator<'info> {
+    /// Permissionless emergency_unstake: the vote account must show no credits
+    /// in the last state.delinquent_unstake_epochs completed epochs.
+    /// Zeroes the validator score and deactivates one of its stake accounts per call
+    pub fn process(&mut self, stake_index: u32, validator_index: u32) -> Result<()> {
+        self.state.check_not_paused(State::PAUSE_STAKE_MOVES)?;
+
+        let validator_index = self.state.validator_system.resolve_index(
+            &self.validator_list.to_account_info().data.as_ref().borrow(),
+            validator_index,
+            self.validator_vote.key,
+        )?;
+        let mut validator = self.state.validator_system.get_checked(
+            &self.validator_list.to_account_info().data.as_ref().borrow(),
+            validator_index,
+            self.validator_vote.key,
+        )?;
+        let vote = VoteAccountSummary::parse(&self.validator_vote.data.borrow())
+            .map_err(|e| e.with_account_name("validator_vote"))?;
+        require!(
+            self.state.is_validator_delinquent(&vote, self.clock.epoch),
+            StaderLiquidStakingError::ValidatorIsNotDelinquent
+        );
+
+        let stake_index = self.state.stake_system.resolve_index(
+            &self.stake_list.to_account_info().data.as_ref().borrow(),
+            stake_index,
+            self.stake_account.to_account_info().key,
+        )?;
+        let mut stake = self.state.stake_system.get_checked(
+            &self.stake_list.to_account_info().data.as_ref().borrow(),
+            stake_index,
+            self.stake_account.to_account_info().key,
+        )?;
+        // check that the account is delegated to the right validator
+        check_stake_amount_and_validator(
+            &self.stake_account,
+            stake.last_update_delegated_lamports,
+            &validator.validator_account,
+        )?;
+        // check the account is not already in emergency_unstake
+        require_eq!(
+            stake.is_emergency_unstaking,
+            0,
+            StaderLiquidStakingError::StakeAccountIsEmergencyUnstaking
+        );
+
+        // no new stake for the validator, like set_validator_score(0)
+        let score_change = U32ValueChange {
+            old: validator.score,
+            new: 0,
+        };
+        let directed_shares = DirectedStake::load_shares(
+            &self.directed_stake,
+            &self.state.key(),
+            self.validator_vote.key,
+        )
+        .map_err(|e| e.with_account_name("directed_stake"))?;
+        self.state
+            .on_validator_score_change(validator.score, 0, directed_shares);
+        self.state.validator_system.total_validator_score -= validator.score;
+        validator.score = 0;
+
+        let unstake_amount = stake.last_update_delegated_lamports;
+        self.state.on_stake_moved(unstake_amount, &self.clock)?;
+        msg!("Deactivate whole stake {}", stake.stake_account);
+        deactivate_stake(CpiContext::new_with_signer(
+            self.stake_program.to_account_info(),
+            DeactivateStake {
+                stake: self.stake_account.to_account_info(),
+                staker: self.stake_deposit_authority.to_account_info(),
+                clock: self.clock.to_account_info(),
+            },
+            &[&[
+                &self.state.key().to_bytes(),
+                StakeSystem::STAKE_DEPOSIT_SEED,
+                &[self.state.stake_system.stake_deposit_bump_seed],
+            ]],
+        ))?;
+        stake.is_emergency_unstaking = 1;
+        self.state
+            .on_stake_record_deactivating(&stake, self.clock.epoch);
+
+        // same accounting as emergency_unstake
+        validator.active_balance -= unstake_amount;
+        self.state.validator_system.total_active_balance -= unstake_amount;
+        self.state.emergency_cooling_down += unstake_amount;
+
+        self.state.stake_system.set(
+            &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
+            stake_index,
+            stake,
+        )?;
+        self.state.validator_system.set(
+            &mut self
+                .validator_list
+                .to_account_info()
+                .data
+                .as_ref()
+                .borrow_mut(),
+            validator_index,
+            validator,
+        )?;
+
+        emit!(UnstakeDelinquentValidatorEvent {
+            state: self.state.key(),
+            epoch: self.clock.epoch,
+            validator: validator.validator_account,
+            validator_index,
+            score_change,
+            stake_account: stake.stake_account,
+            stake_index,
+            unstake_amount,
+            delinquent_unstake_epochs: self.state.delinquent_unstake_epochs,
+        });
+
+        Ok(())
+    }
+}
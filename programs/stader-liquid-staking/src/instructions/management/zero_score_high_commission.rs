@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::vote;
+
+use crate::{
+    error::StaderLiquidStakingError,
+    events::{management::ZeroScoreHighCommissionEvent, U32ValueChange},
+    state::{
+        directed_stake::DirectedStake, validator_system::ValidatorList,
+        vote_account::VoteAccountSummary,
+    },
+    State,
+};
+
+#[derive(Accounts)]
+pub struct ZeroScoreHighCommission<'info> {
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    #[account(
+        mut,
+        address = state.validator_system.validator_list.account,
+    )]
+    pub validator_list: Account<'info, ValidatorList>,
+    /// CHECK: parsed manually, see VoteAccountSummary
+    #[account(owner = vote::program::ID)]
+    pub validator_vote: UncheckedAccount<'info>,
+    pub clock: Sysvar<'info, Clock>,
+    /// CHECK: may not exist, see DirectedStake::load_shares
+    pub directed_stake: UncheckedAccount<'info>,
+}
+
+impl<'info> ZeroScoreHighCommission<'info> {
+    /// Permissionless: a validator over state.max_validator_commission gets score 0,
+    /// so deactivate_stake drains its stake
+    pub fn process(&mut self, index: u32) -> Result<()> {
+        self.state.check_not_paused(State::PAUSE_STAKE_MOVES)?;
+
+        let index = self.state.validator_system.resolve_index(
+            &self.validator_list.to_account_info().data.borrow(),
+            index,
+            self.validator_vote.key,
+        )?;
+        let mut validator = self.state.validator_system.get_checked(
+            &self.validator_list.to_account_info().data.borrow(),
+            index,
+            self.validator_vote.key,
+        )?;
+        let vote = VoteAccountSummary::parse(&self.validator_vote.data.borrow())
+            .map_err(|e| e.with_account_name("validator_vote"))?;
+        require!(
+            self.state.is_validator_commission_too_high(vote.commission),
+            StaderLiquidStakingError::ValidatorCommissionIsNotTooHigh
+        );
+
+        let directed_shares = DirectedStake::load_shares(
+            &self.directed_stake,
+            &self.state.key(),
+            self.validator_vote.key,
+        )
+        .map_err(|e| e.with_account_name("directed_stake"))?;
+        self.state
+            .on_validator_score_change(validator.score, 0, directed_shares);
+        self.state.validator_system.total_validator_score -= validator.score;
+        let score_change = U32ValueChange {
+            old: validator.score,
+            new: 0,
+        };
+        validator.score = 0;
+        self.state.validator_system.set(
+            &mut self.validator_list.to_account_info().data.borrow_mut(),
+            index,
+            validator,
+        )?;
+        self.state.validator_system.update_metadata(
+            &mut self.validator_list.to_account_info().data.borrow_mut(),
+            index,
+            |metadata| metadata.observe_commission(vote.commission),
+        )?;
+
+        emit!(ZeroScoreHighCommissionEvent {
+            state: self.state.key(),
+            epoch: self.clock.epoch,
+            validator: validator.validator_account,
+            index,
+            score_change,
+            commission: vote.commission,
+            max_validator_commission: self.state.max_validator_commission,
+        });
+
+        Ok(())
+    }
+}
@@ -8,7 +8,9 @@ use anchor_spl::token::{
 
 use crate::error::StaderLiquidStakingError;
 use crate::events::user::DepositEvent;
+use crate::state::directed_stake::DirectedStake;
 use crate::state::liq_pool::LiqPool;
+use crate::state::validator_system::ValidatorList;
 use crate::{require_lte, State};
 
 #[derive(Accounts)]
@@ -80,14 +82,21 @@ pub struct Deposit<'info> {
     )]
     pub stader_sol_mint_authority: UncheckedAccount<'info>,
 
+    // only required with a preferred validator
+    #[account(address = state.validator_system.validator_list.account)]
+    pub validator_list: Option<Account<'info, ValidatorList>>,
+    #[account(mut, has_one = state)]
+    pub directed_stake: Option<Box<Account<'info, DirectedStake>>>,
+
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
 }
 
 impl<'info> Deposit<'info> {
     // fn deposit_sol()
-    pub fn process(&mut self, lamports: u64) -> Result<()> {
-        require!(!self.state.paused, StaderLiquidStakingError::ProgramIsPaused);
+    pub fn process(&mut self, lamports: u64, preferred_validator_index: Option<u32>) -> Result<()> {
+        self.state.check_not_paused(State::PAUSE_DEPOSITS)?;
+        self.state.check_not_winding_down()?;
 
         require_gte!(
             lamports,
@@ -116,8 +125,14 @@ impl<'info> Deposit<'info> {
         let total_virtual_staked_lamports = self.state.total_virtual_staked_lamports();
         let stader_sol_supply = self.state.stader_sol_supply;
 
+        // the whole deposit is transferred, the fee part goes to the reserve
+        // and is credited to staderSOL holders
+        let stale_price_fee = self.state.stale_price_fee(&Clock::get()?, lamports)?;
+
         //compute how many staderSOL to sell/mint for the user, base on how many lamports being deposited
-        let user_stader_sol_buy_order = self.state.calc_stader_sol_from_lamports(lamports)?;
+        let user_stader_sol_buy_order = self
+            .state
+            .calc_stader_sol_from_lamports(lamports - stale_price_fee)?;
         msg!("--- user_s_sol_buy_order {}", user_stader_sol_buy_order);
 
         //First we try to "sell" staderSOL to the user from the LiqPool.
@@ -132,14 +147,12 @@ impl<'info> Deposit<'info> {
         //if we can sell from the LiqPool
         let sol_swapped = if stader_sol_swapped > 0 {
             // how much lamports go into the LiqPool?
-            let sol_swapped = if user_stader_sol_buy_order == stader_sol_swapped {
-                //we are fulfilling 100% the user order
-                lamports //100% of the user deposit
-            } else {
-                // partially filled
-                // then it's the lamport value of the tokens we're selling
-                self.state.stader_sol_to_sol(stader_sol_swapped)?
-            };
+            let sol_swapped = self.state.deposit_sol_swapped(
+                lamports,
+                stale_price_fee,
+                user_stader_sol_buy_order,
+                stader_sol_swapped,
+            )?;
 
             // transfer staderSOL to the user
 
@@ -181,7 +194,9 @@ impl<'info> Deposit<'info> {
         // check if we have more lamports from the user besides the amount we swapped
         let sol_deposited = lamports - sol_swapped;
         if sol_deposited > 0 {
-            self.state.check_staking_cap(sol_deposited)?;
+            // the stale price fee is not staked by the user
+            self.state
+                .check_staking_cap(sol_deposited.saturating_sub(stale_price_fee))?;
 
             // transfer sol_deposited to reserve
             transfer(
@@ -220,6 +235,37 @@ impl<'info> Deposit<'info> {
             self.state.on_stader_sol_mint(stader_sol_minted);
         }
 
+        // the whole deposit value is directed, including the part bought from the LiqPool
+        let directed_validator = if let Some(validator_index) = preferred_validator_index {
+            let (validator_list, directed_stake) =
+                match (&self.validator_list, &mut self.directed_stake) {
+                    (Some(validator_list), Some(directed_stake)) => {
+                        (validator_list, directed_stake)
+                    }
+                    _ => return err!(StaderLiquidStakingError::MissingDirectedStakeAccounts),
+                };
+            let validator_index = self.state.validator_system.resolve_index(
+                &validator_list.to_account_info().data.borrow(),
+                validator_index,
+                &directed_stake.validator_vote,
+            )?;
+            let validator = self.state.validator_system.get_checked(
+                &validator_list.to_account_info().data.borrow(),
+                validator_index,
+                &directed_stake.validator_vote,
+            )?;
+            // the stake target of a zero score validator ignores its directed stake
+            require_neq!(
+                validator.score,
+                0,
+                StaderLiquidStakingError::DirectedValidatorHasZeroScore
+            );
+            directed_stake.shares += self.state.on_directed_deposit(lamports)?;
+            Some(validator.validator_account)
+        } else {
+            None
+        };
+
         emit!(DepositEvent {
             state: self.state.key(),
             sol_owner: self.transfer_from.key(),
@@ -233,7 +279,11 @@ impl<'info> Deposit<'info> {
             sol_deposited,
             stader_sol_minted,
             total_virtual_staked_lamports,
-            stader_sol_supply
+            stader_sol_supply,
+            directed_validator,
+            preferred_validator_index,
+            total_directed_lamports: self.state.total_directed_lamports,
+            stale_price_fee,
         });
 
         Ok(())
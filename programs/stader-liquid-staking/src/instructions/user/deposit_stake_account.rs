@@ -1,14 +1,16 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::stake::instruction::LockupArgs;
 use anchor_lang::solana_program::{
-    program::invoke, stake, stake::state::StakeAuthorize, system_program,
+    program::invoke, stake, stake::state::StakeAuthorize, system_program, vote,
 };
 use anchor_spl::stake::{Stake, StakeAccount};
 use anchor_spl::token::{mint_to, Mint, MintTo, Token, TokenAccount};
 
 use crate::events::user::DepositStakeAccountEvent;
 use crate::state::stake_system::StakeList;
+use crate::state::validator_blocklist::ValidatorBlocklistEntry;
 use crate::state::validator_system::ValidatorList;
+use crate::state::vote_account::VoteAccountSummary;
 use crate::{error::StaderLiquidStakingError, require_lte, state::stake_system::StakeSystem, State, ID};
 
 #[derive(Accounts)]
@@ -72,12 +74,27 @@ pub struct DepositStakeAccount<'info> {
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub stake_program: Program<'info, Stake>,
+
+    /// CHECK: parsed manually, the vote account the stake is delegated to
+    #[account(owner = vote::program::ID)]
+    pub validator_vote: UncheckedAccount<'info>,
+    /// CHECK: must not exist, see block_validator
+    #[account(
+        seeds = [
+            &state.key().to_bytes(),
+            ValidatorBlocklistEntry::SEED,
+            &validator_vote.key().to_bytes(),
+        ],
+        bump,
+    )]
+    pub blocklist_entry: UncheckedAccount<'info>,
 }
 
 impl<'info> DepositStakeAccount<'info> {
     pub const WAIT_EPOCHS: u64 = 0; // Accepting fresh/redelegated accounts also because those are mergeable anyways
     pub fn process(&mut self, validator_index: u32) -> Result<()> {
-        require!(!self.state.paused, StaderLiquidStakingError::ProgramIsPaused);
+        self.state.check_not_paused(State::PAUSE_DEPOSITS)?;
+        self.state.check_not_winding_down()?;
 
         // impossible to happen check outside bug (staderSOL mint auth is a PDA)
         require_lte!(
@@ -135,6 +152,16 @@ impl<'info> DepositStakeAccount<'info> {
                 .map_err(|e| e.with_account_name("stake_account"));
         }
 
+        require_keys_eq!(
+            self.validator_vote.key(),
+            delegation.voter_pubkey,
+            StaderLiquidStakingError::WrongValidatorAccountOrIndex
+        );
+        let validator_index = self.state.validator_system.resolve_index(
+            &self.validator_list.to_account_info().data.as_ref().borrow(),
+            validator_index,
+            &delegation.voter_pubkey,
+        )?;
         let mut validator = self.state.validator_system.get_checked(
             &self.validator_list.to_account_info().data.as_ref().borrow(),
             validator_index,
@@ -142,8 +169,26 @@ impl<'info> DepositStakeAccount<'info> {
         )?;
         // record balance for event log
         let validator_active_balance = validator.active_balance;
+
+        require!(
+            self.blocklist_entry.data_is_empty(),
+            StaderLiquidStakingError::ValidatorIsBlocklisted
+        );
+        let vote = VoteAccountSummary::parse(&self.validator_vote.data.borrow())
+            .map_err(|e| e.with_account_name("validator_vote"))?;
+        require!(
+            !self.state.is_validator_commission_too_high(vote.commission),
+            StaderLiquidStakingError::ValidatorCommissionIsTooHigh
+        );
         // update validator.active_balance
         validator.active_balance += delegation.stake;
+        require_gte!(
+            self.state.max_validator_stake(
+                self.state.validator_system.total_active_balance + delegation.stake
+            ),
+            validator.active_balance,
+            StaderLiquidStakingError::ValidatorStakeShareExceeded
+        );
         self.state.validator_system.set(
             &mut self
                 .validator_list
@@ -245,15 +290,20 @@ impl<'info> DepositStakeAccount<'info> {
             )?;
         }
 
-        self.state.stake_system.add(
+        let stake_index = self.state.stake_system.add(
             &mut self.stake_list.to_account_info().data.as_ref().borrow_mut(),
             self.stake_account.to_account_info().key,
             delegation.stake,
             &self.clock,
             0, // is_emergency_unstaking? no
         )?;
+        self.state.on_stake_record_added(self.clock.epoch);
 
-        let stader_sol_to_mint = self.state.calc_stader_sol_from_lamports(delegation.stake)?;
+        // the fee part of the stake is credited to staderSOL holders
+        let stale_price_fee = self.state.stale_price_fee(&self.clock, delegation.stake)?;
+        let stader_sol_to_mint = self
+            .state
+            .calc_stader_sol_from_lamports(delegation.stake - stale_price_fee)?;
 
         mint_to(
             CpiContext::new_with_signer(
@@ -283,7 +333,7 @@ impl<'info> DepositStakeAccount<'info> {
             stake: self.stake_account.key(),
             delegated: delegation.stake,
             withdrawer: old_withdrawer,
-            stake_index: self.state.stake_system.stake_count() - 1,
+            stake_index,
             validator: delegation.voter_pubkey,
             validator_index,
             validator_active_balance,
@@ -291,7 +341,8 @@ impl<'info> DepositStakeAccount<'info> {
             user_stader_sol_balance,
             stader_sol_minted: stader_sol_to_mint,
             total_virtual_staked_lamports,
-            stader_sol_supply
+            stader_sol_supply,
+            stale_price_fee,
         });
         Ok(())
     }
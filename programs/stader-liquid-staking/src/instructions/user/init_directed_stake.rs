@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_program;
+
+use crate::{state::directed_stake::DirectedStake, State};
+
+#[derive(Accounts)]
+pub struct InitDirectedStake<'info> {
+    pub state: Account<'info, State>,
+
+    /// CHECK: only the key is used
+    pub validator_vote: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = rent_payer,
+        space = DirectedStake::LEN,
+        seeds = [
+            &state.key().to_bytes(),
+            DirectedStake::SEED,
+            &validator_vote.key().to_bytes(),
+        ],
+        bump,
+    )]
+    pub directed_stake: Account<'info, DirectedStake>,
+    #[account(
+        mut,
+        owner = system_program::ID
+    )]
+    pub rent_payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitDirectedStake<'info> {
+    /// Permissionless, must be done once per validator before directing deposits to it
+    pub fn process(&mut self) -> Result<()> {
+        self.directed_stake.set_inner(DirectedStake {
+            state: self.state.key(),
+            validator_vote: self.validator_vote.key(),
+            shares: 0,
+        });
+        Ok(())
+    }
+}
@@ -1,7 +1,11 @@
 pub mod deposit;
 pub mod deposit_stake_account;
+pub mod init_directed_stake;
+pub mod wind_down_redeem;
 pub mod withdraw_stake_account;
 
 pub use deposit::*;
 pub use deposit_stake_account::*;
+pub use init_directed_stake::*;
+pub use wind_down_redeem::*;
 pub use withdraw_stake_account::*;
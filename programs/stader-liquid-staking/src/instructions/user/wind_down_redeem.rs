@@ -0,0 +1,119 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::token::{burn, Burn, Mint, Token, TokenAccount};
+
+use crate::{
+    checks::check_token_source_account, error::StaderLiquidStakingError,
+    events::user::WindDownRedeemEvent, State,
+};
+
+#[derive(Accounts)]
+pub struct WindDownRedeem<'info> {
+    #[account(
+        mut,
+        has_one = stader_sol_mint
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(mut)]
+    pub stader_sol_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = state.stader_sol_mint
+    )]
+    pub burn_stader_sol_from: Box<Account<'info, TokenAccount>>,
+    pub burn_stader_sol_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            &state.key().to_bytes(),
+            State::RESERVE_SEED
+        ],
+        bump = state.reserve_bump_seed
+    )]
+    pub reserve_pda: SystemAccount<'info>,
+    #[account(mut)]
+    pub transfer_sol_to: SystemAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> WindDownRedeem<'info> {
+    /// burns staderSOL and pays its pro-rata share of the reserve, without fees
+    pub fn process(&mut self, stader_sol_amount: u64) -> Result<()> {
+        self.state.check_not_paused(State::PAUSE_DELAYED_UNSTAKE)?;
+        require!(
+            self.state.wind_down,
+            StaderLiquidStakingError::ProtocolIsNotWindingDown
+        );
+        // every lamport must be back in the reserve, otherwise the share would be underpaid
+        require!(
+            self.state.validator_system.total_active_balance == 0
+                && self.state.total_cooling_down() == 0,
+            StaderLiquidStakingError::StakesAreNotWithdrawn
+        );
+
+        check_token_source_account(
+            &self.burn_stader_sol_from,
+            self.burn_stader_sol_authority.key,
+            stader_sol_amount,
+        )
+        .map_err(|e| e.with_account_name("burn_stader_sol_from"))?;
+        let user_stader_sol_balance = self.burn_stader_sol_from.amount;
+
+        // save staderSOL price source
+        let total_virtual_staked_lamports = self.state.total_virtual_staked_lamports();
+        let stader_sol_supply = self.state.stader_sol_supply;
+
+        // with no stake left total_virtual_staked_lamports is the reserve minus the unclaimed tickets
+        let sol_redeemed = self.state.stader_sol_to_sol(stader_sol_amount)?;
+
+        burn(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                Burn {
+                    mint: self.stader_sol_mint.to_account_info(),
+                    from: self.burn_stader_sol_from.to_account_info(),
+                    authority: self.burn_stader_sol_authority.to_account_info(),
+                },
+            ),
+            stader_sol_amount,
+        )?;
+        self.state.on_stader_sol_burn(stader_sol_amount);
+
+        transfer(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: self.reserve_pda.to_account_info(),
+                    to: self.transfer_sol_to.to_account_info(),
+                },
+                &[&[
+                    &self.state.key().to_bytes(),
+                    State::RESERVE_SEED,
+                    &[self.state.reserve_bump_seed],
+                ]],
+            ),
+            sol_redeemed,
+        )?;
+        self.state.on_transfer_from_reserve(sol_redeemed);
+
+        emit!(WindDownRedeemEvent {
+            state: self.state.key(),
+            epoch: self.clock.epoch,
+            user_stader_sol_balance,
+            user_stader_sol_auth: self.burn_stader_sol_authority.key(),
+            stader_sol_burned: stader_sol_amount,
+            sol_redeemed,
+            transfer_sol_to: self.transfer_sol_to.key(),
+            reserve_balance: self.reserve_pda.lamports(),
+            total_virtual_staked_lamports,
+            stader_sol_supply,
+        });
+
+        Ok(())
+    }
+}
@@ -108,7 +108,7 @@ impl<'info> WithdrawStakeAccount<'info> {
         stader_sol_amount: u64,
         beneficiary: Pubkey,
     ) -> Result<()> {
-        require!(!self.state.paused, StaderLiquidStakingError::ProgramIsPaused);
+        self.state.check_not_paused(State::PAUSE_LIQUID_UNSTAKE)?;
         require!(
             self.state.withdraw_stake_account_enabled,
             StaderLiquidStakingError::WithdrawStakeAccountIsNotEnabled
@@ -126,6 +126,11 @@ impl<'info> WithdrawStakeAccount<'info> {
         )
         .map_err(|e| e.with_account_name("burn_stader_sol_from"))?;
 
+        let stake_index = self.state.stake_system.resolve_index(
+            &self.stake_list.to_account_info().data.as_ref().borrow(),
+            stake_index,
+            self.stake_account.to_account_info().key,
+        )?;
         let mut stake = self.state.stake_system.get_checked(
             &self.stake_list.to_account_info().data.as_ref().borrow(),
             stake_index,
@@ -150,6 +155,15 @@ impl<'info> WithdrawStakeAccount<'info> {
             StaderLiquidStakingError::RequiredActiveStake
         );
 
+        let validator_index = self.state.validator_system.resolve_index(
+            &self.validator_list.to_account_info().data.as_ref().borrow(),
+            validator_index,
+            &self
+                .stake_account
+                .delegation()
+                .map(|delegation| delegation.voter_pubkey)
+                .unwrap_or_default(),
+        )?;
         let mut validator = self.state.validator_system.get(
             &self.validator_list.to_account_info().data.as_ref().borrow(),
             validator_index,
@@ -162,10 +176,12 @@ impl<'info> WithdrawStakeAccount<'info> {
             &validator.validator_account,
         )?;
 
+        // compute how many lamport the withdraw request's staderSOL amount represents
+        let sol_value = self.state.stader_sol_to_sol(stader_sol_amount)?;
+        // the fee value is burned but not delivered, credited to staderSOL holders
+        let stale_price_fee = self.state.stale_price_fee(&self.clock, sol_value)?;
         // compute how many lamport to split
         let split_lamports = {
-            // compute how many lamport the withdraw request's staderSOL amount represents
-            let sol_value = self.state.stader_sol_to_sol(stader_sol_amount)?;
             require_gte!(
                 sol_value,
                 self.state.min_withdraw,
@@ -178,7 +194,7 @@ impl<'info> WithdrawStakeAccount<'info> {
             // The staderSOL fee value is sending to the treasury but
             // the corresponding SOL value is not delivering inside the stake to the user
             // because it is a fee user is paying for running this instruction
-            sol_value - withdraw_stake_account_fee_lamports
+            sol_value - withdraw_stake_account_fee_lamports - stale_price_fee
         };
 
         // check withdraw amount (new stake account) >= self.state.stake_system.min_stake
@@ -209,7 +225,10 @@ impl<'info> WithdrawStakeAccount<'info> {
 
         let stader_sol_fees = if treasury_stader_sol_balance.is_some() {
             // saturating sub may be needed in case of some weird calculation rounding
-            stader_sol_amount.saturating_sub(self.state.calc_stader_sol_from_lamports(split_lamports)?)
+            stader_sol_amount.saturating_sub(
+                self.state
+                    .calc_stader_sol_from_lamports(split_lamports + stale_price_fee)?,
+            )
         } else {
             0
         };
@@ -243,6 +262,8 @@ impl<'info> WithdrawStakeAccount<'info> {
             )?;
             self.state.on_stader_sol_burn(stader_sol_burned);
         }
+        self.state
+            .unwind_directed_stake(sol_value, total_virtual_staked_lamports)?;
 
         // split split_lamports from stake account into out split_stake_account
         msg!(
@@ -360,6 +381,7 @@ impl<'info> WithdrawStakeAccount<'info> {
             fee_bp_cents: self.state.withdraw_stake_account_fee.bp_cents,
             total_virtual_staked_lamports,
             stader_sol_supply,
+            stale_price_fee,
         });
 
         Ok(())
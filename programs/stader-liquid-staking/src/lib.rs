@@ -12,6 +12,7 @@ pub mod state;
 use instructions::*;
 
 pub use state::State;
+use state::validator_system::ValidatorScoringConfig;
 
 declare_id!("SdsLUAYNXCpwdkJ7skmjaF8PxiqoUmNmAQfN2D2Jwmb");
 
@@ -56,11 +57,42 @@ pub mod stader_liquid_staking {
         ctx.accounts.process(data)
     }
 
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        ctx.accounts.process()
+    }
+
+    pub fn cancel_authority_change(ctx: Context<ChangeAuthority>) -> Result<()> {
+        ctx.accounts.cancel()
+    }
+
     pub fn add_validator(ctx: Context<AddValidator>, score: u32) -> Result<()> {
         // check_context(&ctx)?;
         ctx.accounts.process(score)
     }
 
+    pub fn apply_validator(ctx: Context<ApplyValidator>) -> Result<()> {
+        // check_context(&ctx)?;
+        ctx.accounts.process()
+    }
+
+    pub fn approve_validator_application(
+        ctx: Context<ApproveValidatorApplication>,
+        score: u32,
+    ) -> Result<()> {
+        // check_context(&ctx)?;
+        ctx.accounts.process(score)
+    }
+
+    pub fn reject_validator_application(ctx: Context<RejectValidatorApplication>) -> Result<()> {
+        // check_context(&ctx)?;
+        ctx.accounts.process()
+    }
+
+    pub fn cancel_validator_application(ctx: Context<CancelValidatorApplication>) -> Result<()> {
+        // check_context(&ctx)?;
+        ctx.accounts.process()
+    }
+
     pub fn remove_validator(
         ctx: Context<RemoveValidator>,
         index: u32,
@@ -80,6 +112,59 @@ pub mod stader_liquid_staking {
         ctx.accounts.process(index, validator_vote, score)
     }
 
+    pub fn release_validator_score(
+        ctx: Context<SetValidatorScore>,
+        index: u32,
+        validator_vote: Pubkey,
+    ) -> Result<()> {
+        // check_context(&ctx)?;
+        ctx.accounts.release(index, validator_vote)
+    }
+
+    pub fn set_validator_scores<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetValidatorScores<'info>>,
+        updates: Vec<ValidatorScoreUpdate>,
+    ) -> Result<()> {
+        // check_context(&ctx)?;
+        ctx.accounts.process(ctx.remaining_accounts, updates)
+    }
+
+    pub fn config_validator_scoring(
+        ctx: Context<ConfigValidatorScoring>,
+        config: ValidatorScoringConfig,
+    ) -> Result<()> {
+        // check_context(&ctx)?;
+        ctx.accounts.process(config)
+    }
+
+    pub fn rescore_validator(ctx: Context<RescoreValidator>, index: u32) -> Result<()> {
+        // check_context(&ctx)?;
+        ctx.accounts.process(index)
+    }
+
+    pub fn block_validator(ctx: Context<BlockValidator>, reason: u8) -> Result<()> {
+        // check_context(&ctx)?;
+        ctx.accounts.process(reason)
+    }
+
+    pub fn unblock_validator(ctx: Context<UnblockValidator>) -> Result<()> {
+        // check_context(&ctx)?;
+        ctx.accounts.process()
+    }
+
+    pub fn zero_score_high_commission(
+        ctx: Context<ZeroScoreHighCommission>,
+        index: u32,
+    ) -> Result<()> {
+        // check_context(&ctx)?;
+        ctx.accounts.process(index)
+    }
+
+    pub fn compact_lists(ctx: Context<CompactLists>) -> Result<()> {
+        // check_context(&ctx)?;
+        ctx.accounts.process()
+    }
+
     pub fn config_validator_system(
         ctx: Context<ConfigValidatorSystem>,
         extra_runs: u32,
@@ -89,9 +174,18 @@ pub mod stader_liquid_staking {
     }
 
     // deposit AKA stake, AKA deposit_sol
-    pub fn deposit(ctx: Context<Deposit>, lamports: u64) -> Result<()> {
+    pub fn deposit(
+        ctx: Context<Deposit>,
+        lamports: u64,
+        preferred_validator_index: Option<u32>,
+    ) -> Result<()> {
         // check_context(&ctx)?;
-        ctx.accounts.process(lamports)
+        ctx.accounts.process(lamports, preferred_validator_index)
+    }
+
+    pub fn init_directed_stake(ctx: Context<InitDirectedStake>) -> Result<()> {
+        // check_context(&ctx)?;
+        ctx.accounts.process()
     }
 
     // SPL stake pool like
@@ -131,6 +225,26 @@ pub mod stader_liquid_staking {
         ctx.accounts.process(params)
     }
 
+    pub fn init_config_history(ctx: Context<InitConfigHistory>) -> Result<()> {
+        ctx.accounts.process()
+    }
+
+    pub fn init_crank_reward_vault(ctx: Context<InitCrankRewardVault>) -> Result<()> {
+        ctx.accounts.process()
+    }
+
+    pub fn init_pending_config(ctx: Context<InitPendingConfig>) -> Result<()> {
+        ctx.accounts.process()
+    }
+
+    pub fn execute_pending_config(ctx: Context<ExecutePendingConfig>) -> Result<()> {
+        ctx.accounts.process()
+    }
+
+    pub fn cancel_pending_config(ctx: Context<CancelPendingConfig>) -> Result<()> {
+        ctx.accounts.process()
+    }
+
     pub fn update_stader_sol_token_metadata(
         ctx: Context<UpdateStaderSolTokenMetadata>,
         name: String,
@@ -171,6 +285,15 @@ pub mod stader_liquid_staking {
         ctx.accounts.process(validator_index)
     }
 
+    pub fn stake_reserve_multi<'info>(
+        ctx: Context<'_, '_, '_, 'info, StakeReserveMulti<'info>>,
+        validator_indices: Vec<u32>,
+    ) -> Result<()> {
+        // check_context(&ctx)?;
+        ctx.accounts
+            .process(ctx.remaining_accounts, validator_indices)
+    }
+
     pub fn update_active(
         ctx: Context<UpdateActive>,
         stake_index: u32,
@@ -184,6 +307,22 @@ pub mod stader_liquid_staking {
         ctx.accounts.process(stake_index)
     }
 
+    pub fn update_active_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpdateActiveBatch<'info>>,
+        entries: Vec<UpdateActiveBatchEntry>,
+    ) -> Result<()> {
+        // check_context(&ctx)?;
+        ctx.accounts.process(ctx.remaining_accounts, entries)
+    }
+
+    pub fn update_deactivated_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpdateDeactivatedBatch<'info>>,
+        stake_indices: Vec<u32>,
+    ) -> Result<()> {
+        // check_context(&ctx)?;
+        ctx.accounts.process(ctx.remaining_accounts, stake_indices)
+    }
+
     pub fn deactivate_stake(
         ctx: Context<DeactivateStake>,
         stake_index: u32,
@@ -202,6 +341,15 @@ pub mod stader_liquid_staking {
         ctx.accounts.process(stake_index, validator_index)
     }
 
+    pub fn unstake_delinquent_validator(
+        ctx: Context<UnstakeDelinquentValidator>,
+        stake_index: u32,
+        validator_index: u32,
+    ) -> Result<()> {
+        // check_context(&ctx)?;
+        ctx.accounts.process(stake_index, validator_index)
+    }
+
     pub fn partial_unstake(
         ctx: Context<PartialUnstake>,
         stake_index: u32,
@@ -235,16 +383,36 @@ pub mod stader_liquid_staking {
             .process(stake_index, source_validator_index, dest_validator_index)
     }
 
+    pub fn rebalance(
+        ctx: Context<Rebalance>,
+        stake_index: u32,
+        source_validator_index: u32,
+        dest_validator_index: u32,
+    ) -> Result<()> {
+        // check_context(&ctx)?;
+        ctx.accounts
+            .process(stake_index, source_validator_index, dest_validator_index)
+    }
+
+    pub fn sweep_reserve_surplus(ctx: Context<SweepReserveSurplus>) -> Result<()> {
+        // check_context(&ctx)?;
+        ctx.accounts.process()
+    }
+
     // emergency pauses the contract
-    pub fn pause(ctx: Context<EmergencyPause>) -> Result<()> {
+    pub fn pause(ctx: Context<EmergencyPause>, groups: u8) -> Result<()> {
         // check_context(&ctx)?;
-        ctx.accounts.pause()
+        ctx.accounts.pause(groups)
     }
 
     // resumes the contract
-    pub fn resume(ctx: Context<EmergencyPause>) -> Result<()> {
+    pub fn resume(ctx: Context<EmergencyPause>, groups: u8) -> Result<()> {
         // check_context(&ctx)?;
-        ctx.accounts.resume()
+        ctx.accounts.resume(groups)
+    }
+
+    pub fn reset_circuit_breaker(ctx: Context<EmergencyPause>) -> Result<()> {
+        ctx.accounts.reset_circuit_breaker()
     }
 
     // immediate withdraw of an active stake account - feature can be enabled or disable by the DAO
@@ -260,11 +428,31 @@ pub mod stader_liquid_staking {
             .process(stake_index, validator_index, stader_sol_amount, beneficiary)
     }
 
+    // pro-rata redemption from the reserve once the wind-down is complete
+    pub fn wind_down_redeem(ctx: Context<WindDownRedeem>, stader_sol_amount: u64) -> Result<()> {
+        ctx.accounts.process(stader_sol_amount)
+    }
+
     pub fn realloc_validator_list(ctx: Context<ReallocValidatorList>, capacity: u32) -> Result<()> {
         // check_context(&ctx)?;
         ctx.accounts.process(capacity)
     }
 
+    pub fn start_wind_down(ctx: Context<StartWindDown>) -> Result<()> {
+        ctx.accounts.process()
+    }
+
+    pub fn migrate_state(ctx: Context<MigrateState>) -> Result<()> {
+        ctx.accounts.process()
+    }
+
+    pub fn migrate_validator_list(
+        ctx: Context<MigrateValidatorList>,
+        capacity: u32,
+    ) -> Result<()> {
+        ctx.accounts.process(capacity)
+    }
+
     pub fn realloc_stake_list(ctx: Context<ReallocStakeList>, capacity: u32) -> Result<()> {
         // check_context(&ctx)?;
         ctx.accounts.process(capacity)
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::State;
+
+/// First anomaly detected by the cranks since the last reset.
+/// When tripped the breaker pauses the groups related to the anomaly
+/// and only the pause authority can reset it (see reset_circuit_breaker)
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct CircuitBreaker {
+    pub reason: u8, // CircuitBreaker::REASON_*
+    pub tripped_epoch: u64,
+    pub expected: u64,      // value the program accounted for
+    pub observed: u64,      // value found on chain
+    pub tripped_groups: u8, // pause groups held by the breaker until reset
+}
+
+impl CircuitBreaker {
+    pub const REASON_NONE: u8 = 0;
+    /// staderSOL supply is higher than the minted by the program
+    pub const REASON_EXTERNAL_MINT: u8 = 1;
+    /// reserve has less lamports than available_reserve_balance + rent
+    pub const REASON_RESERVE_DEFICIT: u8 = 2;
+    /// stake account has less lamports than at the last update
+    pub const REASON_SLASHING: u8 = 3;
+
+    pub fn is_tripped(&self) -> bool {
+        self.reason != Self::REASON_NONE
+    }
+
+    pub fn groups_for(reason: u8) -> u8 {
+        match reason {
+            // staderSOL price is wrong, stop everything that mints or burns staderSOL
+            Self::REASON_EXTERNAL_MINT => {
+                State::PAUSE_DEPOSITS
+                    | State::PAUSE_LIQUID_UNSTAKE
+                    | State::PAUSE_LIQ_POOL
+                    | State::PAUSE_DELAYED_UNSTAKE
+            }
+            // reserve funds deposits, claims and withdrawals
+            Self::REASON_RESERVE_DEFICIT => State::PAUSE_DEPOSITS | State::PAUSE_DELAYED_UNSTAKE,
+            // avoid front-running the price drop until the loss is investigated
+            Self::REASON_SLASHING => {
+                State::PAUSE_DEPOSITS | State::PAUSE_LIQUID_UNSTAKE | State::PAUSE_LIQ_POOL
+            }
+            _ => 0,
+        }
+    }
+}
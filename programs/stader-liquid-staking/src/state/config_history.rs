@@ -0,0 +1,334 @@
+use anchor_lang::prelude::*;
+
+use crate::events::{
+    admin::{ConfigLpEvent, ConfigStaderLiquidStakingEvent},
+    BoolValueChange, FeeCentsValueChange, FeeValueChange, PubkeyValueChange, U64ValueChange,
+};
+
+/// Parameter changed by a config history entry
+#[derive(Clone, Copy, Debug, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub enum ConfigParam {
+    RewardFee,
+    SlotsForStakeDelta,
+    MinStake,
+    MinDeposit,
+    MinWithdraw,
+    StakingSolCap,
+    LiquiditySolCap,
+    WithdrawStakeAccountEnabled,
+    DelayedUnstakeFee,
+    WithdrawStakeAccountFee,
+    MaxStakeMovedPerEpoch,
+    ConfigDelayEpochs,
+    TicketWaitEpochs,
+    TicketExtraWaitSeconds,
+    ReserveSurplusPolicy,
+    LpMinFee,
+    LpMaxFee,
+    LpLiquidityTarget,
+    LpTreasuryCut,
+    // signing authorities are recorded when proposed and when accepted
+    ProposedAdminAuthority,
+    ProposedValidatorManagerAuthority,
+    ProposedPauseAuthority,
+    AdminAuthority,
+    ValidatorManagerAuthority,
+    PauseAuthority,
+    OperationalSolAccount,
+    TreasuryStaderSolAccount,
+    FeeAuthority,
+    LimitsAuthority,
+    MetadataAuthority,
+    LpConfigAuthority,
+    PausedGroups,
+    MaxValidatorStakeShare,
+    MaxValidatorCommission,
+    DelinquentUnstakeEpochs,
+    StalePriceGraceSeconds,
+    StalePriceFee,
+    CrankRewardLamports,
+    MaxCrankRewardsPerEpoch,
+    RebalanceTolerance,
+    SlashingThreshold,
+    MaxDirectedStakeShare,
+    // earliest execution epoch of the canceled changes, new is u64::MAX (never)
+    CanceledPendingConfig,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub enum ConfigValueChange {
+    U64(U64ValueChange),
+    Fee(FeeValueChange),
+    FeeCents(FeeCentsValueChange),
+    Bool(BoolValueChange),
+    Pubkey(PubkeyValueChange),
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct ConfigHistoryEntry {
+    pub epoch: u64,
+    pub slot: u64,
+    pub signer: Pubkey, // Pubkey::default() for permissionless execute_pending_config
+    pub param: ConfigParam,
+    pub change: ConfigValueChange,
+}
+
+/// Optional ring buffer of the last config changes, for auditors.
+/// Once initialized (State::config_history), instructions changing the config must pass it
+#[account]
+pub struct ConfigHistory {
+    pub state: Pubkey,
+    /// total number of entries ever recorded,
+    /// the next entry is written at total_entries % CAPACITY
+    pub total_entries: u64,
+    pub entries: Vec<ConfigHistoryEntry>,
+}
+
+impl ConfigHistory {
+    pub const SEED: &'static [u8] = b"config_history";
+    pub const CAPACITY: usize = 64;
+
+    /// space for the full buffer with the largest entries
+    pub fn serialized_len() -> usize {
+        let largest_entry = ConfigHistoryEntry {
+            epoch: 0,
+            slot: 0,
+            signer: Pubkey::default(),
+            param: ConfigParam::RewardFee,
+            change: ConfigValueChange::Pubkey(PubkeyValueChange {
+                old: Pubkey::default(),
+                new: Pubkey::default(),
+            }),
+        };
+        ConfigHistory {
+            state: Pubkey::default(),
+            total_entries: 0,
+            entries: vec![largest_entry; Self::CAPACITY],
+        }
+        .try_to_vec()
+        .unwrap()
+        .len()
+            + 8
+    }
+
+    pub fn find_address(state: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[&state.to_bytes()[..32], Self::SEED], &crate::ID)
+    }
+
+    pub fn append(&mut self, entry: ConfigHistoryEntry) {
+        let index = (self.total_entries % Self::CAPACITY as u64) as usize;
+        if index < self.entries.len() {
+            self.entries[index] = entry;
+        } else {
+            self.entries.push(entry);
+        }
+        self.total_entries += 1;
+    }
+
+    /// appends one entry per changed param
+    pub fn record(
+        &mut self,
+        clock: &Clock,
+        signer: Pubkey,
+        changes: impl IntoIterator<Item = (ConfigParam, Option<ConfigValueChange>)>,
+    ) {
+        for (param, change) in changes {
+            if let Some(change) = change {
+                self.append(ConfigHistoryEntry {
+                    epoch: clock.epoch,
+                    slot: clock.slot,
+                    signer,
+                    param,
+                    change,
+                });
+            }
+        }
+    }
+
+    pub fn config_stader_changes(
+        event: &ConfigStaderLiquidStakingEvent,
+    ) -> Vec<(ConfigParam, Option<ConfigValueChange>)> {
+        use ConfigValueChange::*;
+        let u64_change = |change: &Option<U64ValueChange>| change.clone().map(U64);
+        vec![
+            (
+                ConfigParam::RewardFee,
+                event.rewards_fee_change.clone().map(Fee),
+            ),
+            (
+                ConfigParam::SlotsForStakeDelta,
+                u64_change(&event.slots_for_stake_delta_change),
+            ),
+            (ConfigParam::MinStake, u64_change(&event.min_stake_change)),
+            (
+                ConfigParam::MinDeposit,
+                u64_change(&event.min_deposit_change),
+            ),
+            (
+                ConfigParam::MinWithdraw,
+                u64_change(&event.min_withdraw_change),
+            ),
+            (
+                ConfigParam::StakingSolCap,
+                u64_change(&event.staking_sol_cap_change),
+            ),
+            (
+                ConfigParam::LiquiditySolCap,
+                u64_change(&event.liquidity_sol_cap_change),
+            ),
+            (
+                ConfigParam::WithdrawStakeAccountEnabled,
+                event
+                    .withdraw_stake_account_enabled_change
+                    .clone()
+                    .map(Bool),
+            ),
+            (
+                ConfigParam::DelayedUnstakeFee,
+                event.delayed_unstake_fee_change.clone().map(FeeCents),
+            ),
+            (
+                ConfigParam::WithdrawStakeAccountFee,
+                event
+                    .withdraw_stake_account_fee_change
+                    .clone()
+                    .map(FeeCents),
+            ),
+            (
+                ConfigParam::MaxStakeMovedPerEpoch,
+                event.max_stake_moved_per_epoch_change.clone().map(Fee),
+            ),
+            (
+                ConfigParam::ConfigDelayEpochs,
+                u64_change(&event.config_delay_epochs_change),
+            ),
+            (
+                ConfigParam::TicketWaitEpochs,
+                u64_change(&event.ticket_wait_epochs_change),
+            ),
+            (
+                ConfigParam::TicketExtraWaitSeconds,
+                u64_change(&event.ticket_extra_wait_seconds_change),
+            ),
+            (
+                ConfigParam::ReserveSurplusPolicy,
+                u64_change(&event.reserve_surplus_policy_change),
+            ),
+            (
+                ConfigParam::MaxValidatorStakeShare,
+                event.max_validator_stake_share_change.clone().map(Fee),
+            ),
+            (
+                ConfigParam::MaxValidatorCommission,
+                u64_change(&event.max_validator_commission_change),
+            ),
+            (
+                ConfigParam::DelinquentUnstakeEpochs,
+                u64_change(&event.delinquent_unstake_epochs_change),
+            ),
+            (
+                ConfigParam::StalePriceGraceSeconds,
+                u64_change(&event.stale_price_grace_seconds_change),
+            ),
+            (
+                ConfigParam::StalePriceFee,
+                event.stale_price_fee_change.clone().map(FeeCents),
+            ),
+            (
+                ConfigParam::CrankRewardLamports,
+                u64_change(&event.crank_reward_lamports_change),
+            ),
+            (
+                ConfigParam::MaxCrankRewardsPerEpoch,
+                u64_change(&event.max_crank_rewards_per_epoch_change),
+            ),
+            (
+                ConfigParam::RebalanceTolerance,
+                event.rebalance_tolerance_change.clone().map(Fee),
+            ),
+            (
+                ConfigParam::SlashingThreshold,
+                event.slashing_threshold_change.clone().map(Fee),
+            ),
+            (
+                ConfigParam::MaxDirectedStakeShare,
+                event.max_directed_stake_share_change.clone().map(Fee),
+            ),
+        ]
+    }
+
+    pub fn config_lp_changes(
+        event: &ConfigLpEvent,
+    ) -> Vec<(ConfigParam, Option<ConfigValueChange>)> {
+        use ConfigValueChange::*;
+        vec![
+            (ConfigParam::LpMinFee, event.min_fee_change.clone().map(Fee)),
+            (ConfigParam::LpMaxFee, event.max_fee_change.clone().map(Fee)),
+            (
+                ConfigParam::LpLiquidityTarget,
+                event.liquidity_target_change.clone().map(U64),
+            ),
+            (
+                ConfigParam::LpTreasuryCut,
+                event.treasury_cut_change.clone().map(Fee),
+            ),
+        ]
+    }
+
+    pub fn paused_groups_change(old: u8, new: u8) -> (ConfigParam, Option<ConfigValueChange>) {
+        (
+            ConfigParam::PausedGroups,
+            Some(ConfigValueChange::U64(U64ValueChange {
+                old: old.into(),
+                new: new.into(),
+            })),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::*;
+
+    use super::{ConfigHistory, ConfigParam, ConfigValueChange};
+    use crate::events::U64ValueChange;
+
+    #[test]
+    fn test_ring_buffer_wraps() {
+        let mut history = ConfigHistory {
+            state: Pubkey::new_unique(),
+            total_entries: 0,
+            entries: vec![],
+        };
+        let clock = Clock::default();
+        let total = ConfigHistory::CAPACITY as u64 + 5;
+        for i in 0..total {
+            history.record(
+                &clock,
+                Pubkey::default(),
+                [(
+                    ConfigParam::MinStake,
+                    Some(ConfigValueChange::U64(U64ValueChange {
+                        old: i,
+                        new: i + 1,
+                    })),
+                )],
+            );
+        }
+        assert_eq!(history.total_entries, total);
+        assert_eq!(history.entries.len(), ConfigHistory::CAPACITY);
+        // the oldest entries are overwritten
+        for (index, entry) in history.entries.iter().enumerate() {
+            let expected_old = if (index as u64) < total % ConfigHistory::CAPACITY as u64 {
+                index as u64 + ConfigHistory::CAPACITY as u64
+            } else {
+                index as u64
+            };
+            match &entry.change {
+                ConfigValueChange::U64(change) => assert_eq!(change.old, expected_old),
+                _ => panic!("unexpected change"),
+            }
+        }
+        assert!(history.try_to_vec().unwrap().len() + 8 <= ConfigHistory::serialized_len());
+    }
+}
@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+
+/// Lamports paid to the callers of permissionless cranks, see State::take_crank_reward.
+/// Funded with plain transfers, e.g. from operational_sol_account or from the protocol fees
+#[account]
+pub struct CrankRewardVault {
+    pub state: Pubkey,
+}
+
+impl CrankRewardVault {
+    pub const SEED: &'static [u8] = b"crank_reward_vault";
+    pub const LEN: usize = 8 + 32;
+
+    pub fn find_address(state: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[&state.to_bytes()[..32], Self::SEED], &crate::ID)
+    }
+
+    /// lamports that can be paid keeping the vault rent exempt
+    pub fn available_lamports(vault: &AccountInfo) -> Result<u64> {
+        Ok(vault
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(Self::LEN)))
+    }
+}
@@ -0,0 +1,166 @@
+use anchor_lang::prelude::*;
+
+use crate::error::StaderLiquidStakingError;
+
+/// Stake directed to a validator by depositors, see deposit.
+/// Stored as shares of state.total_directed_lamports so unstakes can unwind
+/// every validator's directed stake pro-rata without touching these accounts
+#[account]
+#[derive(Debug)]
+pub struct DirectedStake {
+    pub state: Pubkey,
+    pub validator_vote: Pubkey,
+    pub shares: u64,
+}
+
+impl DirectedStake {
+    pub const SEED: &'static [u8] = b"directed_stake";
+    pub const LEN: usize = 8 + 32 + 32 + 8;
+
+    pub fn find_address(state: &Pubkey, validator_vote: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                &state.to_bytes()[..32],
+                Self::SEED,
+                &validator_vote.to_bytes()[..32],
+            ],
+            &crate::ID,
+        )
+    }
+
+    /// Shares directed to the validator, 0 when the account was never created.
+    /// The account is required by every stake target calculation, so it can't be skipped
+    pub fn load_shares(
+        account: &AccountInfo,
+        state: &Pubkey,
+        validator_vote: &Pubkey,
+    ) -> Result<u64> {
+        Ok(Self::load(account, state, validator_vote)?
+            .map(|directed_stake| directed_stake.shares)
+            .unwrap_or(0))
+    }
+
+    /// Zeroes the shares of an existing account, returns the released shares
+    pub fn release_shares(
+        account: &AccountInfo,
+        state: &Pubkey,
+        validator_vote: &Pubkey,
+    ) -> Result<u64> {
+        let mut directed_stake = match Self::load(account, state, validator_vote)? {
+            Some(directed_stake) => directed_stake,
+            None => return Ok(0),
+        };
+        let released = directed_stake.shares;
+        directed_stake.shares = 0;
+        directed_stake.try_serialize(&mut &mut account.data.borrow_mut()[..])?;
+        Ok(released)
+    }
+
+    fn load(
+        account: &AccountInfo,
+        state: &Pubkey,
+        validator_vote: &Pubkey,
+    ) -> Result<Option<DirectedStake>> {
+        require_keys_eq!(
+            *account.key,
+            Self::find_address(state, validator_vote).0,
+            StaderLiquidStakingError::WrongDirectedStakeAccount
+        );
+        if account.data_is_empty() {
+            return Ok(None);
+        }
+        require_keys_eq!(
+            *account.owner,
+            crate::ID,
+            ErrorCode::AccountOwnedByWrongProgram
+        );
+        Ok(Some(DirectedStake::try_deserialize(
+            &mut &account.data.borrow()[..],
+        )?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::StaderLiquidStakingError,
+        state::{fee::Fee, State},
+    };
+
+    const SOL: u64 = 1_000_000_000;
+
+    fn state() -> State {
+        let mut state = State::for_tests();
+        state.validator_system.total_active_balance = 1000 * SOL;
+        state.max_directed_stake_share = Fee::from_basis_points(1000);
+        state
+    }
+
+    #[test]
+    fn test_directed_deposit_cap() {
+        let mut state = state();
+        assert_eq!(state.on_directed_deposit(60 * SOL).unwrap(), 60 * SOL);
+        assert_eq!(state.on_directed_deposit(40 * SOL).unwrap(), 40 * SOL);
+        assert_eq!(
+            state.on_directed_deposit(1).unwrap_err(),
+            StaderLiquidStakingError::DirectedStakeCapExceeded.into()
+        );
+    }
+
+    #[test]
+    fn test_unwind_directed_stake() {
+        let mut state = state();
+        let shares = state.on_directed_deposit(50 * SOL).unwrap();
+        // a quarter of the pool leaves, a quarter of the direction goes with it
+        assert_eq!(
+            state.unwind_directed_stake(250 * SOL, 1000 * SOL).unwrap(),
+            (50 * SOL) / 4
+        );
+        assert_eq!(state.total_directed_lamports, 50 * SOL - (50 * SOL) / 4);
+        assert_eq!(
+            state.directed_lamports(shares).unwrap(),
+            50 * SOL - (50 * SOL) / 4
+        );
+        // shares deposited after the unwind are worth their lamports (rounded down)
+        let new_shares = state.on_directed_deposit(10 * SOL).unwrap();
+        assert!(10 * SOL - state.directed_lamports(new_shares).unwrap() <= 1);
+        // never unwinds more than the directed total
+        state.unwind_directed_stake(2000 * SOL, 1000 * SOL).unwrap();
+        assert_eq!(state.total_directed_lamports, 0);
+        // the worthless shares can't dilute new directed deposits
+        assert_eq!(
+            state.on_directed_deposit(10 * SOL).unwrap_err(),
+            StaderLiquidStakingError::DirectedStakeIsUnwound.into()
+        );
+        state.on_directed_release(shares + new_shares, 1).unwrap();
+        assert_eq!(state.on_directed_deposit(10 * SOL).unwrap(), 10 * SOL);
+    }
+
+    #[test]
+    fn test_honored_directed_lamports() {
+        let mut state = state();
+        let shares_a = state.on_directed_deposit(30 * SOL).unwrap();
+        let shares_b = state.on_directed_deposit(20 * SOL).unwrap();
+        assert_eq!(state.honored_directed_lamports().unwrap(), 50 * SOL);
+
+        // validator a is zeroed, its directed stake goes back to the score pool
+        state.on_validator_score_change(10, 0, shares_a);
+        assert_eq!(state.honored_directed_lamports().unwrap(), 20 * SOL);
+        // score changes not crossing 0 keep the totals
+        state.on_validator_score_change(0, 0, shares_a);
+        state.on_validator_score_change(20, 30, shares_b);
+        assert_eq!(state.honored_directed_lamports().unwrap(), 20 * SOL);
+        state.on_validator_score_change(0, 5, shares_a);
+        assert_eq!(state.honored_directed_lamports().unwrap(), 50 * SOL);
+
+        // removing a zero score validator releases its shares from both totals
+        state.on_validator_score_change(5, 0, shares_a);
+        state.on_directed_release(shares_a, 0).unwrap();
+        assert_eq!(state.total_unhonored_directed_shares, 0);
+        assert_eq!(state.total_directed_lamports, 20 * SOL);
+        assert_eq!(state.honored_directed_lamports().unwrap(), 20 * SOL);
+        state.on_directed_release(shares_b, 30).unwrap();
+        assert_eq!(state.total_directed_shares, 0);
+        assert_eq!(state.total_directed_lamports, 0);
+    }
+}
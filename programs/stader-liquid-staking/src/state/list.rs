@@ -2,7 +2,7 @@ use std::io::Cursor;
 
 use anchor_lang::prelude::*;
 use borsh::BorshSchema;
-use std::convert::TryFrom;
+use std::convert::{TryFrom, TryInto};
 
 use crate::{error::StaderLiquidStakingError, require_lt};
 
@@ -11,24 +11,39 @@ pub struct List {
     pub account: Pubkey,
     pub item_size: u32,
     pub count: u32,
+    /// index + 1 of the last removed item, 0 when there is none, see tombstone
+    pub free_head: u32,
     // Unused
-    pub _reserved1: Pubkey,
-    pub _reserved2: u32,
+    pub _reserved1: [u8; 28],
+    /// removed items still counted in `count`, see tombstone
+    pub tombstones: u32,
 }
 
 impl List {
+    /// Index argument asking to find the item by its pubkey instead, see find
+    pub const FIND_BY_KEY: u32 = std::u32::MAX;
+    /// removed items hold the free list link (index + 1 of the next removed item) after the zeroed key
+    const FREE_LINK: std::ops::Range<usize> = 32..36;
+    pub const MIN_ITEM_SIZE: u32 = Self::FREE_LINK.end as u32;
+
     pub fn new(
         discriminator: &[u8; 8],
         item_size: u32,
         account: Pubkey,
         data: &mut [u8],
     ) -> Result<Self> {
+        require_gte!(
+            item_size,
+            Self::MIN_ITEM_SIZE,
+            StaderLiquidStakingError::CalculationFailure
+        );
         let result = Self {
             account,
             item_size,
             count: 0,
-            _reserved1: Pubkey::default(),
-            _reserved2: 0,
+            free_head: 0,
+            _reserved1: [0; 28],
+            tombstones: 0,
         };
         result.init_account(discriminator, data)?;
         Ok(result)
@@ -81,8 +96,12 @@ impl List {
         .unwrap_or(std::u32::MAX)) // for zst element (why you are using it in list?)
     }
 
+    /// Fails on removed items, a zeroed item would decode with a default key
     pub fn get<I: AnchorDeserialize>(&self, data: &[u8], index: u32) -> Result<I> {
-        require_lt!(index, self.len(), StaderLiquidStakingError::ListIndexOutOfBounds);
+        require!(
+            !self.is_tombstone(data, index)?,
+            StaderLiquidStakingError::ListItemIsRemoved
+        );
 
         let start = 8 + (index * self.item_size()) as usize;
         I::deserialize(&mut &data[start..(start + self.item_size() as usize)]).map_err(|err| {
@@ -91,7 +110,10 @@ impl List {
     }
 
     pub fn set<I: AnchorSerialize>(&self, data: &mut [u8], index: u32, item: I) -> Result<()> {
-        require_lt!(index, self.len(), StaderLiquidStakingError::ListIndexOutOfBounds);
+        require!(
+            !self.is_tombstone(data, index)?,
+            StaderLiquidStakingError::ListItemIsRemoved
+        );
 
         let start = 8 + (index * self.item_size()) as usize;
         let mut cursor = Cursor::new(&mut data[start..(start + self.item_size() as usize)]);
@@ -100,73 +122,179 @@ impl List {
         Ok(())
     }
 
-    pub fn push<I: AnchorSerialize>(&mut self, data: &mut [u8], item: I) -> Result<()> {
-        let capacity = self.capacity(data.len())?;
-        require_lt!(self.len(), capacity, StaderLiquidStakingError::ListOverflow);
+    /// Writes the item in the last removed slot, or appends it. Returns its index
+    pub fn push<I: AnchorSerialize>(&mut self, data: &mut [u8], item: I) -> Result<u32> {
+        let index = if self.free_head != 0 {
+            let index = self.free_head - 1;
+            self.free_head = self.free_link(data, index);
+            self.tombstones -= 1;
+            index
+        } else {
+            let capacity = self.capacity(data.len())?;
+            require_lt!(self.len(), capacity, StaderLiquidStakingError::ListOverflow);
+            self.count += 1;
+            self.count - 1
+        };
 
-        let start = 8 + (self.len() * self.item_size()) as usize;
-        let mut cursor = Cursor::new(&mut data[start..(start + self.item_size() as usize)]);
+        let range = self.item_range(index);
+        data[range.clone()].fill(0); // clears the free list link
+        let mut cursor = Cursor::new(&mut data[range]);
         item.serialize(&mut cursor)?;
 
-        self.count += 1;
+        Ok(index)
+    }
 
-        Ok(())
+    fn free_link(&self, data: &[u8], index: u32) -> u32 {
+        let start = self.item_range(index).start;
+        u32::from_le_bytes(
+            data[start + Self::FREE_LINK.start..start + Self::FREE_LINK.end]
+                .try_into()
+                .unwrap(),
+        )
+    }
+
+    fn item_range(&self, index: u32) -> std::ops::Range<usize> {
+        let start = 8 + (index * self.item_size()) as usize;
+        start..(start + self.item_size() as usize)
     }
 
-    pub fn remove(&mut self, data: &mut [u8], index: u32) -> Result<()> {
+    /// Removed items have a zeroed key, no live item has one.
+    /// Items start with a pubkey, see find
+    pub fn is_tombstone(&self, data: &[u8], index: u32) -> Result<bool> {
         require_lt!(index, self.len(), StaderLiquidStakingError::ListIndexOutOfBounds);
+        Ok(data[self.item_range(index)][..32].iter().all(|byte| *byte == 0))
+    }
 
-        self.count -= 1;
-        if index == self.count {
-            return Ok(());
-        }
-        let start = 8 + (index * self.item_size()) as usize;
-        let last_item_start = 8 + (self.count * self.item_size()) as usize;
-        data.copy_within(
-            last_item_start..last_item_start + self.item_size() as usize,
-            start,
+    /// Removes the item keeping the indices of all other items.
+    /// The last item is dropped, the others stay as tombstones until compact
+    /// and are chained in the free list reused by push
+    pub fn tombstone(&mut self, data: &mut [u8], index: u32) -> Result<()> {
+        require!(
+            !self.is_tombstone(data, index)?,
+            StaderLiquidStakingError::ListItemIsRemoved
         );
+        let range = self.item_range(index);
+        data[range.clone()].fill(0);
+        if index == self.count - 1 {
+            self.count -= 1;
+        } else {
+            data[range.start + Self::FREE_LINK.start..range.start + Self::FREE_LINK.end]
+                .copy_from_slice(&self.free_head.to_le_bytes());
+            self.free_head = index + 1;
+            self.tombstones += 1;
+        }
+        Ok(())
+    }
 
+    /// Index of the item starting with `key`, for items whose first field is a pubkey.
+    /// Scans the whole list: meant for instructions handling one item of a small list,
+    /// batches over the stake list take explicit indices
+    pub fn find(&self, data: &[u8], key: &Pubkey) -> Option<u32> {
+        if *key == Pubkey::default() {
+            return None; // tombstone
+        }
+        (0..self.len()).find(|index| data[self.item_range(*index)][..32] == key.to_bytes())
+    }
+
+    /// Drops every tombstone keeping the order of the items. Changes indices
+    pub fn compact(&mut self, data: &mut [u8]) -> Result<()> {
+        let mut live = 0;
+        for index in 0..self.len() {
+            if self.is_tombstone(data, index)? {
+                continue;
+            }
+            if live != index {
+                let range = self.item_range(index);
+                data.copy_within(range, self.item_range(live).start);
+            }
+            live += 1;
+        }
+        self.count = live;
+        self.tombstones = 0;
+        self.free_head = 0;
         Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::BTreeSet;
-
     use anchor_lang::prelude::*;
 
     use super::List;
 
+    const ITEM_SIZE: u32 = List::MIN_ITEM_SIZE;
+
     #[test]
-    fn test_remove() -> Result<()> {
-        const COUNT: usize = 10;
-        for remove_index in 0..COUNT {
-            let mut list_data = [0; COUNT + 8];
-            let list_account = Pubkey::new_unique();
-            let discriminator = &[1, 2, 3, 4, 5, 6, 7, 8];
-            let mut list = List::new(discriminator, 1u32, list_account, &mut list_data)?;
-            for i in 0..COUNT {
-                list.push::<u8>(&mut list_data, 9 + i as u8)?;
-            }
-            assert_eq!(list.len(), COUNT as u32);
-            for i in 0..COUNT {
-                assert_eq!(list.get::<u8>(&list_data, i as u32)?, 9 + i as u8);
-            }
+    fn test_tombstone_and_compact() -> Result<()> {
+        const COUNT: u32 = 5;
+        let mut list_data = [0; 8 + (ITEM_SIZE * COUNT) as usize];
+        let mut list = List::new(&[1; 8], ITEM_SIZE, Pubkey::new_unique(), &mut list_data)?;
+        let keys: Vec<Pubkey> = (0..COUNT).map(|_| Pubkey::new_unique()).collect();
+        for key in &keys {
+            list.push(&mut list_data, key)?;
+        }
+
+        list.tombstone(&mut list_data, 1)?;
+        assert!(list.tombstone(&mut list_data, 1).is_err());
+        assert_eq!(list.len(), COUNT);
+        assert_eq!(list.tombstones, 1);
+        assert_eq!(list.get::<Pubkey>(&list_data, 2)?, keys[2]);
+        assert_eq!(list.find(&list_data, &keys[3]), Some(3));
+        assert_eq!(list.find(&list_data, &keys[1]), None);
+        assert_eq!(list.find(&list_data, &Pubkey::default()), None);
+        assert!(list.get::<Pubkey>(&list_data, 1).is_err());
+        assert!(list.set(&mut list_data, 1, keys[1]).is_err());
+
+        // the last item is dropped
+        list.tombstone(&mut list_data, 3)?;
+        list.tombstone(&mut list_data, 4)?;
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.tombstones, 2);
+
+        list.compact(&mut list_data)?;
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.tombstones, 0);
+        assert_eq!(list.get::<Pubkey>(&list_data, 0)?, keys[0]);
+        assert_eq!(list.get::<Pubkey>(&list_data, 1)?, keys[2]);
+        // the free list is emptied
+        assert_eq!(list.push(&mut list_data, keys[4])?, 2);
+        Ok(())
+    }
 
-            list.remove(&mut list_data, remove_index as u32)?;
-            assert_eq!(list.len(), COUNT as u32 - 1);
-            let expected_set: BTreeSet<u8> = (0..COUNT)
-                .filter(|i| *i as usize != remove_index)
-                .map(|x| (x + 9) as u8)
-                .collect();
-            let result_set = (0..list.len())
-                .map(|i| list.get::<u8>(&list_data, i as u32))
-                .collect::<Result<BTreeSet<u8>>>()?;
-
-            assert_eq!(expected_set, result_set);
+    #[test]
+    fn test_push_reuses_tombstones() -> Result<()> {
+        const COUNT: u32 = 4;
+        let mut list_data = [0; 8 + (ITEM_SIZE * COUNT) as usize];
+        let mut list = List::new(&[1; 8], ITEM_SIZE, Pubkey::new_unique(), &mut list_data)?;
+        let keys: Vec<Pubkey> = (0..COUNT).map(|_| Pubkey::new_unique()).collect();
+        for (index, key) in keys.iter().enumerate() {
+            assert_eq!(list.push(&mut list_data, key)?, index as u32);
         }
+        assert!(list.push(&mut list_data, Pubkey::new_unique()).is_err());
+
+        list.tombstone(&mut list_data, 2)?;
+        list.tombstone(&mut list_data, 0)?;
+        let new_keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        assert_eq!(list.push(&mut list_data, new_keys[0])?, 0);
+        assert_eq!(list.push(&mut list_data, new_keys[1])?, 2);
+        assert_eq!(list.len(), COUNT);
+        assert_eq!(list.tombstones, 0);
+        assert_eq!(list.get::<Pubkey>(&list_data, 0)?, new_keys[0]);
+        assert_eq!(list.get::<Pubkey>(&list_data, 1)?, keys[1]);
+        assert_eq!(list.get::<Pubkey>(&list_data, 2)?, new_keys[1]);
+        assert_eq!(list.get::<Pubkey>(&list_data, 3)?, keys[3]);
+        assert!(list.push(&mut list_data, Pubkey::new_unique()).is_err());
+
+        // the last removed slot is reused first, a dropped last item is appended again
+        list.tombstone(&mut list_data, 1)?;
+        list.tombstone(&mut list_data, 2)?;
+        list.tombstone(&mut list_data, 3)?;
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.push(&mut list_data, keys[2])?, 2);
+        assert_eq!(list.push(&mut list_data, keys[1])?, 1);
+        assert_eq!(list.push(&mut list_data, keys[3])?, 3);
+        assert_eq!(list.tombstones, 0);
+        assert_eq!(list.get::<Pubkey>(&list_data, 1)?, keys[1]);
         Ok(())
     }
 }
@@ -0,0 +1,434 @@
+use anchor_lang::{prelude::*, Discriminator};
+use std::mem::MaybeUninit;
+
+use crate::{
+    error::StaderLiquidStakingError,
+    state::{
+        circuit_breaker::CircuitBreaker, liq_pool::LiqPool, list::List, stake_system::StakeSystem,
+        validator_system::{ValidatorScoringConfig, ValidatorSystem},
+        Fee, FeeCents,
+    },
+    State,
+};
+
+// Copies of the structs embedded in StateV0 as they were deployed.
+// The live types may change, these must not
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct ListV0 {
+    pub account: Pubkey,
+    pub item_size: u32,
+    pub count: u32,
+    pub _reserved1: Pubkey,
+    pub _reserved2: u32,
+}
+
+impl From<ListV0> for List {
+    fn from(old: ListV0) -> Self {
+        Self {
+            account: old.account,
+            item_size: old.item_size,
+            count: old.count,
+            free_head: 0,
+            _reserved1: [0; 28],
+            tombstones: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct StakeSystemV0 {
+    pub stake_list: ListV0,
+    pub delayed_unstake_cooling_down: u64,
+    pub stake_deposit_bump_seed: u8,
+    pub stake_withdraw_bump_seed: u8,
+    pub slots_for_stake_delta: u64,
+    pub last_stake_delta_epoch: u64,
+    pub min_stake: u64,
+    pub extra_stake_delta_runs: u32,
+}
+
+impl From<StakeSystemV0> for StakeSystem {
+    fn from(old: StakeSystemV0) -> Self {
+        Self {
+            stake_list: old.stake_list.into(),
+            delayed_unstake_cooling_down: old.delayed_unstake_cooling_down,
+            stake_deposit_bump_seed: old.stake_deposit_bump_seed,
+            stake_withdraw_bump_seed: old.stake_withdraw_bump_seed,
+            slots_for_stake_delta: old.slots_for_stake_delta,
+            last_stake_delta_epoch: old.last_stake_delta_epoch,
+            min_stake: old.min_stake,
+            extra_stake_delta_runs: old.extra_stake_delta_runs,
+        }
+    }
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct ValidatorSystemV0 {
+    pub validator_list: ListV0,
+    pub manager_authority: Pubkey,
+    pub total_validator_score: u32,
+    pub total_active_balance: u64,
+    pub auto_add_validator_enabled: u8,
+}
+
+impl From<ValidatorSystemV0> for ValidatorSystem {
+    fn from(old: ValidatorSystemV0) -> Self {
+        Self {
+            validator_list: old.validator_list.into(),
+            manager_authority: old.manager_authority,
+            total_validator_score: old.total_validator_score,
+            total_active_balance: old.total_active_balance,
+            auto_add_validator_enabled: old.auto_add_validator_enabled,
+        }
+    }
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct LiqPoolV0 {
+    pub lp_mint: Pubkey,
+    pub lp_mint_authority_bump_seed: u8,
+    pub sol_leg_bump_seed: u8,
+    pub stader_sol_leg_authority_bump_seed: u8,
+    pub stader_sol_leg: Pubkey,
+    pub lp_liquidity_target: u64,
+    pub lp_max_fee: Fee,
+    pub lp_min_fee: Fee,
+    pub treasury_cut: Fee,
+    pub lp_supply: u64,
+    pub lent_from_sol_leg: u64,
+    pub liquidity_sol_cap: u64,
+}
+
+impl From<LiqPoolV0> for LiqPool {
+    fn from(old: LiqPoolV0) -> Self {
+        Self {
+            lp_mint: old.lp_mint,
+            lp_mint_authority_bump_seed: old.lp_mint_authority_bump_seed,
+            sol_leg_bump_seed: old.sol_leg_bump_seed,
+            stader_sol_leg_authority_bump_seed: old.stader_sol_leg_authority_bump_seed,
+            stader_sol_leg: old.stader_sol_leg,
+            lp_liquidity_target: old.lp_liquidity_target,
+            lp_max_fee: old.lp_max_fee,
+            lp_min_fee: old.lp_min_fee,
+            treasury_cut: old.treasury_cut,
+            lp_supply: old.lp_supply,
+            lent_from_sol_leg: old.lent_from_sol_leg,
+            liquidity_sol_cap: old.liquidity_sol_cap,
+        }
+    }
+}
+
+/// State layout deployed before versioning was introduced (version 0).
+/// Must never be changed, it is used to decode old accounts in migrate_state
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct StateV0 {
+    pub stader_sol_mint: Pubkey,
+    pub admin_authority: Pubkey,
+    pub operational_sol_account: Pubkey,
+    pub treasury_stader_sol_account: Pubkey,
+    pub reserve_bump_seed: u8,
+    pub stader_sol_mint_authority_bump_seed: u8,
+    pub rent_exempt_for_token_acc: u64,
+    pub reward_fee: Fee,
+    pub stake_system: StakeSystemV0,
+    pub validator_system: ValidatorSystemV0,
+    pub liq_pool: LiqPoolV0,
+    pub available_reserve_balance: u64,
+    pub stader_sol_supply: u64,
+    pub stader_sol_price: u64,
+    pub circulating_ticket_count: u64,
+    pub circulating_ticket_balance: u64,
+    pub lent_from_reserve: u64,
+    pub min_deposit: u64,
+    pub min_withdraw: u64,
+    pub staking_sol_cap: u64,
+    pub emergency_cooling_down: u64,
+    pub pause_authority: Pubkey,
+    pub paused: bool,
+    pub delayed_unstake_fee: FeeCents,
+    pub withdraw_stake_account_fee: FeeCents,
+    pub withdraw_stake_account_enabled: bool,
+    pub last_stake_move_epoch: u64,
+    pub stake_moved: u64,
+    pub max_stake_moved_per_epoch: Fee,
+}
+
+impl StateV0 {
+    pub fn serialized_len() -> usize {
+        unsafe { MaybeUninit::<Self>::zeroed().assume_init() }
+            .try_to_vec()
+            .unwrap()
+            .len()
+            + 8
+    }
+}
+
+impl From<StateV0> for State {
+    fn from(old: StateV0) -> Self {
+        Self {
+            stader_sol_mint: old.stader_sol_mint,
+            admin_authority: old.admin_authority,
+            operational_sol_account: old.operational_sol_account,
+            treasury_stader_sol_account: old.treasury_stader_sol_account,
+            reserve_bump_seed: old.reserve_bump_seed,
+            stader_sol_mint_authority_bump_seed: old.stader_sol_mint_authority_bump_seed,
+            rent_exempt_for_token_acc: old.rent_exempt_for_token_acc,
+            reward_fee: old.reward_fee,
+            stake_system: old.stake_system.into(),
+            validator_system: old.validator_system.into(),
+            liq_pool: old.liq_pool.into(),
+            available_reserve_balance: old.available_reserve_balance,
+            stader_sol_supply: old.stader_sol_supply,
+            stader_sol_price: old.stader_sol_price,
+            circulating_ticket_count: old.circulating_ticket_count,
+            circulating_ticket_balance: old.circulating_ticket_balance,
+            lent_from_reserve: old.lent_from_reserve,
+            min_deposit: old.min_deposit,
+            min_withdraw: old.min_withdraw,
+            staking_sol_cap: old.staking_sol_cap,
+            emergency_cooling_down: old.emergency_cooling_down,
+            pause_authority: old.pause_authority,
+            // the single flag paused everything
+            paused_groups: if old.paused { State::PAUSE_ALL } else { 0 },
+            delayed_unstake_fee: old.delayed_unstake_fee,
+            withdraw_stake_account_fee: old.withdraw_stake_account_fee,
+            withdraw_stake_account_enabled: old.withdraw_stake_account_enabled,
+            last_stake_move_epoch: old.last_stake_move_epoch,
+            stake_moved: old.stake_moved,
+            max_stake_moved_per_epoch: old.max_stake_moved_per_epoch,
+            pending_admin_authority: Pubkey::default(),
+            pending_validator_manager_authority: Pubkey::default(),
+            pending_pause_authority: Pubkey::default(),
+            config_delay_epochs: State::DEFAULT_CONFIG_DELAY_EPOCHS,
+            fee_authority: Pubkey::default(),
+            limits_authority: Pubkey::default(),
+            metadata_authority: Pubkey::default(),
+            lp_config_authority: Pubkey::default(),
+            circuit_breaker: CircuitBreaker::default(),
+            version: State::CURRENT_VERSION,
+            ticket_wait_epochs: State::DEFAULT_TICKET_WAIT_EPOCHS,
+            ticket_extra_wait_seconds: State::DEFAULT_TICKET_EXTRA_WAIT_SECONDS,
+            wind_down: false,
+            wind_down_epoch: 0,
+            reserve_surplus_policy: State::RESERVE_SURPLUS_DONATE,
+            validator_scoring: ValidatorScoringConfig::default(),
+            max_validator_stake_share: Fee::from_basis_points(Fee::MAX_BASIS_POINTS), // no cap
+            max_validator_commission: 100, // no guard
+            total_directed_shares: 0,
+            total_directed_lamports: 0,
+            delinquent_unstake_epochs: 0, // disabled
+            stake_update_epoch: 0,
+            updated_stake_count: 0,
+            stale_price_grace_seconds: 0, // no stale price protection
+            stale_price_fee: FeeCents::from_bp_cents(0),
+            crank_reward_lamports: 0, // disabled
+            max_crank_rewards_per_epoch: 0,
+            crank_reward_epoch: 0,
+            crank_rewards_paid_in_epoch: 0,
+            total_crank_rewards_paid: 0,
+            rebalance_tolerance: Fee::from_basis_points(0), // any gap over min_stake
+            slashing_threshold: State::DEFAULT_SLASHING_THRESHOLD,
+            legacy_ticket_wait_epochs: 0,
+            legacy_ticket_wait_until_epoch: 0,
+            config_history: Pubkey::default(),
+            total_unhonored_directed_shares: 0,
+            max_directed_stake_share: State::DEFAULT_MAX_DIRECTED_STAKE_SHARE,
+            deactivating_stake_count: 0,
+            reserved: [0; 317],
+        }
+    }
+}
+
+/// Initializes the fields carved out of `reserved` by the versions after state.version
+fn upgrade(state: &mut State) {
+    if state.version < 2 {
+        state.ticket_wait_epochs = State::DEFAULT_TICKET_WAIT_EPOCHS;
+        state.ticket_extra_wait_seconds = State::DEFAULT_TICKET_EXTRA_WAIT_SECONDS;
+    }
+    if state.version < 6 {
+        state.max_validator_stake_share = Fee::from_basis_points(Fee::MAX_BASIS_POINTS);
+    }
+    if state.version < 7 {
+        state.max_validator_commission = 100;
+    }
+    if state.version < 13 {
+        state.slashing_threshold = State::DEFAULT_SLASHING_THRESHOLD;
+    }
+    if state.version < 17 {
+        state.max_directed_stake_share = State::DEFAULT_MAX_DIRECTED_STAKE_SHARE;
+    }
+    // version 3 to 5, 8 to 12, 14 to 16 and 18 fields are valid when zeroed (not winding down, donate the reserve surplus,
+    // on-chain scoring disabled, nothing directed, no permissionless delinquent unstake,
+    // stake updates counted from the next epoch, no stale price protection, no crank rewards,
+    // rebalance any gap over min_stake, ticket wait never lowered, no config history,
+    // zero score validators counted from their next score change,
+    // stakes already cooling down counted as live until update_deactivated removes them)
+    state.version = State::CURRENT_VERSION;
+}
+
+/// Offset of State::version in the account data.
+/// The version 1 fields follow the version 0 layout, then comes the version byte
+fn version_offset() -> usize {
+    StateV0::serialized_len()
+        + 3 * 32 // pending authorities
+        + 8 // config_delay_epochs
+        + 4 * 32 // fee, limits, metadata and lp config authorities
+        + CircuitBreaker::default().try_to_vec().unwrap().len()
+}
+
+/// Decodes the state account data of any known layout and converts it to the current one.
+/// Accounts can be allocated larger than the layout, so the layout is told by the version byte:
+/// in a version 0 account it is past the data or in the zeroed spare space.
+/// Returns the version found in the data
+pub fn decode_any_version(data: &[u8]) -> Result<(u8, State)> {
+    require!(
+        data.len() >= 8 && data[..8] == State::DISCRIMINATOR,
+        StaderLiquidStakingError::UnknownStateLayout
+    );
+    let mut body = &data[8..];
+    let version = data.get(version_offset()).copied().unwrap_or(0);
+    if version == 0 {
+        require_gte!(
+            data.len(),
+            StateV0::serialized_len(),
+            StaderLiquidStakingError::UnknownStateLayout
+        );
+        Ok((0, StateV0::deserialize(&mut body)?.into()))
+    } else {
+        require!(
+            version <= State::CURRENT_VERSION && data.len() >= State::serialized_len(),
+            StaderLiquidStakingError::UnknownStateLayout
+        );
+        let mut state = State::deserialize(&mut body)?;
+        upgrade(&mut state);
+        Ok((version, state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{prelude::*, Discriminator};
+
+    use super::{decode_any_version, version_offset, StateV0};
+    use crate::{state::Fee, State};
+
+    /// state account data in the version 0 layout, as written by the pre-versioning program
+    const STATE_V0_FIXTURE: &[u8] = include_bytes!("fixtures/state_v0.bin");
+
+    #[test]
+    fn test_decode_v0_fixture() -> Result<()> {
+        assert_eq!(STATE_V0_FIXTURE.len(), StateV0::serialized_len());
+        let old = StateV0::deserialize(&mut &STATE_V0_FIXTURE[8..])?;
+
+        let (version, state) = decode_any_version(STATE_V0_FIXTURE)?;
+        assert_eq!(version, 0);
+        assert_eq!(state.version, State::CURRENT_VERSION);
+        assert_eq!(state.admin_authority, old.admin_authority);
+        assert_eq!(state.stader_sol_mint, old.stader_sol_mint);
+        assert_eq!(state.pause_authority, old.pause_authority);
+        assert_eq!(
+            state.validator_system.total_active_balance,
+            old.validator_system.total_active_balance
+        );
+        assert_eq!(state.stake_system.min_stake, old.stake_system.min_stake);
+        assert_eq!(state.liq_pool.lp_mint, old.liq_pool.lp_mint);
+        assert_eq!(state.stader_sol_supply, old.stader_sol_supply);
+        assert_eq!(
+            state.max_stake_moved_per_epoch,
+            old.max_stake_moved_per_epoch
+        );
+        assert_eq!(
+            state.paused_groups,
+            if old.paused { State::PAUSE_ALL } else { 0 }
+        );
+        assert_eq!(
+            state.config_delay_epochs,
+            State::DEFAULT_CONFIG_DELAY_EPOCHS
+        );
+        assert!(!state.circuit_breaker.is_tripped());
+        assert!(!state.has_pending_authority_change());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reencode_current() -> Result<()> {
+        let (_, state) = decode_any_version(STATE_V0_FIXTURE)?;
+        let mut data = State::DISCRIMINATOR.to_vec();
+        state.serialize(&mut data)?;
+        assert_eq!(data.len(), State::serialized_len());
+
+        assert_eq!(data[version_offset()], State::CURRENT_VERSION);
+
+        let (version, decoded) = decode_any_version(&data)?;
+        assert_eq!(version, State::CURRENT_VERSION);
+        assert_eq!(decoded.try_to_vec()?, state.try_to_vec()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_larger_accounts() -> Result<()> {
+        let mut data = STATE_V0_FIXTURE.to_vec();
+        data.resize(State::serialized_len() + 100, 0);
+        let (version, from_v0) = decode_any_version(&data)?;
+        assert_eq!(version, 0);
+
+        let mut data = State::DISCRIMINATOR.to_vec();
+        from_v0.serialize(&mut data)?;
+        data.resize(data.len() + 100, 0);
+        let (version, decoded) = decode_any_version(&data)?;
+        assert_eq!(version, State::CURRENT_VERSION);
+        assert_eq!(decoded.try_to_vec()?, from_v0.try_to_vec()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_upgrade_v1() -> Result<()> {
+        let (_, mut state) = decode_any_version(STATE_V0_FIXTURE)?;
+        // version 1 had zeroed reserved space where the version 2 fields are
+        state.version = 1;
+        state.ticket_wait_epochs = 0;
+        state.ticket_extra_wait_seconds = 0;
+        let mut data = State::DISCRIMINATOR.to_vec();
+        state.serialize(&mut data)?;
+
+        let (version, upgraded) = decode_any_version(&data)?;
+        assert_eq!(version, 1);
+        assert_eq!(upgraded.version, State::CURRENT_VERSION);
+        assert_eq!(
+            upgraded.ticket_wait_epochs,
+            State::DEFAULT_TICKET_WAIT_EPOCHS
+        );
+        assert_eq!(
+            upgraded.ticket_extra_wait_seconds,
+            State::DEFAULT_TICKET_EXTRA_WAIT_SECONDS
+        );
+        assert_eq!(upgraded.admin_authority, state.admin_authority);
+        Ok(())
+    }
+
+    #[test]
+    fn test_upgrade_v5() -> Result<()> {
+        let (_, mut state) = decode_any_version(STATE_V0_FIXTURE)?;
+        state.version = 5;
+        state.max_validator_stake_share = Fee::from_basis_points(0);
+        let mut data = State::DISCRIMINATOR.to_vec();
+        state.serialize(&mut data)?;
+
+        let (version, upgraded) = decode_any_version(&data)?;
+        assert_eq!(version, 5);
+        assert_eq!(
+            upgraded.max_validator_stake_share,
+            Fee::from_basis_points(Fee::MAX_BASIS_POINTS)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_layout() {
+        assert!(decode_any_version(&STATE_V0_FIXTURE[..100]).is_err());
+        let mut wrong_discriminator = STATE_V0_FIXTURE.to_vec();
+        wrong_discriminator[0] ^= 1;
+        assert!(decode_any_version(&wrong_discriminator).is_err());
+    }
+}
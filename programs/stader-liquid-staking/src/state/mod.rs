@@ -1,6 +1,7 @@
 use crate::{
-    calc::{shares_from_value, value_from_shares},
+    calc::{proportional, shares_from_value, value_from_shares},
     error::StaderLiquidStakingError,
+    events::crank::CircuitBreakerTrippedEvent,
     require_lte, ID,
 };
 use anchor_lang::{
@@ -9,14 +10,24 @@ use anchor_lang::{
 use anchor_spl::token::spl_token;
 use std::mem::MaybeUninit;
 
-use self::{liq_pool::LiqPool, stake_system::StakeSystem, validator_system::ValidatorSystem};
+use self::{
+    circuit_breaker::CircuitBreaker, liq_pool::LiqPool, stake_system::{StakeRecord, StakeSystem}, validator_system::{ValidatorRecord, ValidatorScoringConfig, ValidatorSystem}, vote_account::VoteAccountSummary};
 
+pub mod circuit_breaker;
+pub mod config_history;
+pub mod crank_reward_vault;
 pub mod delayed_unstake_ticket;
+pub mod directed_stake;
 pub mod fee;
 pub mod liq_pool;
 pub mod list;
+pub mod migration;
+pub mod pending_config;
 pub mod stake_system;
+pub mod validator_application;
+pub mod validator_blocklist;
 pub mod validator_system;
+pub mod vote_account;
 
 pub use fee::Fee;
 pub use fee::FeeCents;
@@ -52,7 +63,7 @@ pub struct State {
     // epoch_stake_orders: u64,
     // epoch_unstake_orders: u64,
     pub liq_pool: LiqPool,
-    pub available_reserve_balance: u64, // reserve_pda.lamports() - self.rent_exempt_for_token_acc. Virtual value (real may be > because of transfers into reserve). Use Update* to align
+    pub available_reserve_balance: u64, // reserve_pda.lamports() - self.rent_exempt_for_token_acc. Virtual value (real may be > because of transfers into reserve). Use SweepReserveSurplus to align
     pub stader_sol_supply: u64, // Virtual value (may be < because of token burn). Use Update* to align
     // For FE. Don't use it for token amount calculation
     pub stader_sol_price: u64,
@@ -70,7 +81,7 @@ pub struct State {
 
     /// emergency pause
     pub pause_authority: Pubkey,
-    pub paused: bool,
+    pub paused_groups: u8, // bitmask of State::PAUSE_* instruction groups
 
     // delayed unstake account fee
     // to avoid economic attacks this value should not be zero
@@ -91,10 +102,105 @@ pub struct State {
     pub last_stake_move_epoch: u64, // epoch of the last stake move action
     pub stake_moved: u64,           // total amount of moved SOL during the epoch #stake_move_epoch
     pub max_stake_moved_per_epoch: Fee, // % of total_lamports_under_control
+
+    // Two-step authority handover: change_authority only proposes the new key,
+    // the proposed key must sign accept_authority to take over.
+    // Pubkey::default() means no pending change
+    pub pending_admin_authority: Pubkey,
+    pub pending_validator_manager_authority: Pubkey,
+    pub pending_pause_authority: Pubkey,
+
+    // fee raises and cap increases wait this many epochs in the PendingConfig account
+    pub config_delay_epochs: u64,
+
+    // Optional roles narrower than admin_authority,
+    // Pubkey::default() means the role is unset and admin_authority acts for it
+    pub fee_authority: Pubkey,        // reward, delayed unstake and withdraw stake account fees
+    pub limits_authority: Pubkey,     // caps, minimums and stake move limits
+    pub metadata_authority: Pubkey,   // token metadata of staderSOL and LP mints
+    pub lp_config_authority: Pubkey,  // liquidity pool fee curve and treasury cut
+
+    pub circuit_breaker: CircuitBreaker,
+
+    // Layout version, see migrate_state.
+    // New fields go after `version` and are carved out of `reserved` (bumping the version),
+    // so the account size does not change on upgrades
+    pub version: u8,
+
+    // version 2: delayed unstake ticket waiting time
+    pub ticket_wait_epochs: u64, // ticket created on epoch N is due on epoch N + ticket_wait_epochs
+    pub ticket_extra_wait_seconds: u64, // from the due epoch start, the bot needs time to withdraw inactive stakes
+
+    // version 3: wind-down mode, see start_wind_down
+    pub wind_down: bool,
+    pub wind_down_epoch: u64, // epoch when the wind-down was started
+
+    // version 4: what sweep_reserve_surplus does with lamports sent to the reserve from outside
+    pub reserve_surplus_policy: u8, // State::RESERVE_SURPLUS_*
+
+    // version 5: formula of the permissionless rescore_validator, set by the validator manager
+    pub validator_scoring: ValidatorScoringConfig,
+
+    // version 6: no validator can hold more than this share of total_active_balance
+    pub max_validator_stake_share: Fee,
+
+    // version 7: validators with a higher commission (percent) get no new stake
+    pub max_validator_commission: u8,
+
+    // version 8: stake directed by depositors to a validator, see DirectedStake
+    pub total_directed_shares: u64,
+    pub total_directed_lamports: u64,
+
+    // version 9: validators without vote credits in this many completed epochs
+    // can be unstaked by anyone, see unstake_delinquent_validator. 0 = disabled
+    pub delinquent_unstake_epochs: u8,
+
+    // version 10: stake records updated in stake_update_epoch, see is_epoch_fully_updated
+    pub stake_update_epoch: u64,
+    pub updated_stake_count: u32,
+    // until the epoch is fully updated and for at most this time from the epoch start,
+    // every operation converting at the staderSOL price (deposit, deposit_stake_account, order_unstake,
+    // liquid_unstake, withdraw_stake_account) pays stale_price_fee, or is rejected if it is zero. 0 = disabled
+    pub stale_price_grace_seconds: u64,
+    pub stale_price_fee: FeeCents,
+
+    // version 11: lamports paid from the CrankRewardVault per permissionless crank operation, 0 = disabled
+    pub crank_reward_lamports: u64,
+    pub max_crank_rewards_per_epoch: u64,
+    pub crank_reward_epoch: u64, // epoch of crank_rewards_paid_in_epoch
+    pub crank_rewards_paid_in_epoch: u64,
+    pub total_crank_rewards_paid: u64,
+
+    // version 12: share of the stake target a validator can be off by before rebalance moves its stake
+    pub rebalance_tolerance: Fee,
+
+    // version 13: a stake account balance drop over this share of its last update trips the circuit breaker
+    pub slashing_threshold: Fee,
+
+    // version 14: tickets created before legacy_ticket_wait_until_epoch wait at least legacy_ticket_wait_epochs,
+    // the longest wait lowered since, so lowering ticket_wait_epochs does not shorten existing tickets
+    pub legacy_ticket_wait_epochs: u64,
+    pub legacy_ticket_wait_until_epoch: u64,
+
+    // version 15: set by init_config_history, config changes must pass this account from then on
+    pub config_history: Pubkey,
+
+    // version 16: DirectedStake shares of zero score validators, not honored by the stake targets
+    pub total_unhonored_directed_shares: u64,
+
+    // version 17: directed deposits are refused over this share of the total active balance
+    pub max_directed_stake_share: Fee,
+
+    // version 18: stake records cooling down, see is_epoch_fully_updated
+    pub deactivating_stake_count: u32,
+
+    pub reserved: [u8; 317],
 }
 
 impl State {
     pub const PRICE_DENOMINATOR: u64 = 0x1_0000_0000;
+    /// Layout version written by initialize and migrate_state
+    pub const CURRENT_VERSION: u8 = 18;
     /// Suffix for reserve account seed
     pub const RESERVE_SEED: &'static [u8] = b"reserve";
     pub const STADER_SOL_MINT_AUTHORITY_SEED: &'static [u8] = b"st_mint";
@@ -115,6 +221,48 @@ impl State {
     // min_stake minimum value is MIN_STAKE_MULTIPLIER * rent_exempt_for_token_acc
     pub const MIN_STAKE_LOWER_LIMIT: u64 = LAMPORTS_PER_SOL / 100;
 
+    // instruction groups that can be paused independently
+    pub const PAUSE_DEPOSITS: u8 = 1 << 0; // deposit, deposit_stake_account
+    pub const PAUSE_LIQUID_UNSTAKE: u8 = 1 << 1; // liquid_unstake, withdraw_stake_account
+    pub const PAUSE_LIQ_POOL: u8 = 1 << 2; // add_liquidity, remove_liquidity
+    pub const PAUSE_DELAYED_UNSTAKE: u8 = 1 << 3; // order_unstake, claim
+    pub const PAUSE_CRANKS: u8 = 1 << 4; // stake_reserve, update_*, merge_stakes, deactivate_stake
+    pub const PAUSE_STAKE_MOVES: u8 = 1 << 5; // redelegate, partial/emergency unstake, validator management
+    pub const PAUSE_ALL: u8 = (1 << 6) - 1;
+    /// every group with instructions taking list indices (user instructions included,
+    /// clients cache the indices too), compact_lists needs them paused
+    pub const PAUSE_LIST_COMPACTION: u8 = Self::PAUSE_DEPOSITS
+        | Self::PAUSE_LIQUID_UNSTAKE
+        | Self::PAUSE_DELAYED_UNSTAKE
+        | Self::PAUSE_CRANKS
+        | Self::PAUSE_STAKE_MOVES;
+
+    pub const DEFAULT_TICKET_WAIT_EPOCHS: u64 = 1;
+    // the stake of the unstake orders is deactivating until the next epoch
+    pub const MIN_TICKET_WAIT_EPOCHS: u64 = 1;
+    pub const MAX_TICKET_WAIT_EPOCHS: u64 = 3;
+    pub const DEFAULT_TICKET_EXTRA_WAIT_SECONDS: u64 = 30 * 60;
+    pub const MAX_TICKET_EXTRA_WAIT_SECONDS: u64 = 12 * 60 * 60;
+
+    // reserve surplus policies
+    pub const RESERVE_SURPLUS_DONATE: u8 = 0; // credited to staderSOL holders
+    pub const RESERVE_SURPLUS_TO_OPERATIONAL: u8 = 1; // sent to operational_sol_account
+
+    pub const DEFAULT_CONFIG_DELAY_EPOCHS: u64 = 1;
+    pub const MAX_CONFIG_DELAY_EPOCHS: u64 = 10;
+
+    /// vote accounts keep the credits of the last 64 epochs
+    pub const MAX_DELINQUENT_UNSTAKE_EPOCHS: u8 = 64;
+
+    pub const MAX_STALE_PRICE_GRACE_SECONDS: u64 = 12 * 60 * 60;
+    pub const MAX_STALE_PRICE_FEE: FeeCents = FeeCents::from_bp_cents(10_000); // 1% max fee
+
+    pub const MAX_CRANK_REWARD_LAMPORTS: u64 = LAMPORTS_PER_SOL / 100;
+
+    // ignores lamport rounding, slashing losses are much larger
+    pub const DEFAULT_SLASHING_THRESHOLD: Fee = Fee::from_basis_points(1); // 0.01%
+    pub const DEFAULT_MAX_DIRECTED_STAKE_SHARE: Fee = Fee::from_basis_points(1000); // 10%
+
     pub fn serialized_len() -> usize {
         unsafe { MaybeUninit::<Self>::zeroed().assume_init() }
             .try_to_vec()
@@ -123,6 +271,15 @@ impl State {
             + 8
     }
 
+    /// State with every field zeroed but the version, the unit tests set the fields they use
+    #[cfg(test)]
+    pub fn for_tests() -> Self {
+        let data = vec![0; Self::serialized_len() - 8];
+        let mut state = Self::deserialize(&mut data.as_slice()).unwrap();
+        state.version = Self::CURRENT_VERSION;
+        state
+    }
+
     pub fn find_stader_sol_mint_authority(state: &Pubkey) -> (Pubkey, u8) {
         Pubkey::find_program_address(
             &[&state.to_bytes()[..32], State::STADER_SOL_MINT_AUTHORITY_SEED],
@@ -227,6 +384,26 @@ impl State {
         )
     }
 
+    /// Lamports of a deposit paid to the LiqPool for the stader_sol_swapped part
+    /// of user_stader_sol_buy_order. The stale price fee is never swapped,
+    /// it goes to the reserve with the rest of the deposit
+    pub fn deposit_sol_swapped(
+        &self,
+        lamports: u64,
+        stale_price_fee: u64,
+        user_stader_sol_buy_order: u64,
+        stader_sol_swapped: u64,
+    ) -> Result<u64> {
+        if user_stader_sol_buy_order == stader_sol_swapped {
+            //we are fulfilling 100% the user order
+            Ok(lamports - stale_price_fee) //100% of the user deposit but the fee
+        } else {
+            // partially filled
+            // then it's the lamport value of the tokens we're selling
+            self.stader_sol_to_sol(stader_sol_swapped)
+        }
+    }
+
     // **i128**: when do staking/unstaking use real reserve balance instead of virtual field
     pub fn stake_delta(&self, reserve_balance: u64) -> i128 {
         // Never try to stake lamports from emergency_cooling_down
@@ -235,6 +412,9 @@ impl State {
         // then we can count part of emergency stakes as starting to cooling down delayed unstakes
         // preventing unstake duplication by recalculating stake-delta for negative values
 
+        // A reserve surplus is not counted until sweep_reserve_surplus handles it
+        let reserve_balance = reserve_balance.saturating_sub(self.reserve_surplus(reserve_balance));
+
         // OK. Lets get stake_delta without emergency first
         let raw = reserve_balance.saturating_sub(self.rent_exempt_for_token_acc) as i128
             + self.stake_system.delayed_unstake_cooling_down as i128
@@ -250,6 +430,381 @@ impl State {
         }
     }
 
+    /// deposits and new liquidity are refused forever once the wind-down started
+    pub fn check_not_winding_down(&self) -> Result<()> {
+        require!(
+            !self.wind_down,
+            StaderLiquidStakingError::ProtocolIsWindingDown
+        );
+        Ok(())
+    }
+
+    /// Most lamports a single validator may hold when the pool has `total_active_balance` staked
+    pub fn max_validator_stake(&self, total_active_balance: u64) -> u64 {
+        self.max_validator_stake_share.apply(total_active_balance)
+    }
+
+    /// Lamports to stake from the reserve into a validator `missing_stake` under its target:
+    /// at most `stake_delta`, all of it when the rest would be under min_stake,
+    /// never over the validator max share. Under min_stake means nothing to stake
+    pub fn reserve_stake_amount(
+        &self,
+        missing_stake: u64,
+        stake_delta: u64,
+        validator_active_balance: u64,
+    ) -> u64 {
+        let amount = missing_stake.min(stake_delta);
+        // if what's left in stake_delta after this operation is < min_stake, take all the remainder
+        let amount = if stake_delta - amount < self.stake_system.min_stake {
+            stake_delta
+        } else {
+            amount
+        };
+        // taking the remainder must not push the validator over its max share
+        amount.min(
+            self.max_validator_stake(self.validator_system.total_active_balance + amount)
+                .saturating_sub(validator_active_balance),
+        )
+    }
+
+    /// lamports value of DirectedStake shares
+    pub fn directed_lamports(&self, shares: u64) -> Result<u64> {
+        value_from_shares(
+            shares,
+            self.total_directed_lamports,
+            self.total_directed_shares,
+        )
+    }
+
+    /// Returns the DirectedStake shares for the lamports.
+    /// Directions are not tied to the depositor's staderSOL (unstakes only unwind them pro-rata),
+    /// so the total is capped at max_directed_stake_share of the active balance.
+    /// Refused while the directed stake is unwound to 0 with shares outstanding,
+    /// the worthless shares would take a cut of the deposit (shares_from_value falls back to 1:1)
+    pub fn on_directed_deposit(&mut self, lamports: u64) -> Result<u64> {
+        require!(
+            self.total_directed_lamports > 0 || self.total_directed_shares == 0,
+            StaderLiquidStakingError::DirectedStakeIsUnwound
+        );
+        let shares = shares_from_value(
+            lamports,
+            self.total_directed_lamports,
+            self.total_directed_shares,
+        )?;
+        self.total_directed_shares += shares;
+        self.total_directed_lamports += lamports;
+        require_lte!(
+            self.total_directed_lamports,
+            self.max_directed_stake_share
+                .apply(self.validator_system.total_active_balance),
+            StaderLiquidStakingError::DirectedStakeCapExceeded
+        );
+        Ok(shares)
+    }
+
+    /// the shares of a removed validator stop being directed
+    pub fn on_directed_release(&mut self, shares: u64, validator_score: u32) -> Result<()> {
+        // moves them out of total_unhonored_directed_shares
+        self.on_validator_score_change(validator_score, 1, shares);
+        let released = self.directed_lamports(shares)?;
+        self.total_directed_lamports -= released;
+        self.total_directed_shares -= shares;
+        Ok(())
+    }
+
+    /// Directed lamports of the validators with a score, the stake targets honor them
+    /// and spread the rest of the stake by score
+    pub fn honored_directed_lamports(&self) -> Result<u64> {
+        self.directed_lamports(self.total_directed_shares - self.total_unhonored_directed_shares)
+    }
+
+    /// A validator score crossing 0 moves its DirectedStake shares out of the honored stake or back
+    pub fn on_validator_score_change(&mut self, old_score: u32, new_score: u32, directed_shares: u64) {
+        if old_score != 0 && new_score == 0 {
+            self.total_unhonored_directed_shares += directed_shares;
+        } else if old_score == 0 && new_score != 0 {
+            // states upgraded from version < 16 did not count the zero score validators
+            self.total_unhonored_directed_shares = self
+                .total_unhonored_directed_shares
+                .saturating_sub(directed_shares);
+        }
+    }
+
+    /// `lamports` of `total_virtual_staked_lamports` leave the pool,
+    /// the directed stake of all validators shrinks by the same proportion
+    pub fn unwind_directed_stake(
+        &mut self,
+        lamports: u64,
+        total_virtual_staked_lamports: u64,
+    ) -> Result<u64> {
+        let unwound = proportional(
+            self.total_directed_lamports,
+            lamports,
+            total_virtual_staked_lamports,
+        )?
+        .min(self.total_directed_lamports);
+        self.total_directed_lamports -= unwound;
+        Ok(unwound)
+    }
+
+    /// directed lamports of the validator used by its stake target.
+    /// Zero score validators are drained, stake directed to them is not honored,
+    /// see total_unhonored_directed_shares
+    pub fn validator_directed_lamports(
+        &self,
+        validator: &ValidatorRecord,
+        directed_shares: u64,
+    ) -> Result<u64> {
+        if validator.score == 0 {
+            return Ok(0);
+        }
+        self.directed_lamports(directed_shares)
+    }
+
+    pub fn is_validator_commission_too_high(&self, commission: u8) -> bool {
+        commission > self.max_validator_commission
+    }
+
+    /// No vote credits in any of the last delinquent_unstake_epochs completed epochs
+    pub fn is_validator_delinquent(&self, vote: &VoteAccountSummary, epoch: u64) -> bool {
+        let epochs = self.delinquent_unstake_epochs as u64;
+        epochs > 0
+            && epoch >= epochs
+            && (1..=epochs).all(|back| vote.credits_in_epoch(epoch - back) == 0)
+    }
+
+    fn roll_stake_update_epoch(&mut self, epoch: u64) {
+        if self.stake_update_epoch != epoch {
+            // no record can be updated in the new epoch yet
+            self.stake_update_epoch = epoch;
+            self.updated_stake_count = 0;
+        }
+    }
+
+    /// New stake records are up to date
+    pub fn on_stake_record_added(&mut self, epoch: u64) {
+        self.roll_stake_update_epoch(epoch);
+        self.updated_stake_count += 1;
+    }
+
+    /// Call before setting last_update_epoch of the record to the current epoch
+    pub fn on_stake_record_updated(&mut self, record: &StakeRecord, epoch: u64) {
+        self.roll_stake_update_epoch(epoch);
+        if record.last_update_epoch != epoch {
+            self.updated_stake_count += 1;
+        }
+    }
+
+    pub fn on_stake_record_removed(&mut self, record: &StakeRecord, epoch: u64) {
+        self.roll_stake_update_epoch(epoch);
+        if record.last_update_epoch == epoch {
+            // saturating: records updated in the epoch of the migration to version 10 are not counted
+            self.updated_stake_count = self.updated_stake_count.saturating_sub(1);
+        }
+    }
+
+    /// Call when the stake account of the record starts cooling down.
+    /// Its lamports stay in the cooling down totals until update_deactivated withdraws them,
+    /// possibly several epochs later, so it no longer counts for is_epoch_fully_updated
+    pub fn on_stake_record_deactivating(&mut self, record: &StakeRecord, epoch: u64) {
+        self.on_stake_record_removed(record, epoch);
+        self.deactivating_stake_count += 1;
+    }
+
+    /// New records split off to cool down right away, see on_stake_record_deactivating
+    pub fn on_deactivating_stake_record_added(&mut self, epoch: u64) {
+        self.roll_stake_update_epoch(epoch);
+        self.deactivating_stake_count += 1;
+    }
+
+    /// update_deactivated removes the record of a stake account that finished cooling down
+    pub fn on_deactivated_stake_record_removed(&mut self, epoch: u64) {
+        self.roll_stake_update_epoch(epoch);
+        // saturating: records cooling down at the migration to version 18 are not counted
+        self.deactivating_stake_count = self.deactivating_stake_count.saturating_sub(1);
+    }
+
+    /// Every stake record was updated (or created) in this epoch, so the staderSOL price is current.
+    /// Records cooling down don't change the price until update_deactivated removes them
+    pub fn is_epoch_fully_updated(&self, epoch: u64) -> bool {
+        let live_stake_count = (self.stake_system.stake_count()
+            - self.stake_system.removed_stake_count())
+        .saturating_sub(self.deactivating_stake_count);
+        live_stake_count == 0
+            || (self.stake_update_epoch == epoch && self.updated_stake_count >= live_stake_count)
+    }
+
+    pub fn is_price_stale(&self, clock: &Clock) -> bool {
+        self.stale_price_grace_seconds > 0
+            && !self.is_epoch_fully_updated(clock.epoch)
+            && clock.unix_timestamp
+                < clock
+                    .epoch_start_timestamp
+                    .saturating_add(self.stale_price_grace_seconds as i64)
+    }
+
+    /// Fee taken from every user operation converting at the staderSOL price while the price is stale,
+    /// in the unit of `amount` (lamports or staderSOL). It is credited to staderSOL holders.
+    /// Fails if those operations are blocked instead
+    pub fn stale_price_fee(&self, clock: &Clock, amount: u64) -> Result<u64> {
+        if !self.is_price_stale(clock) {
+            return Ok(0);
+        }
+        require_gt!(
+            self.stale_price_fee.bp_cents,
+            0,
+            StaderLiquidStakingError::PriceIsStale
+        );
+        Ok(self.stale_price_fee.apply(amount))
+    }
+
+    /// Accounts the reward of one crank operation within the per-epoch cap
+    /// and the `available` vault lamports. Returns the lamports to pay
+    pub fn take_crank_reward(&mut self, epoch: u64, available: u64) -> u64 {
+        if self.crank_reward_epoch != epoch {
+            self.crank_reward_epoch = epoch;
+            self.crank_rewards_paid_in_epoch = 0;
+        }
+        let reward = self
+            .crank_reward_lamports
+            .min(
+                self.max_crank_rewards_per_epoch
+                    .saturating_sub(self.crank_rewards_paid_in_epoch),
+            )
+            .min(available);
+        self.crank_rewards_paid_in_epoch += reward;
+        self.total_crank_rewards_paid += reward;
+        reward
+    }
+
+    /// e.g.: with ticket_wait_epochs = 1, ticket created on epoch 14 is due on epoch 15
+    pub fn ticket_due_epoch(&self, created_epoch: u64) -> u64 {
+        let wait_epochs = if created_epoch < self.legacy_ticket_wait_until_epoch {
+            self.ticket_wait_epochs.max(self.legacy_ticket_wait_epochs)
+        } else {
+            self.ticket_wait_epochs
+        };
+        created_epoch + wait_epochs
+    }
+
+    /// Keeps the wait of the tickets created before the change when ticket_wait_epochs is lowered
+    pub fn set_ticket_wait_epochs(&mut self, ticket_wait_epochs: u64, epoch: u64) {
+        if ticket_wait_epochs < self.ticket_wait_epochs {
+            self.legacy_ticket_wait_epochs =
+                self.legacy_ticket_wait_epochs.max(self.ticket_wait_epochs);
+            self.legacy_ticket_wait_until_epoch = epoch;
+        }
+        self.ticket_wait_epochs = ticket_wait_epochs;
+    }
+
+    /// lamports in the reserve not accounted in available_reserve_balance
+    pub fn reserve_surplus(&self, reserve_balance: u64) -> u64 {
+        reserve_balance
+            .saturating_sub(self.rent_exempt_for_token_acc)
+            .saturating_sub(self.available_reserve_balance)
+    }
+
+    /// once init_config_history ran, every config change must be recorded in the history account
+    pub fn check_config_history(&self, config_history: Option<Pubkey>) -> Result<()> {
+        if self.config_history != Pubkey::default() {
+            require_keys_eq!(
+                config_history.unwrap_or_default(),
+                self.config_history,
+                StaderLiquidStakingError::MissingConfigHistory
+            );
+        }
+        Ok(())
+    }
+
+    pub fn check_not_paused(&self, group: u8) -> Result<()> {
+        require!(
+            self.paused_groups & group == 0,
+            StaderLiquidStakingError::ProgramIsPaused
+        );
+        Ok(())
+    }
+
+    /// stake account balance dropped by more than slashing_threshold since the last update
+    pub fn is_slashing_anomaly(&self, last_update_lamports: u64, observed_lamports: u64) -> bool {
+        last_update_lamports.saturating_sub(observed_lamports)
+            > self.slashing_threshold.apply(last_update_lamports)
+    }
+
+    /// Records the anomaly and pauses the groups related to it.
+    /// The first anomaly is kept until reset_circuit_breaker, later ones only add their pause groups.
+    /// Returns the event to be emitted by the caller
+    pub fn trip_circuit_breaker(
+        &mut self,
+        state_address: Pubkey,
+        reason: u8,
+        epoch: u64,
+        expected: u64,
+        observed: u64,
+    ) -> CircuitBreakerTrippedEvent {
+        let groups = CircuitBreaker::groups_for(reason);
+        // groups paused by the pause authority stay paused after reset_circuit_breaker
+        let newly_paused_groups = groups & !self.paused_groups;
+        if self.circuit_breaker.is_tripped() {
+            self.circuit_breaker.tripped_groups |= newly_paused_groups;
+        } else {
+            self.circuit_breaker = CircuitBreaker {
+                reason,
+                tripped_epoch: epoch,
+                expected,
+                observed,
+                tripped_groups: newly_paused_groups,
+            };
+        }
+        self.paused_groups |= groups;
+        CircuitBreakerTrippedEvent {
+            state: state_address,
+            reason,
+            epoch,
+            expected,
+            observed,
+            tripped_groups: groups,
+            paused_groups: self.paused_groups,
+        }
+    }
+
+    /// Clears the circuit breaker record and resumes the groups it paused.
+    /// Returns the cleared record
+    pub fn reset_circuit_breaker(&mut self) -> CircuitBreaker {
+        let circuit_breaker = std::mem::take(&mut self.circuit_breaker);
+        self.paused_groups &= !circuit_breaker.tripped_groups;
+        circuit_breaker
+    }
+
+    fn role_or_admin(&self, role: Pubkey) -> Pubkey {
+        if role == Pubkey::default() {
+            self.admin_authority
+        } else {
+            role
+        }
+    }
+
+    pub fn effective_fee_authority(&self) -> Pubkey {
+        self.role_or_admin(self.fee_authority)
+    }
+
+    pub fn effective_limits_authority(&self) -> Pubkey {
+        self.role_or_admin(self.limits_authority)
+    }
+
+    pub fn effective_metadata_authority(&self) -> Pubkey {
+        self.role_or_admin(self.metadata_authority)
+    }
+
+    pub fn effective_lp_config_authority(&self) -> Pubkey {
+        self.role_or_admin(self.lp_config_authority)
+    }
+
+    pub fn has_pending_authority_change(&self) -> bool {
+        self.pending_admin_authority != Pubkey::default()
+            || self.pending_validator_manager_authority != Pubkey::default()
+            || self.pending_pause_authority != Pubkey::default()
+    }
+
     pub fn on_transfer_to_reserve(&mut self, amount: u64) {
         self.available_reserve_balance += amount
     }
@@ -269,6 +824,57 @@ impl State {
         self.stader_sol_supply -= amount
     }
 
+    /// lamports that can still be moved in the epoch, see on_stake_moved
+    pub fn stake_move_remaining(&self, epoch: u64) -> u64 {
+        let moved = if epoch == self.last_stake_move_epoch {
+            self.stake_moved
+        } else {
+            0
+        };
+        self.max_stake_moved_per_epoch
+            .apply(self.total_lamports_under_control())
+            .saturating_sub(moved)
+    }
+
+    /// Gap to the stake target ignored by rebalance, at least min_stake
+    pub fn rebalance_threshold(&self, validator_stake_target: u64) -> u64 {
+        self.rebalance_tolerance
+            .apply(validator_stake_target)
+            .max(self.stake_system.min_stake)
+    }
+
+    /// Lamports rebalance moves out of a stake account with `delegated` lamports:
+    /// the smaller gap, capped by the stake moves left in the epoch and the dest validator max share.
+    /// The whole account is moved rather than leaving less than min_stake in it.
+    /// Returns (amount, move_whole_account), an amount under min_stake means nothing to move
+    pub fn rebalance_amount(
+        &self,
+        epoch: u64,
+        source_excess: u64,
+        dest_shortfall: u64,
+        dest_active_balance: u64,
+        delegated: u64,
+    ) -> (u64, bool) {
+        // hard limits, also for moving the whole account
+        let max_amount = self.stake_move_remaining(epoch).min(
+            self.max_validator_stake(self.validator_system.total_active_balance)
+                .saturating_sub(dest_active_balance),
+        );
+        let min_stake = self.stake_system.min_stake;
+        let amount = source_excess
+            .min(dest_shortfall)
+            .min(delegated)
+            .min(max_amount);
+        // do not leave less than min_stake in the account
+        if delegated - amount >= min_stake {
+            (amount, false)
+        } else if delegated <= max_amount {
+            (delegated, true)
+        } else {
+            (delegated.saturating_sub(min_stake), false)
+        }
+    }
+
     pub fn on_stake_moved(&mut self, amount: u64, clock: &Clock) -> Result<()> {
         if clock.epoch != self.last_stake_move_epoch {
             self.last_stake_move_epoch = clock.epoch;
@@ -284,3 +890,187 @@ impl State {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{circuit_breaker::CircuitBreaker, stake_system::StakeRecord, Fee, State};
+
+    fn state(live_stake_count: u32) -> State {
+        let mut state = State::for_tests();
+        state.stake_system.stake_list.count = live_stake_count;
+        state
+    }
+
+    fn record(last_update_epoch: u64) -> StakeRecord {
+        StakeRecord {
+            last_update_epoch,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_deactivating_stake_does_not_block_full_update() {
+        let mut state = state(3);
+        // one record deactivated last epoch, still cooling down
+        state.on_stake_record_deactivating(&record(9), 9);
+        state.on_stake_record_updated(&record(9), 10);
+        assert!(!state.is_epoch_fully_updated(10));
+        state.on_stake_record_updated(&record(9), 10);
+        assert!(state.is_epoch_fully_updated(10));
+        // a record updated in the epoch, then deactivated, is no longer counted as updated
+        state.on_stake_record_deactivating(&record(10), 10);
+        assert!(state.is_epoch_fully_updated(10));
+        assert_eq!(state.updated_stake_count, 1);
+        // a split record cooling down from its creation
+        state.stake_system.stake_list.count += 1;
+        state.on_deactivating_stake_record_added(10);
+        assert!(state.is_epoch_fully_updated(10));
+        // update_deactivated removes them
+        state.stake_system.stake_list.count -= 3;
+        for _ in 0..3 {
+            state.on_deactivated_stake_record_removed(11);
+        }
+        assert_eq!(state.deactivating_stake_count, 0);
+        assert!(!state.is_epoch_fully_updated(11));
+        state.on_stake_record_updated(&record(10), 11);
+        assert!(state.is_epoch_fully_updated(11));
+    }
+
+    #[test]
+    fn test_circuit_breaker_keeps_manual_pause() {
+        let mut state = state(0);
+        state.paused_groups = State::PAUSE_DEPOSITS;
+        state.trip_circuit_breaker(
+            Default::default(),
+            CircuitBreaker::REASON_SLASHING,
+            5,
+            100,
+            90,
+        );
+        assert_eq!(
+            state.circuit_breaker.tripped_groups,
+            State::PAUSE_LIQUID_UNSTAKE | State::PAUSE_LIQ_POOL
+        );
+        // a later trip only holds the groups it paused itself
+        state.trip_circuit_breaker(
+            Default::default(),
+            CircuitBreaker::REASON_EXTERNAL_MINT,
+            5,
+            100,
+            110,
+        );
+        assert_eq!(state.circuit_breaker.reason, CircuitBreaker::REASON_SLASHING);
+        assert_eq!(
+            state.circuit_breaker.tripped_groups,
+            State::PAUSE_LIQUID_UNSTAKE | State::PAUSE_LIQ_POOL | State::PAUSE_DELAYED_UNSTAKE
+        );
+        state.reset_circuit_breaker();
+        assert!(!state.circuit_breaker.is_tripped());
+        assert_eq!(state.paused_groups, State::PAUSE_DEPOSITS);
+    }
+
+    #[test]
+    fn test_deposit_sol_swapped() {
+        let mut state = state(0);
+        // 2 lamports per staderSOL
+        state.available_reserve_balance = 2_000;
+        state.stader_sol_supply = 1_000;
+        let buy_order = state.calc_stader_sol_from_lamports(1_000 - 10).unwrap();
+        assert_eq!(buy_order, 495);
+        // fully filled from the LiqPool, the fee is left for the reserve
+        assert_eq!(state.deposit_sol_swapped(1_000, 10, 495, 495).unwrap(), 990);
+        assert_eq!(state.deposit_sol_swapped(1_000, 0, 500, 500).unwrap(), 1_000);
+        // partially filled, the fee is in the remainder with the minted part
+        assert_eq!(state.deposit_sol_swapped(1_000, 10, 495, 200).unwrap(), 400);
+    }
+
+    #[test]
+    fn test_rebalance_amount() {
+        const SOL: u64 = 1_000_000_000;
+        let mut state = state(0);
+        state.stake_system.min_stake = SOL;
+        state.validator_system.total_active_balance = 1_000 * SOL;
+        state.max_validator_stake_share = Fee::from_basis_points(1_000); // 100 SOL
+        state.max_stake_moved_per_epoch = Fee::from_basis_points(1_000); // 100 SOL
+        state.last_stake_move_epoch = 5;
+        state.stake_moved = 80 * SOL;
+
+        // the smaller gap
+        assert_eq!(
+            state.rebalance_amount(6, 10 * SOL, 20 * SOL, 0, 50 * SOL),
+            (10 * SOL, false)
+        );
+        // stake moves left in the epoch
+        assert_eq!(
+            state.rebalance_amount(5, 30 * SOL, 30 * SOL, 0, 50 * SOL),
+            (20 * SOL, false)
+        );
+        // dest validator max share
+        assert_eq!(
+            state.rebalance_amount(6, 30 * SOL, 30 * SOL, 95 * SOL, 50 * SOL),
+            (5 * SOL, false)
+        );
+        // the whole account rather than leaving less than min_stake in it
+        assert_eq!(
+            state.rebalance_amount(6, 30 * SOL, 30 * SOL, 0, 30 * SOL + SOL / 2),
+            (30 * SOL + SOL / 2, true)
+        );
+        // unless the caps forbid it, then min_stake is left
+        assert_eq!(
+            state.rebalance_amount(5, 30 * SOL, 30 * SOL, 0, 20 * SOL + SOL / 2),
+            (19 * SOL + SOL / 2, false)
+        );
+        // under min_stake, nothing to move
+        assert_eq!(
+            state.rebalance_amount(6, SOL / 2, 30 * SOL, 0, 50 * SOL),
+            (SOL / 2, false)
+        );
+    }
+
+    #[test]
+    fn test_reserve_stake_amount() {
+        const SOL: u64 = 1_000_000_000;
+        let mut state = state(0);
+        state.stake_system.min_stake = SOL;
+        state.validator_system.total_active_balance = 1_000 * SOL;
+        state.max_validator_stake_share = Fee::from_basis_points(1_000);
+
+        assert_eq!(state.reserve_stake_amount(10 * SOL, 50 * SOL, 0), 10 * SOL);
+        assert_eq!(state.reserve_stake_amount(50 * SOL, 10 * SOL, 0), 10 * SOL);
+        // the remainder would be under min_stake, take all of it
+        assert_eq!(
+            state.reserve_stake_amount(10 * SOL, 10 * SOL + SOL / 2, 0),
+            10 * SOL + SOL / 2
+        );
+        // but not over the max share of the new total: 10% of 1010.5 - 95
+        assert_eq!(
+            state.reserve_stake_amount(10 * SOL, 10 * SOL + SOL / 2, 95 * SOL),
+            6 * SOL + SOL / 20
+        );
+        // already at the max share
+        assert_eq!(state.reserve_stake_amount(10 * SOL, 50 * SOL, 110 * SOL), 0);
+    }
+
+    #[test]
+    fn test_take_crank_reward() {
+        let mut state = state(0);
+        state.crank_reward_lamports = 100;
+        state.max_crank_rewards_per_epoch = 250;
+        state.crank_reward_epoch = 4;
+        state.crank_rewards_paid_in_epoch = 250;
+        state.total_crank_rewards_paid = 1_000;
+        // the cap of the previous epoch does not carry over
+        assert_eq!(state.take_crank_reward(5, 1_000), 100);
+        assert_eq!(state.crank_reward_epoch, 5);
+        assert_eq!(state.take_crank_reward(5, 1_000), 100);
+        // the last reward of the epoch is cut to the cap, then nothing is paid
+        assert_eq!(state.take_crank_reward(5, 1_000), 50);
+        assert_eq!(state.take_crank_reward(5, 1_000), 0);
+        assert_eq!(state.crank_rewards_paid_in_epoch, 250);
+        // never more than the vault holds
+        assert_eq!(state.take_crank_reward(6, 30), 30);
+        assert_eq!(state.take_crank_reward(6, 0), 0);
+        assert_eq!(state.crank_rewards_paid_in_epoch, 30);
+        assert_eq!(state.total_crank_rewards_paid, 1_280);
+    }
+}
@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::StaderLiquidStakingError,
+    instructions::{ConfigLpParams, ConfigStaderParams},
+    state::{Fee, FeeCents},
+};
+
+/// Config changes announced by the admin and waiting for the timelock to pass.
+/// Anybody can apply them with execute_pending_config once
+/// clock.epoch >= earliest_execution_epoch
+#[account]
+#[derive(Debug)]
+pub struct PendingConfig {
+    pub state: Pubkey,
+    pub stader_params: ConfigStaderParams,
+    pub lp_params: ConfigLpParams,
+    /// epoch of the last queued change, the timelock restarts on every queue
+    pub queued_epoch: u64,
+    pub earliest_execution_epoch: u64,
+    /// authority that queued the pending changes. Only it can queue more until they are executed or canceled,
+    /// so one role can not push back the changes of another one
+    pub queued_by: Pubkey,
+}
+
+impl PendingConfig {
+    pub const SEED: &'static [u8] = b"pending_config";
+
+    /// space for the account with every param queued (Options take their max size)
+    pub fn serialized_len() -> usize {
+        let some_fee = Some(Fee::default());
+        let some_fee_cents = Some(FeeCents::default());
+        PendingConfig {
+            state: Pubkey::default(),
+            stader_params: ConfigStaderParams {
+                rewards_fee: some_fee,
+                slots_for_stake_delta: Some(0),
+                min_stake: Some(0),
+                min_deposit: Some(0),
+                min_withdraw: Some(0),
+                staking_sol_cap: Some(0),
+                liquidity_sol_cap: Some(0),
+                withdraw_stake_account_enabled: Some(false),
+                delayed_unstake_fee: some_fee_cents,
+                withdraw_stake_account_fee: some_fee_cents,
+                max_stake_moved_per_epoch: some_fee,
+                config_delay_epochs: Some(0),
+                ticket_wait_epochs: Some(0),
+                ticket_extra_wait_seconds: Some(0),
+                reserve_surplus_policy: Some(0),
+                max_validator_stake_share: some_fee,
+                max_validator_commission: Some(0),
+                delinquent_unstake_epochs: Some(0),
+                stale_price_grace_seconds: Some(0),
+                stale_price_fee: some_fee_cents,
+                crank_reward_lamports: Some(0),
+                max_crank_rewards_per_epoch: Some(0),
+                rebalance_tolerance: some_fee,
+                slashing_threshold: some_fee,
+                max_directed_stake_share: some_fee,
+            },
+            lp_params: ConfigLpParams {
+                min_fee: some_fee,
+                max_fee: some_fee,
+                liquidity_target: Some(0),
+                treasury_cut: some_fee,
+            },
+            queued_epoch: 0,
+            earliest_execution_epoch: 0,
+            queued_by: Pubkey::default(),
+        }
+        .try_to_vec()
+        .unwrap()
+        .len()
+            + 8
+    }
+
+    pub fn find_address(state: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[&state.to_bytes()[..32], Self::SEED], &crate::ID)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stader_params == ConfigStaderParams::default()
+            && self.lp_params == ConfigLpParams::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.stader_params = ConfigStaderParams::default();
+        self.lp_params = ConfigLpParams::default();
+        self.earliest_execution_epoch = u64::MAX; // never
+        self.queued_by = Pubkey::default();
+    }
+
+    /// Restarts the timelock for the changes queued by `authority`
+    pub fn restart_timelock(&mut self, authority: Pubkey, epoch: u64, delay_epochs: u64) -> Result<()> {
+        require!(
+            self.is_empty() || self.queued_by == authority,
+            StaderLiquidStakingError::PendingConfigQueuedByOtherAuthority
+        );
+        self.queued_by = authority;
+        self.queued_epoch = epoch;
+        self.earliest_execution_epoch = epoch + delay_epochs;
+        Ok(())
+    }
+}
@@ -152,6 +152,7 @@ impl StakeSystem {
         self.stake_list.item_size()
     }
 
+    /// Returns the index of the new record, it may reuse the slot of a removed one
     pub fn add(
         &mut self,
         stake_list_data: &mut [u8],
@@ -159,7 +160,7 @@ impl StakeSystem {
         delegated_lamports: u64,
         clock: &Clock,
         is_emergency_unstaking: u8,
-    ) -> Result<()> {
+    ) -> Result<u32> {
         self.stake_list
             .push(
                 stake_list_data,
@@ -170,8 +171,7 @@ impl StakeSystem {
                     is_emergency_unstaking,
                 ),
             )
-            .map_err(|e| e.with_account_name("stake_list"))?;
-        Ok(())
+            .map_err(|e| e.with_account_name("stake_list"))
     }
 
     fn get(&self, stake_list_data: &[u8], index: u32) -> Result<StakeRecord> {
@@ -201,9 +201,29 @@ impl StakeSystem {
             .set(stake_list_data, index, stake)
             .map_err(|e| e.with_account_name("stake_list"))
     }
+    /// Keeps the indices of the other stake accounts stable, see compact_lists
     pub fn remove(&mut self, stake_list_data: &mut [u8], index: u32) -> Result<()> {
         self.stake_list
-            .remove(stake_list_data, index)
+            .tombstone(stake_list_data, index)
             .map_err(|e| e.with_account_name("stake_list"))
     }
+
+    /// `index`, or the index of `stake_account` when `index` is List::FIND_BY_KEY
+    pub fn resolve_index(
+        &self,
+        stake_list_data: &[u8],
+        index: u32,
+        stake_account: &Pubkey,
+    ) -> Result<u32> {
+        if index != List::FIND_BY_KEY {
+            return Ok(index);
+        }
+        self.stake_list
+            .find(stake_list_data, stake_account)
+            .ok_or_else(|| error!(StaderLiquidStakingError::WrongStakeAccountOrIndex))
+    }
+
+    pub fn removed_stake_count(&self) -> u32 {
+        self.stake_list.tombstones
+    }
 }
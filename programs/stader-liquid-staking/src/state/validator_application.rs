@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+/// Request by a validator to be added to the validator list, see apply_validator.
+/// Closed by approve_validator_application, reject_validator_application
+/// or cancel_validator_application, returning its rent to rent_payer
+#[account]
+#[derive(Debug)]
+pub struct ValidatorApplication {
+    pub state: Pubkey,
+    pub validator_vote: Pubkey,
+    /// authorized withdrawer or node identity of the vote account
+    pub applicant: Pubkey,
+    pub rent_payer: Pubkey,
+    // vote account data at the time of application
+    pub node_pubkey: Pubkey,
+    pub commission: u8,
+    pub last_epoch_credits: u64,
+    pub applied_epoch: u64,
+}
+
+impl ValidatorApplication {
+    pub const SEED: &'static [u8] = b"validator_application";
+    pub const LEN: usize = 8 + 32 * 5 + 1 + 8 + 8;
+
+    pub fn find_address(state: &Pubkey, validator_vote: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                &state.to_bytes()[..32],
+                Self::SEED,
+                &validator_vote.to_bytes()[..32],
+            ],
+            &crate::ID,
+        )
+    }
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+/// Exists while the vote account is blocklisted.
+/// add_validator and deposit_stake_account refuse validators having this account
+#[account]
+#[derive(Debug)]
+pub struct ValidatorBlocklistEntry {
+    pub state: Pubkey,
+    pub validator_vote: Pubkey,
+    pub reason: u8, // ValidatorBlocklistEntry::REASON_*
+    pub blocked_epoch: u64,
+}
+
+impl ValidatorBlocklistEntry {
+    pub const SEED: &'static [u8] = b"blocklist";
+    pub const LEN: usize = 8 + 32 + 32 + 1 + 8;
+
+    pub const REASON_OTHER: u8 = 0;
+    pub const REASON_DELINQUENT: u8 = 1;
+    pub const REASON_HIGH_COMMISSION: u8 = 2;
+    pub const REASON_MALICIOUS: u8 = 3;
+    pub const MAX_REASON: u8 = Self::REASON_MALICIOUS;
+
+    pub fn find_address(state: &Pubkey, validator_vote: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                &state.to_bytes()[..32],
+                Self::SEED,
+                &validator_vote.to_bytes()[..32],
+            ],
+            &crate::ID,
+        )
+    }
+}
@@ -3,7 +3,7 @@
 use crate::{calc::proportional, error::StaderLiquidStakingError, ID};
 use anchor_lang::{prelude::*, Discriminator};
 
-use super::list::List;
+use super::{list::List, vote_account::VoteAccountSummary, Fee};
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
 pub struct ValidatorRecord {
@@ -73,6 +73,52 @@ impl ValidatorRecord {
     }
 }
 
+/// Extension of ValidatorRecord stored in the additional record space of the validator list.
+/// Lists whose records are too small have no metadata, see migrate_validator_list
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct ValidatorMetadata {
+    /// Validator identity, default when unknown
+    pub node_pubkey: Pubkey,
+    /// u64::MAX when unknown (added before the metadata existed)
+    pub added_epoch: u64,
+    /// Commission seen the last time the vote account was read, percent
+    pub last_commission: u8,
+    /// Staking and MEV rewards collected by update_active from this validator's stake accounts
+    pub cumulative_rewards: u64,
+    pub flags: u8, // ValidatorMetadata::FLAG_*
+}
+
+impl ValidatorMetadata {
+    pub const LEN: u32 = 32 + 8 + 1 + 8 + 1;
+
+    /// last_commission was observed, 0 can be a real commission
+    pub const FLAG_COMMISSION_OBSERVED: u8 = 1 << 0;
+    /// added through approve_validator_application
+    pub const FLAG_FROM_APPLICATION: u8 = 1 << 1;
+    /// score set by the manager, rescore_validator keeps it until release_validator_score
+    pub const FLAG_MANUAL_SCORE: u8 = 1 << 2;
+
+    /// Defaults for records created before the metadata existed
+    pub fn unknown() -> Self {
+        Self {
+            added_epoch: std::u64::MAX,
+            ..Default::default()
+        }
+    }
+
+    pub fn observe_commission(&mut self, commission: u8) {
+        self.last_commission = commission;
+        self.flags |= Self::FLAG_COMMISSION_OBSERVED;
+    }
+}
+
+// borsh layout of a record with metadata, the metadata just follows the record fields
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct ValidatorRecordWithMetadata {
+    record: ValidatorRecord,
+    metadata: ValidatorMetadata,
+}
+
 #[derive(Debug, Clone, AnchorSerialize, AnchorDeserialize)]
 pub struct ValidatorList {}
 
@@ -121,6 +167,42 @@ pub struct ValidatorSystem {
     pub auto_add_validator_enabled: u8,
 }
 
+/// Parameters of the on-chain scoring done by rescore_validator.
+/// score = average credits of the last `credits_epochs` completed epochs, in per-mille of
+/// MAX_CREDITS_PER_EPOCH, * (100 - commission) / 100.
+/// 0 when the commission is above `max_commission` or the last vote is older than `max_delinquent_slots`.
+/// Scores set by the manager share total_validator_score, they should use the same scale
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct ValidatorScoringConfig {
+    /// rescore_validator is refused while disabled, scores come only from the manager then
+    pub enabled: bool,
+    /// percent
+    pub max_commission: u8,
+    pub max_delinquent_slots: u64,
+    pub credits_epochs: u8,
+}
+
+impl ValidatorScoringConfig {
+    pub const MAX_CREDITS_EPOCHS: u8 = 10;
+    /// 16 timely vote credits for each slot of a 432_000 slots epoch
+    pub const MAX_CREDITS_PER_EPOCH: u64 = 16 * 432_000;
+    /// score of a validator earning MAX_CREDITS_PER_EPOCH without commission
+    pub const MAX_SCORE: u64 = 1_000;
+
+    pub fn validate(&self) -> Result<()> {
+        require_gte!(
+            100,
+            self.max_commission,
+            StaderLiquidStakingError::InvalidValidatorScoringConfig
+        );
+        require!(
+            self.credits_epochs > 0 && self.credits_epochs <= Self::MAX_CREDITS_EPOCHS,
+            StaderLiquidStakingError::InvalidValidatorScoringConfig
+        );
+        Ok(())
+    }
+}
+
 impl ValidatorSystem {
     pub fn bytes_for_list(count: u32, additional_record_space: u32) -> u32 {
         List::bytes_for(
@@ -167,6 +249,103 @@ impl ValidatorSystem {
         self.validator_list.item_size()
     }
 
+    pub fn base_record_size() -> u32 {
+        ValidatorRecord::default().try_to_vec().unwrap().len() as u32
+    }
+
+    /// Record size needed to store ValidatorMetadata
+    pub fn record_size_with_metadata() -> u32 {
+        Self::base_record_size() + ValidatorMetadata::LEN
+    }
+
+    /// Replaces a validator score in total_validator_score.
+    /// Scores set by the manager can overflow u32 in total, that fails instead of panicking
+    pub fn replace_score(&mut self, old_score: u32, new_score: u32) -> Result<()> {
+        self.total_validator_score = u32::try_from(
+            self.total_validator_score as u64 - old_score as u64 + new_score as u64,
+        )
+        .map_err(|_| error!(StaderLiquidStakingError::CalculationFailure))?;
+        Ok(())
+    }
+
+    pub fn has_metadata(&self) -> bool {
+        self.validator_record_size() >= Self::record_size_with_metadata()
+    }
+
+    pub fn get_metadata(
+        &self,
+        validator_list_data: &[u8],
+        index: u32,
+    ) -> Result<Option<ValidatorMetadata>> {
+        if !self.has_metadata() {
+            return Ok(None);
+        }
+        let item: ValidatorRecordWithMetadata = self
+            .validator_list
+            .get(validator_list_data, index)
+            .map_err(|e| e.with_account_name("validator_list"))?;
+        Ok(Some(item.metadata))
+    }
+
+    /// Does nothing when the list has no room for metadata
+    pub fn update_metadata<F: FnOnce(&mut ValidatorMetadata)>(
+        &self,
+        validator_list_data: &mut [u8],
+        index: u32,
+        f: F,
+    ) -> Result<()> {
+        if !self.has_metadata() {
+            return Ok(());
+        }
+        let mut item: ValidatorRecordWithMetadata = self
+            .validator_list
+            .get(validator_list_data, index)
+            .map_err(|e| e.with_account_name("validator_list"))?;
+        f(&mut item.metadata);
+        self.validator_list
+            .set(validator_list_data, index, item)
+            .map_err(|e| e.with_account_name("validator_list"))
+    }
+
+    /// Grows every record in place to record_size_with_metadata() filling ValidatorMetadata::unknown().
+    /// The list data must already be large enough for the current count
+    pub fn migrate_to_metadata(&mut self, validator_list_data: &mut [u8]) -> Result<()> {
+        let old_size = self.validator_record_size() as usize;
+        let new_size = Self::record_size_with_metadata() as usize;
+        require_gt!(
+            new_size,
+            old_size,
+            StaderLiquidStakingError::ValidatorListAlreadyMigrated
+        );
+        let count = self.validator_count() as usize;
+        require_gte!(
+            validator_list_data.len(),
+            8 + count * new_size,
+            StaderLiquidStakingError::ListOverflow
+        );
+        let base_size = Self::base_record_size() as usize;
+        let mut metadata = Vec::with_capacity(new_size - base_size);
+        ValidatorMetadata::unknown().serialize(&mut metadata)?;
+        metadata.resize(new_size - base_size, 0);
+        // backwards, so records are not overwritten before being moved
+        for index in (0..count).rev() {
+            let old_start = 8 + index * old_size;
+            let new_start = 8 + index * new_size;
+            let is_tombstone = self
+                .validator_list
+                .is_tombstone(validator_list_data, index as u32)?;
+            validator_list_data.copy_within(old_start..old_start + base_size, new_start);
+            let new_metadata = &mut validator_list_data[new_start + base_size..new_start + new_size];
+            if is_tombstone {
+                new_metadata.fill(0);
+            } else {
+                new_metadata.copy_from_slice(&metadata);
+            }
+        }
+        self.validator_list.item_size = new_size as u32;
+        Ok(())
+    }
+
     pub fn add(
         &mut self,
         validator_list_data: &mut [u8],
@@ -174,15 +353,13 @@ impl ValidatorSystem {
         score: u32,
         state: &Pubkey,
         duplication_flag_address: &Pubkey,
-    ) -> Result<()> {
-        self.validator_list
-            .push(
-                validator_list_data,
-                ValidatorRecord::new(validator_account, score, state, duplication_flag_address)?,
-            )
-            .map_err(|e| e.with_account_name("validator_list"))?;
-        self.total_validator_score += score;
-        Ok(())
+        metadata: ValidatorMetadata,
+    ) -> Result<u32> {
+        let record =
+            ValidatorRecord::new(validator_account, score, state, duplication_flag_address)?;
+        let index = self.push_record(validator_list_data, record, metadata)?;
+        self.replace_score(0, score)?;
+        Ok(index)
     }
 
     pub fn add_with_balance(
@@ -193,15 +370,36 @@ impl ValidatorSystem {
         balance: u64,
         state: &Pubkey,
         duplication_flag_address: &Pubkey,
-    ) -> Result<()> {
+    ) -> Result<u32> {
         let mut validator =
             ValidatorRecord::new(validator_account, score, state, duplication_flag_address)?;
         validator.active_balance = balance;
-        self.validator_list
-            .push(validator_list_data, validator)
-            .map_err(|e| e.with_account_name("validator_list"))?;
-        self.total_validator_score += score;
-        Ok(())
+        let index =
+            self.push_record(validator_list_data, validator, ValidatorMetadata::unknown())?;
+        self.replace_score(0, score)?;
+        Ok(index)
+    }
+
+    // the metadata is always written, the slot may contain a removed record.
+    // Returns the index of the record, see List::push
+    fn push_record(
+        &mut self,
+        validator_list_data: &mut [u8],
+        record: ValidatorRecord,
+        metadata: ValidatorMetadata,
+    ) -> Result<u32> {
+        if self.has_metadata() {
+            self.validator_list
+                .push(
+                    validator_list_data,
+                    ValidatorRecordWithMetadata { record, metadata },
+                )
+                .map_err(|e| e.with_account_name("validator_list"))
+        } else {
+            self.validator_list
+                .push(validator_list_data, record)
+                .map_err(|e| e.with_account_name("validator_list"))
+        }
     }
 
     pub fn remove(
@@ -218,8 +416,9 @@ impl ValidatorSystem {
 
         self.total_validator_score -= record.score;
 
+        // keep the indices of the other validators stable, see compact_lists
         self.validator_list
-            .remove(validator_list_data, index)
+            .tombstone(validator_list_data, index)
             .map_err(|e| e.with_account_name("validator_list"))?;
 
         Ok(())
@@ -247,6 +446,25 @@ impl ValidatorSystem {
         Ok(validator_record)
     }
 
+    /// `index`, or the index of `validator_account` when `index` is List::FIND_BY_KEY
+    pub fn resolve_index(
+        &self,
+        validator_list_data: &[u8],
+        index: u32,
+        validator_account: &Pubkey,
+    ) -> Result<u32> {
+        if index != List::FIND_BY_KEY {
+            return Ok(index);
+        }
+        self.validator_list
+            .find(validator_list_data, validator_account)
+            .ok_or_else(|| error!(StaderLiquidStakingError::WrongValidatorAccountOrIndex))
+    }
+
+    pub fn removed_validator_count(&self) -> u32 {
+        self.validator_list.tombstones
+    }
+
     // Do not forget to update totals
     pub fn set(
         &self,
@@ -259,18 +477,234 @@ impl ValidatorSystem {
             .map_err(|e| e.with_account_name("validator_list"))
     }
 
+    pub fn compute_score(
+        config: &ValidatorScoringConfig,
+        vote: &VoteAccountSummary,
+        clock: &Clock,
+    ) -> u32 {
+        if vote.commission > config.max_commission {
+            return 0;
+        }
+        match vote.last_vote_slot {
+            Some(last_vote_slot)
+                if clock.slot.saturating_sub(last_vote_slot) <= config.max_delinquent_slots => {}
+            _ => return 0, // delinquent
+        }
+        // completed epochs only, the current one is still accumulating credits
+        let credits: u64 = (1..=config.credits_epochs as u64)
+            .filter(|back| *back <= clock.epoch)
+            .map(|back| vote.credits_in_epoch(clock.epoch - back))
+            .sum();
+        let average_credits = (credits / config.credits_epochs as u64)
+            .min(ValidatorScoringConfig::MAX_CREDITS_PER_EPOCH);
+        // bounded by MAX_SCORE, so total_validator_score can't overflow with the computed scores
+        let score = average_credits
+            * ValidatorScoringConfig::MAX_SCORE
+            * (100 - vote.commission.min(100) as u64)
+            / (ValidatorScoringConfig::MAX_CREDITS_PER_EPOCH * 100);
+        score as u32
+    }
+
+    /// directed_lamports + (total_stake_target - honored_directed_lamports) * score / total_score,
+    /// clamped to max_stake_share of total_stake_target.
+    /// honored_directed_lamports is State::honored_directed_lamports, stake directed to
+    /// zero score validators goes back to the score pool
     pub fn validator_stake_target(
         &self,
         validator: &ValidatorRecord,
         total_stake_target: u64,
+        max_stake_share: Fee,
+        directed_lamports: u64,
+        honored_directed_lamports: u64,
     ) -> Result<u64> {
-        if self.total_validator_score == 0 {
-            return Ok(0);
+        let score_target = if self.total_validator_score == 0 {
+            0
+        } else {
+            proportional(
+                total_stake_target.saturating_sub(honored_directed_lamports),
+                validator.score as u64,
+                self.total_validator_score as u64,
+            )?
+        };
+        Ok((directed_lamports + score_target).min(max_stake_share.apply(total_stake_target)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::*;
+
+    use super::{
+        ValidatorMetadata, ValidatorRecord, ValidatorScoringConfig, ValidatorSystem,
+        VoteAccountSummary,
+    };
+    use crate::state::fee::Fee;
+
+    #[test]
+    fn test_migrate_to_metadata() -> Result<()> {
+        let state = Pubkey::new_unique();
+        let count = 3;
+        let mut data =
+            vec![0u8; ValidatorSystem::bytes_for_list(count, ValidatorMetadata::LEN) as usize];
+        let mut system =
+            ValidatorSystem::new(Pubkey::new_unique(), &mut data, Pubkey::new_unique(), 0)?;
+        assert!(!system.has_metadata());
+
+        let mut records = vec![];
+        for score in 0..count {
+            let validator = Pubkey::new_unique();
+            let (duplication_flag, _) = ValidatorRecord::find_duplication_flag(&state, &validator);
+            system.add(
+                &mut data,
+                validator,
+                score,
+                &state,
+                &duplication_flag,
+                ValidatorMetadata::default(),
+            )?;
+            records.push(system.get(&data, score)?);
         }
-        proportional(
-            total_stake_target,
-            validator.score as u64,
-            self.total_validator_score as u64,
-        )
+        assert_eq!(system.get_metadata(&data, 0)?, None);
+
+        system.migrate_to_metadata(&mut data)?;
+        assert!(system.has_metadata());
+        for (index, record) in records.iter().enumerate() {
+            assert_eq!(system.get(&data, index as u32)?, *record);
+            assert_eq!(
+                system.get_metadata(&data, index as u32)?,
+                Some(ValidatorMetadata::unknown())
+            );
+        }
+        assert!(system.migrate_to_metadata(&mut data).is_err());
+
+        system.update_metadata(&mut data, 1, |metadata| metadata.observe_commission(5))?;
+        assert_eq!(system.get(&data, 1)?, records[1]);
+        assert_eq!(system.get_metadata(&data, 1)?.unwrap().last_commission, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_validator_stake_target() -> Result<()> {
+        const SOL: u64 = 1_000_000_000;
+        let mut data = vec![0u8; ValidatorSystem::bytes_for_list(1, 0) as usize];
+        let mut system =
+            ValidatorSystem::new(Pubkey::new_unique(), &mut data, Pubkey::new_unique(), 0)?;
+        system.total_validator_score = 100;
+        let validator = ValidatorRecord {
+            score: 50,
+            ..Default::default()
+        };
+        let target = |max_stake_share, directed_lamports, honored_directed_lamports| {
+            system.validator_stake_target(
+                &validator,
+                1_000 * SOL,
+                Fee::from_basis_points(max_stake_share),
+                directed_lamports,
+                honored_directed_lamports,
+            )
+        };
+
+        // the score share of what is not directed, plus its own directed stake
+        assert_eq!(target(10_000, 0, 0)?, 500 * SOL);
+        assert_eq!(target(10_000, 30 * SOL, 200 * SOL)?, 430 * SOL);
+        // never over the max share
+        assert_eq!(target(1_000, 0, 0)?, 100 * SOL);
+        assert_eq!(target(1_000, 30 * SOL, 200 * SOL)?, 100 * SOL);
+
+        // no score, only the directed stake
+        system.total_validator_score = 0;
+        let target = system.validator_stake_target(
+            &validator,
+            1_000 * SOL,
+            Fee::from_basis_points(1_000),
+            30 * SOL,
+            200 * SOL,
+        )?;
+        assert_eq!(target, 30 * SOL);
+        Ok(())
+    }
+
+    #[test]
+    fn test_compute_score() {
+        let config = ValidatorScoringConfig {
+            enabled: true,
+            max_commission: 10,
+            max_delinquent_slots: 100,
+            credits_epochs: 2,
+        };
+        let clock = Clock {
+            slot: 1_000,
+            epoch: 10,
+            ..Default::default()
+        };
+        let max_credits = ValidatorScoringConfig::MAX_CREDITS_PER_EPOCH;
+        let vote = VoteAccountSummary {
+            commission: 0,
+            last_vote_slot: Some(950),
+            // the current epoch is not counted
+            epoch_credits: vec![
+                (8, max_credits, 0),
+                (9, 2 * max_credits, max_credits / 2),
+                (10, 3 * max_credits, 2 * max_credits),
+            ],
+            ..Default::default()
+        };
+        // (1 + 3/2) / 2 of the max credits, capped at the max
+        assert_eq!(ValidatorSystem::compute_score(&config, &vote, &clock), 1_000);
+        let vote = VoteAccountSummary {
+            epoch_credits: vec![(8, max_credits / 2, 0), (9, max_credits, max_credits / 2)],
+            ..vote
+        };
+        assert_eq!(ValidatorSystem::compute_score(&config, &vote, &clock), 500);
+
+        // commission reduces the score up to the cap, 0 over it
+        let commission = |commission| VoteAccountSummary {
+            commission,
+            ..vote.clone()
+        };
+        assert_eq!(
+            ValidatorSystem::compute_score(&config, &commission(10), &clock),
+            450
+        );
+        assert_eq!(
+            ValidatorSystem::compute_score(&config, &commission(11), &clock),
+            0
+        );
+
+        // delinquent
+        let last_vote = |last_vote_slot| VoteAccountSummary {
+            last_vote_slot,
+            ..vote.clone()
+        };
+        assert_eq!(
+            ValidatorSystem::compute_score(&config, &last_vote(Some(900)), &clock),
+            500
+        );
+        assert_eq!(
+            ValidatorSystem::compute_score(&config, &last_vote(Some(899)), &clock),
+            0
+        );
+        assert_eq!(
+            ValidatorSystem::compute_score(&config, &last_vote(None), &clock),
+            0
+        );
+
+        // missing epochs count as no credits
+        let vote = VoteAccountSummary {
+            epoch_credits: vec![(9, max_credits / 2, 0)],
+            ..vote
+        };
+        assert_eq!(ValidatorSystem::compute_score(&config, &vote, &clock), 250);
+        // epochs before the first one don't exist
+        let clock = Clock {
+            epoch: 1,
+            slot: 1_000,
+            ..Default::default()
+        };
+        let vote = VoteAccountSummary {
+            epoch_credits: vec![(0, max_credits, 0)],
+            ..vote
+        };
+        assert_eq!(ValidatorSystem::compute_score(&config, &vote, &clock), 500);
     }
 }
@@ -0,0 +1,205 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::clock::Epoch;
+
+use crate::error::StaderLiquidStakingError;
+
+/// The part of a vote account the program uses for validator scoring.
+/// VoteState::deserialize is not available on-chain, so the bincode layout is walked manually
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VoteAccountSummary {
+    pub node_pubkey: Pubkey,
+    pub authorized_withdrawer: Pubkey,
+    pub commission: u8,
+    pub last_vote_slot: Option<u64>,
+    /// (epoch, credits, prev_credits), oldest first
+    pub epoch_credits: Vec<(Epoch, u64, u64)>,
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        require_gte!(
+            self.data.len(),
+            len,
+            StaderLiquidStakingError::InvalidVoteAccount
+        );
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<()> {
+        self.take(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn pubkey(&mut self) -> Result<Pubkey> {
+        Ok(Pubkey::new_from_array(self.take(32)?.try_into().unwrap()))
+    }
+
+    fn len(&mut self) -> Result<usize> {
+        usize::try_from(self.u64()?)
+            .map_err(|_| error!(StaderLiquidStakingError::InvalidVoteAccount))
+    }
+}
+
+impl VoteAccountSummary {
+    // VoteStateVersions variants
+    const VERSION_1_14_11: u32 = 1;
+    const VERSION_CURRENT: u32 = 2;
+
+    const LOCKOUT_LEN: usize = 8 + 4; // slot, confirmation_count
+    const LANDED_VOTE_LEN: usize = 1 + Self::LOCKOUT_LEN; // latency, lockout
+    const AUTHORIZED_VOTER_LEN: usize = 8 + 32; // epoch, pubkey
+    const PRIOR_VOTERS_LEN: usize = 32 * (32 + 8 + 8) + 8 + 1; // buf, idx, is_empty
+
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let mut reader = Reader { data };
+        let vote_len = match reader.u32()? {
+            Self::VERSION_1_14_11 => Self::LOCKOUT_LEN,
+            Self::VERSION_CURRENT => Self::LANDED_VOTE_LEN,
+            _ => return err!(StaderLiquidStakingError::InvalidVoteAccount),
+        };
+        let node_pubkey = reader.pubkey()?;
+        let authorized_withdrawer = reader.pubkey()?;
+        let commission = reader.u8()?;
+
+        let votes = reader.len()?;
+        let last_vote_slot = if votes > 0 {
+            reader.skip((votes - 1) * vote_len)?;
+            // the lockout slot is the last field before confirmation_count
+            let last_vote = reader.take(vote_len)?;
+            let slot_offset = vote_len - Self::LOCKOUT_LEN;
+            Some(u64::from_le_bytes(
+                last_vote[slot_offset..slot_offset + 8].try_into().unwrap(),
+            ))
+        } else {
+            None
+        };
+
+        // root_slot: Option<Slot>
+        if reader.u8()? != 0 {
+            reader.skip(8)?;
+        }
+        let authorized_voters = reader.len()?;
+        reader.skip(authorized_voters * Self::AUTHORIZED_VOTER_LEN)?;
+        reader.skip(Self::PRIOR_VOTERS_LEN)?;
+
+        let epoch_credits_len = reader.len()?;
+        // every entry is 24 bytes, check before allocating
+        require_gte!(
+            reader.data.len() / 24,
+            epoch_credits_len,
+            StaderLiquidStakingError::InvalidVoteAccount
+        );
+        let mut epoch_credits = Vec::with_capacity(epoch_credits_len);
+        for _ in 0..epoch_credits_len {
+            epoch_credits.push((reader.u64()?, reader.u64()?, reader.u64()?));
+        }
+
+        Ok(Self {
+            node_pubkey,
+            authorized_withdrawer,
+            commission,
+            last_vote_slot,
+            epoch_credits,
+        })
+    }
+
+    /// Credits earned during `epoch`, 0 if the validator did not vote in it
+    pub fn credits_in_epoch(&self, epoch: Epoch) -> u64 {
+        self.epoch_credits
+            .iter()
+            .rev()
+            .find(|(credits_epoch, _, _)| *credits_epoch == epoch)
+            .map(|(_, credits, prev_credits)| credits.saturating_sub(*prev_credits))
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::*;
+    use anchor_lang::solana_program::vote::state::{
+        VoteInit, VoteState, VoteState1_14_11, VoteStateVersions,
+    };
+
+    use super::VoteAccountSummary;
+
+    fn vote_state() -> VoteState {
+        let mut vote_state = VoteState::new(
+            &VoteInit {
+                node_pubkey: Pubkey::new_unique(),
+                authorized_voter: Pubkey::new_unique(),
+                authorized_withdrawer: Pubkey::new_unique(),
+                commission: 7,
+            },
+            &Clock::default(),
+        );
+        for slot in 1..40 {
+            vote_state.process_next_vote_slot(slot, 0, slot, false, false);
+        }
+        vote_state.increment_credits(10, 100);
+        vote_state.increment_credits(11, 250);
+        vote_state
+    }
+
+    fn check(vote_state: &VoteState, versioned: VoteStateVersions) -> Result<()> {
+        let mut data = vec![0u8; VoteState::size_of()];
+        VoteState::serialize(&versioned, &mut data).unwrap();
+        let summary = VoteAccountSummary::parse(&data)?;
+        assert_eq!(summary.node_pubkey, vote_state.node_pubkey);
+        assert_eq!(
+            summary.authorized_withdrawer,
+            vote_state.authorized_withdrawer
+        );
+        assert_eq!(summary.commission, 7);
+        assert_eq!(summary.last_vote_slot, vote_state.last_voted_slot());
+        assert_eq!(&summary.epoch_credits, vote_state.epoch_credits());
+        assert_eq!(summary.credits_in_epoch(10), 100);
+        assert_eq!(summary.credits_in_epoch(11), 250);
+        assert_eq!(summary.credits_in_epoch(12), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_current() -> Result<()> {
+        let vote_state = vote_state();
+        check(
+            &vote_state,
+            VoteStateVersions::new_current(vote_state.clone()),
+        )
+    }
+
+    #[test]
+    fn test_parse_1_14_11() -> Result<()> {
+        let vote_state = vote_state();
+        check(
+            &vote_state,
+            VoteStateVersions::V1_14_11(Box::new(VoteState1_14_11::from(vote_state.clone()))),
+        )
+    }
+
+    #[test]
+    fn test_parse_truncated() {
+        let vote_state = vote_state();
+        let mut data = vec![0u8; VoteState::size_of()];
+        VoteState::serialize(&VoteStateVersions::new_current(vote_state), &mut data).unwrap();
+        assert!(VoteAccountSummary::parse(&data[..200]).is_err());
+        assert!(VoteAccountSummary::parse(&[0u8; 4]).is_err());
+    }
+}
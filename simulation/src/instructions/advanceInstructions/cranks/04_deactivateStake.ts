@@ -1,6 +1,7 @@
 
 import { 
     Connection, 
+    ParsedAccountData, 
     PublicKey, 
     sendAndConfirmTransaction, 
     Signer, 
@@ -29,6 +30,11 @@ export const deactivateStake = async (connection: Connection, stakeAccount: Publ
         splitStakeAccount,
     } = deactivateStakeParam
 
+    // the stake target of the validator counts the stake directed to it
+    const stakeAccountInfo = await connection.getParsedAccountInfo(stakeAccount)
+    const validatorVote = new PublicKey((stakeAccountInfo.value?.data as ParsedAccountData).parsed.info.stake.delegation.voter)
+    const [directedStake] = PublicKey.findProgramAddressSync([stateAccount.toBuffer(), Buffer.from("directed_stake"), validatorVote.toBuffer()], program.programId)
+
     const tx = await program.methods.deactivateStake(stakeIndex, validatorIndex)
         .accounts({
             state: stateAccount,
@@ -41,7 +47,8 @@ export const deactivateStake = async (connection: Connection, stakeAccount: Publ
             splitStakeRentPayer: cranker.publicKey,
             epochSchedule: SYSVAR_EPOCH_SCHEDULE_PUBKEY,
             stakeHistory: SYSVAR_STAKE_HISTORY_PUBKEY,
-            stakeProgram: StakeProgram.programId
+            stakeProgram: StakeProgram.programId,
+            directedStake: directedStake,
         })
         .signers([cranker])
         .transaction()
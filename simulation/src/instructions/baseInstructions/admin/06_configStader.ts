@@ -1,6 +1,7 @@
 
-import { Connection, sendAndConfirmTransaction, Signer } from "@solana/web3.js";
+import { Connection, PublicKey, sendAndConfirmTransaction, Signer, SYSVAR_CLOCK_PUBKEY } from "@solana/web3.js";
 import { 
+    contractAddr,
     programDevnet as program,
     // program,
     stateAccount,
@@ -8,10 +9,21 @@ import {
 import { ConfigStaderParam } from "../../../types";
 
 export const configStader = async (connection: Connection, admin: Signer, configStaderParam: ConfigStaderParam) => {
+    // optional accounts, passed once init_pending_config / init_config_history have run.
+    // The pending config is needed only to queue timelocked params
+    const existingPda = async (seed: string) => {
+        const [address] = PublicKey.findProgramAddressSync([stateAccount.toBuffer(), Buffer.from(seed)], contractAddr)
+        return (await connection.getAccountInfo(address)) !== null ? address : null
+    }
+
     const tx = await program.methods.configStader(configStaderParam)
         .accounts({
             state: stateAccount,
-            adminAuthority: admin.publicKey,
+            // admin, fee or limits authority, depending on the params
+            authority: admin.publicKey,
+            pendingConfig: await existingPda("pending_config"),
+            clock: SYSVAR_CLOCK_PUBKEY,
+            configHistory: await existingPda("config_history"),
         })
         .signers([admin])
         .transaction()
@@ -35,6 +35,8 @@ export const depositNewStakeAccount = async (
     const validatorVote = voteAccount[validatorIndex];
 
     const [duplicationFlag] = PublicKey.findProgramAddressSync([stateAccount.toBuffer(), Buffer.from("unique_validator"), validatorVote.toBuffer()], program.programId)
+    // must not exist, blocklisted validators get no deposits
+    const [blocklistEntry] = PublicKey.findProgramAddressSync([stateAccount.toBuffer(), Buffer.from("blocklist"), validatorVote.toBuffer()], program.programId)
 
     const stakeTx = new Transaction()
 
@@ -89,7 +91,10 @@ export const depositNewStakeAccount = async (
                 staderSolMint: staderSolMint,
                 mintTo: userStaderSolTokenAccount,
                 staderSolMintAuthority: authorityStaderSolAcc,
-                stakeProgram: StakeProgram.programId
+                stakeProgram: StakeProgram.programId,
+                // the vote account the stake is delegated to
+                validatorVote: validatorVote,
+                blocklistEntry: blocklistEntry,
             })
             .signers([user])
             .transaction()
@@ -36,6 +36,8 @@ export const depositExistingStakeAccount = async (
     const validatorVote = voteAccount[validatorIndex];
 
     const [duplicationFlag] = PublicKey.findProgramAddressSync([stateAccount.toBuffer(), Buffer.from("unique_validator"), validatorVote.toBuffer()], program.programId)
+    // must not exist, blocklisted validators get no deposits
+    const [blocklistEntry] = PublicKey.findProgramAddressSync([stateAccount.toBuffer(), Buffer.from("blocklist"), validatorVote.toBuffer()], program.programId)
     
     const tx = new Transaction()
     
@@ -59,7 +61,10 @@ export const depositExistingStakeAccount = async (
                 staderSolMint: staderSolMint,
                 mintTo: userStaderSolTokenAccount,
                 staderSolMintAuthority: authorityStaderSolAcc,
-                stakeProgram: StakeProgram.programId
+                stakeProgram: StakeProgram.programId,
+                // the vote account the stake is delegated to
+                validatorVote: validatorVote,
+                blocklistEntry: blocklistEntry,
             })
             .signers([user])
             .transaction()